@@ -0,0 +1,397 @@
+use eframe::egui;
+use encoding_rs::Encoding;
+use std::path::{Path, PathBuf};
+use crate::file_index::{FileIndex, RankedFile};
+use crate::fuzzy::fuzzy_match;
+use crate::ui::commands::Command;
+use crate::ui::keymap::Keymap;
+
+/// 命令面板选中条目后产生的动作
+#[derive(Clone)]
+pub enum PaletteAction {
+    Run(Command),
+    OpenFile(PathBuf),
+    OpenFolder(PathBuf),
+    SaveFileAs(PathBuf),
+    SetDarkMode(bool),
+    SetEncoding(&'static Encoding),
+}
+
+/// 路径输入的用途
+#[derive(Clone, Copy, PartialEq)]
+pub enum PathPurpose {
+    Open,
+    OpenFolder,
+    SaveAs,
+}
+
+/// 面板当前的工作模式
+enum PaletteMode {
+    Commands,                                                 // 命令列表
+    Path(PathPurpose),                                        // 输入文件路径
+    QuickOpen,                                                // 按文件名模糊查找工作目录中的文件
+    Pick { prompt: String, items: Vec<(String, PaletteAction)> }, // 从给定列表中选择
+}
+
+/// 面板中的一个候选条目
+struct PaletteEntry {
+    label: String,
+    detail: String,
+    positions: Vec<usize>,
+    action: EntryAction,
+}
+
+enum EntryAction {
+    Emit(PaletteAction),
+    Complete(String), // 路径模式下补全到输入框，不关闭面板
+}
+
+/// 命令面板组件（Ctrl+Shift+P）
+pub struct CommandPalette {
+    pub open: bool,
+    query: String,
+    selected: usize,
+    mode: PaletteMode,
+    base_directory: PathBuf, // 路径模式下相对路径的基准目录
+    file_index: Option<FileIndex>, // 快速打开使用的文件索引
+    recent_files: Vec<PathBuf>,    // 最近打开的文件（最近的在前）
+    ranked_cache: Option<(String, usize, Vec<RankedFile>)>, // 上次排序的输入、索引文件数和结果
+}
+
+/// 文件索引超过该时间（秒）后，再次快速打开时重新索引
+const FILE_INDEX_MAX_AGE: u64 = 60;
+
+/// 快速打开最多显示的条目数
+const QUICK_OPEN_LIMIT: usize = 100;
+
+impl CommandPalette {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            query: String::new(),
+            selected: 0,
+            mode: PaletteMode::Commands,
+            base_directory: PathBuf::from("."),
+            file_index: None,
+            recent_files: Vec::new(),
+            ranked_cache: None,
+        }
+    }
+
+    /// 打开命令列表
+    pub fn show_commands(&mut self) {
+        self.reset(PaletteMode::Commands, String::new());
+    }
+
+    /// 打开路径输入
+    pub fn show_path_input(&mut self, purpose: PathPurpose, base_directory: PathBuf, initial: String) {
+        self.base_directory = base_directory;
+        self.reset(PaletteMode::Path(purpose), initial);
+    }
+
+    /// 打开快速打开（根目录变化或索引过旧时重新索引）
+    pub fn show_quick_open(&mut self, root: PathBuf, recent_files: Vec<PathBuf>) {
+        let needs_rebuild = match &self.file_index {
+            Some(index) => index.root() != root || index.age_secs() > FILE_INDEX_MAX_AGE,
+            None => true,
+        };
+        if needs_rebuild {
+            self.file_index = Some(FileIndex::build(root));
+        }
+        self.recent_files = recent_files;
+        self.ranked_cache = None;
+        self.reset(PaletteMode::QuickOpen, String::new());
+    }
+
+    /// 打开选择列表
+    pub fn show_picker(&mut self, prompt: &str, items: Vec<(String, PaletteAction)>) {
+        self.reset(PaletteMode::Pick { prompt: prompt.to_string(), items }, String::new());
+    }
+
+    fn reset(&mut self, mode: PaletteMode, query: String) {
+        self.open = true;
+        self.mode = mode;
+        self.query = query;
+        self.selected = 0;
+    }
+
+    /// 渲染命令面板，返回用户选中的动作
+    pub fn render(&mut self, ctx: &egui::Context, keymap: &Keymap) -> Option<PaletteAction> {
+        if !self.open {
+            return None;
+        }
+
+        // 先消费导航按键，避免被输入框处理
+        let (up, down, enter, escape) = ctx.input_mut(|i| {
+            (
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::Enter),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::Escape),
+            )
+        });
+        if escape {
+            self.open = false;
+            return None;
+        }
+
+        let entries = self.collect_entries(keymap);
+        if !entries.is_empty() {
+            if up {
+                self.selected = self.selected.checked_sub(1).unwrap_or(entries.len() - 1);
+            }
+            if down {
+                self.selected = (self.selected + 1) % entries.len();
+            }
+            self.selected = self.selected.min(entries.len() - 1);
+        }
+
+        let mut chosen = if enter && !entries.is_empty() { Some(self.selected) } else { None };
+
+        let indexing_prompt;
+        let (prompt, hint) = match &self.mode {
+            PaletteMode::Commands => (None, "输入命令名称"),
+            PaletteMode::QuickOpen => {
+                indexing_prompt = match &self.file_index {
+                    Some(index) if !index.is_finished() => {
+                        // 索引进行中，定时刷新以显示新结果
+                        ctx.request_repaint_after(std::time::Duration::from_millis(200));
+                        format!("快速打开（正在索引，已找到 {} 个文件）", index.len())
+                    }
+                    Some(index) => format!("快速打开 - {}", index.root().display()),
+                    None => "快速打开".to_string(),
+                };
+                (Some(indexing_prompt.as_str()), "输入文件名")
+            }
+            PaletteMode::Path(PathPurpose::Open) => (Some("打开文件"), "输入文件路径"),
+            PaletteMode::Path(PathPurpose::OpenFolder) => (Some("打开文件夹作为工作区"), "输入目录路径"),
+            PaletteMode::Path(PathPurpose::SaveAs) => (Some("另存为"), "输入保存路径"),
+            PaletteMode::Pick { prompt, .. } => (Some(prompt.as_str()), "输入以筛选"),
+        };
+        let prompt = prompt.map(str::to_string);
+
+        let area = egui::Area::new("command_palette")
+            .order(egui::Order::Foreground)
+            .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 40.0))
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.set_width(520.0);
+
+                    if let Some(prompt) = &prompt {
+                        ui.label(egui::RichText::new(prompt).strong());
+                    }
+
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut self.query)
+                            .hint_text(hint)
+                            .desired_width(f32::INFINITY),
+                    );
+                    response.request_focus();
+                    if response.changed() {
+                        self.selected = 0;
+                    }
+                    ui.separator();
+
+                    egui::ScrollArea::vertical()
+                        .id_source("command_palette_list")
+                        .max_height(320.0)
+                        .show(ui, |ui| {
+                            if entries.is_empty() {
+                                ui.weak("没有匹配的条目");
+                            }
+                            for (index, entry) in entries.iter().enumerate() {
+                                let is_selected = index == self.selected;
+                                let label = Self::highlighted_label(ui, &entry.label, &entry.positions);
+
+                                let row = ui.horizontal(|ui| {
+                                    let response = ui.selectable_label(is_selected, label);
+                                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                        ui.weak(&entry.detail);
+                                    });
+                                    response
+                                }).inner;
+
+                                if is_selected && (up || down) {
+                                    row.scroll_to_me(None);
+                                }
+                                if row.clicked() {
+                                    chosen = Some(index);
+                                }
+                            }
+                        });
+                });
+            });
+
+        // 点击面板外部时关闭
+        if area.response.clicked_elsewhere() {
+            self.open = false;
+            return None;
+        }
+
+        let entry = entries.into_iter().nth(chosen?)?;
+        match entry.action {
+            EntryAction::Emit(action) => {
+                self.open = false;
+                Some(action)
+            }
+            EntryAction::Complete(text) => {
+                self.query = text;
+                self.selected = 0;
+                None
+            }
+        }
+    }
+
+    /// 根据当前模式和输入收集候选条目
+    fn collect_entries(&mut self, keymap: &Keymap) -> Vec<PaletteEntry> {
+        match &self.mode {
+            PaletteMode::Commands => {
+                let candidates = Command::ALL
+                    .iter()
+                    .filter(|command| **command != Command::ShowPalette)
+                    .map(|command| {
+                        let detail = keymap.binding_text(*command);
+                        (command.title().to_string(), detail, EntryAction::Emit(PaletteAction::Run(*command)))
+                    });
+                Self::rank(&self.query, candidates)
+            }
+            PaletteMode::Pick { items, .. } => {
+                let candidates = items
+                    .iter()
+                    .map(|(label, action)| (label.clone(), String::new(), EntryAction::Emit(action.clone())));
+                Self::rank(&self.query, candidates)
+            }
+            PaletteMode::Path(purpose) => self.collect_path_entries(*purpose),
+            PaletteMode::QuickOpen => self.collect_quick_open_entries(),
+        }
+    }
+
+    /// 快速打开：输入和索引都未变化时复用上次的排序结果
+    fn collect_quick_open_entries(&mut self) -> Vec<PaletteEntry> {
+        let Some(index) = &self.file_index else {
+            return Vec::new();
+        };
+
+        let indexed = index.len();
+        let is_cached = matches!(&self.ranked_cache, Some((query, count, _)) if *query == self.query && *count == indexed);
+        if !is_cached {
+            let ranked = index.rank(&self.query, &self.recent_files, QUICK_OPEN_LIMIT);
+            self.ranked_cache = Some((self.query.clone(), indexed, ranked));
+        }
+
+        let root = index.root();
+        let ranked = self.ranked_cache.as_ref().map(|(_, _, ranked)| ranked.as_slice()).unwrap_or_default();
+        ranked
+            .iter()
+            .map(|file| {
+                let path = root.join(&file.relative_path);
+                let detail = if self.recent_files.contains(&path) { "最近打开".to_string() } else { String::new() };
+                PaletteEntry {
+                    label: file.relative_path.clone(),
+                    detail,
+                    positions: file.positions.clone(),
+                    action: EntryAction::Emit(PaletteAction::OpenFile(path)),
+                }
+            })
+            .collect()
+    }
+
+    /// 对候选条目做模糊匹配并按得分排序
+    fn rank(query: &str, candidates: impl Iterator<Item = (String, String, EntryAction)>) -> Vec<PaletteEntry> {
+        let mut scored: Vec<(i32, PaletteEntry)> = candidates
+            .filter_map(|(label, detail, action)| {
+                let m = fuzzy_match(query, &label)?;
+                Some((m.score, PaletteEntry { label, detail, positions: m.positions, action }))
+            })
+            .collect();
+        // 稳定排序：得分相同的保持注册顺序
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        scored.into_iter().map(|(_, entry)| entry).collect()
+    }
+
+    /// 路径模式：第一项为确认当前输入，其后为所在目录中的补全候选
+    fn collect_path_entries(&self, purpose: PathPurpose) -> Vec<PaletteEntry> {
+        let mut entries = Vec::new();
+        let typed = self.query.trim();
+        if typed.is_empty() {
+            return entries;
+        }
+
+        let resolved = self.resolve(typed);
+        let action = match purpose {
+            PathPurpose::Open => PaletteAction::OpenFile(resolved.clone()),
+            PathPurpose::OpenFolder => PaletteAction::OpenFolder(resolved.clone()),
+            PathPurpose::SaveAs => PaletteAction::SaveFileAs(resolved.clone()),
+        };
+        entries.push(PaletteEntry {
+            label: resolved.display().to_string(),
+            detail: "Enter 确认".to_string(),
+            positions: Vec::new(),
+            action: EntryAction::Emit(action),
+        });
+
+        // 拆分为目录部分与文件名部分，列出目录中匹配的条目
+        let (dir_part, name_part) = match typed.rfind(['/', '\\']) {
+            Some(pos) => (&typed[..=pos], &typed[pos + 1..]),
+            None => ("", typed),
+        };
+        let Ok(read_dir) = std::fs::read_dir(self.resolve(dir_part)) else {
+            return entries;
+        };
+
+        let mut completions: Vec<(i32, PaletteEntry)> = read_dir
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                let m = fuzzy_match(name_part, &name)?;
+                let is_directory = entry.path().is_dir();
+                let completed = if is_directory {
+                    format!("{}{}/", dir_part, name)
+                } else {
+                    format!("{}{}", dir_part, name)
+                };
+                Some((m.score, PaletteEntry {
+                    label: name,
+                    detail: if is_directory { "目录".to_string() } else { String::new() },
+                    positions: m.positions,
+                    action: EntryAction::Complete(completed),
+                }))
+            })
+            .collect();
+        completions.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.label.cmp(&b.1.label)));
+        entries.extend(completions.into_iter().take(50).map(|(_, entry)| entry));
+        entries
+    }
+
+    /// 将输入解析为路径（相对路径基于当前目录）
+    fn resolve(&self, typed: &str) -> PathBuf {
+        let path = Path::new(typed);
+        if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            self.base_directory.join(path)
+        }
+    }
+
+    /// 构建匹配字符高亮的标签
+    fn highlighted_label(ui: &egui::Ui, text: &str, positions: &[usize]) -> egui::text::LayoutJob {
+        let font_id = egui::TextStyle::Body.resolve(ui.style());
+        let normal = ui.visuals().text_color();
+        let highlight = ui.visuals().selection.stroke.color;
+
+        let mut job = egui::text::LayoutJob::default();
+        for (index, ch) in text.chars().enumerate() {
+            let color = if positions.contains(&index) { highlight } else { normal };
+            job.append(
+                ch.encode_utf8(&mut [0; 4]),
+                0.0,
+                egui::TextFormat {
+                    font_id: font_id.clone(),
+                    color,
+                    ..Default::default()
+                },
+            );
+        }
+        job
+    }
+}
@@ -0,0 +1,307 @@
+/// 应用命令：所有可通过命令面板或快捷键触发的功能
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Command {
+    ShowPalette,
+    OpenFile,
+    QuickOpen,
+    OpenFolder,
+    SaveFile,
+    SaveFileAs,
+    ToggleEditMode,
+    ScrollToTop,
+    ScrollToBottom,
+    ToggleFollowMode,
+    ChangeTheme,
+    ChangeEncoding,
+    ToggleFileBrowser,
+    ToggleStatusBar,
+    ToggleSettings,
+    OpenKeybindings,
+    ResetKeybindings,
+    GoToParentDirectory,
+    NavigateBack,
+    NavigateForward,
+    EditLocation,
+    RefreshDirectory,
+    ToggleHiddenFiles,
+    ToggleBookmark,
+    NextBookmark,
+    PreviousBookmark,
+    ToggleFavourite,
+    ToggleBlame,
+    CompareWithSaved,
+    CompareWithHead,
+    NextChange,
+    PreviousChange,
+    ToggleDiffLayout,
+    CloseDiff,
+    ToggleMarkdownPreview,
+    MarkdownPreviewOnly,
+    ZoomIn,
+    ZoomOut,
+    ZoomToFit,
+    ZoomActualSize,
+    TogglePixelGrid,
+    CloseImage,
+    HexGoToOffset,
+    HexFind,
+    HexFindNext,
+    HexFindPrevious,
+    CloseHexView,
+    ToggleStructuredView,
+    FormatDocument,
+    MinifyDocument,
+    ToggleTableView,
+    ToggleFold,
+    FoldAll,
+    UnfoldAll,
+    ToggleMinimap,
+    EditorFind,
+    EditorFindNext,
+    EditorFindPrevious,
+    ToggleSoftWrap,
+    JumpToMatchingBracket,
+    ToggleRainbowBrackets,
+    ToggleIndentGuides,
+    ToggleWhitespace,
+    ConvertToLf,
+    ConvertToCrlf,
+    IndentWithSpaces,
+    IndentWithTabs,
+    TabWidth2,
+    TabWidth4,
+    TabWidth8,
+}
+
+impl Command {
+    /// 命令注册表（命令面板按此顺序列出）
+    pub const ALL: &'static [Command] = &[
+        Command::ShowPalette,
+        Command::OpenFile,
+        Command::QuickOpen,
+        Command::OpenFolder,
+        Command::SaveFile,
+        Command::SaveFileAs,
+        Command::ToggleEditMode,
+        Command::ScrollToTop,
+        Command::ScrollToBottom,
+        Command::ToggleFollowMode,
+        Command::ChangeTheme,
+        Command::ChangeEncoding,
+        Command::ToggleFileBrowser,
+        Command::ToggleStatusBar,
+        Command::ToggleSettings,
+        Command::OpenKeybindings,
+        Command::ResetKeybindings,
+        Command::GoToParentDirectory,
+        Command::NavigateBack,
+        Command::NavigateForward,
+        Command::EditLocation,
+        Command::RefreshDirectory,
+        Command::ToggleHiddenFiles,
+        Command::ToggleBookmark,
+        Command::NextBookmark,
+        Command::PreviousBookmark,
+        Command::ToggleFavourite,
+        Command::ToggleBlame,
+        Command::CompareWithSaved,
+        Command::CompareWithHead,
+        Command::NextChange,
+        Command::PreviousChange,
+        Command::ToggleDiffLayout,
+        Command::CloseDiff,
+        Command::ToggleMarkdownPreview,
+        Command::MarkdownPreviewOnly,
+        Command::ZoomIn,
+        Command::ZoomOut,
+        Command::ZoomToFit,
+        Command::ZoomActualSize,
+        Command::TogglePixelGrid,
+        Command::CloseImage,
+        Command::HexGoToOffset,
+        Command::HexFind,
+        Command::HexFindNext,
+        Command::HexFindPrevious,
+        Command::CloseHexView,
+        Command::ToggleStructuredView,
+        Command::FormatDocument,
+        Command::MinifyDocument,
+        Command::ToggleTableView,
+        Command::ToggleFold,
+        Command::FoldAll,
+        Command::UnfoldAll,
+        Command::ToggleMinimap,
+        Command::EditorFind,
+        Command::EditorFindNext,
+        Command::EditorFindPrevious,
+        Command::ToggleSoftWrap,
+        Command::JumpToMatchingBracket,
+        Command::ToggleRainbowBrackets,
+        Command::ToggleIndentGuides,
+        Command::ToggleWhitespace,
+        Command::ConvertToLf,
+        Command::ConvertToCrlf,
+        Command::IndentWithSpaces,
+        Command::IndentWithTabs,
+        Command::TabWidth2,
+        Command::TabWidth4,
+        Command::TabWidth8,
+    ];
+
+    /// 命令的唯一标识（用于快捷键配置文件）
+    pub fn id(self) -> &'static str {
+        match self {
+            Command::ShowPalette => "palette.show",
+            Command::OpenFile => "file.open",
+            Command::QuickOpen => "file.quick_open",
+            Command::OpenFolder => "file.open_folder",
+            Command::SaveFile => "file.save",
+            Command::SaveFileAs => "file.save_as",
+            Command::ToggleEditMode => "editor.toggle_mode",
+            Command::ScrollToTop => "editor.scroll_to_top",
+            Command::ScrollToBottom => "editor.scroll_to_bottom",
+            Command::ToggleFollowMode => "editor.toggle_follow",
+            Command::ChangeTheme => "view.change_theme",
+            Command::ChangeEncoding => "file.change_encoding",
+            Command::ToggleFileBrowser => "view.toggle_file_browser",
+            Command::ToggleStatusBar => "view.toggle_status_bar",
+            Command::ToggleSettings => "view.toggle_settings",
+            Command::OpenKeybindings => "settings.keybindings",
+            Command::ResetKeybindings => "settings.reset_keybindings",
+            Command::GoToParentDirectory => "browser.parent_directory",
+            Command::NavigateBack => "browser.back",
+            Command::NavigateForward => "browser.forward",
+            Command::EditLocation => "browser.edit_location",
+            Command::RefreshDirectory => "browser.refresh",
+            Command::ToggleHiddenFiles => "browser.toggle_hidden",
+            Command::ToggleBookmark => "bookmark.toggle",
+            Command::NextBookmark => "bookmark.next",
+            Command::PreviousBookmark => "bookmark.previous",
+            Command::ToggleFavourite => "bookmark.toggle_favourite",
+            Command::ToggleBlame => "git.toggle_blame",
+            Command::CompareWithSaved => "diff.with_saved",
+            Command::CompareWithHead => "diff.with_head",
+            Command::NextChange => "diff.next_change",
+            Command::PreviousChange => "diff.previous_change",
+            Command::ToggleDiffLayout => "diff.toggle_layout",
+            Command::CloseDiff => "diff.close",
+            Command::ToggleMarkdownPreview => "markdown.toggle_preview",
+            Command::MarkdownPreviewOnly => "markdown.preview_only",
+            Command::ZoomIn => "image.zoom_in",
+            Command::ZoomOut => "image.zoom_out",
+            Command::ZoomToFit => "image.zoom_to_fit",
+            Command::ZoomActualSize => "image.actual_size",
+            Command::TogglePixelGrid => "image.toggle_grid",
+            Command::CloseImage => "image.close",
+            Command::HexGoToOffset => "hex.go_to_offset",
+            Command::HexFind => "hex.find",
+            Command::HexFindNext => "hex.find_next",
+            Command::HexFindPrevious => "hex.find_previous",
+            Command::CloseHexView => "hex.close",
+            Command::ToggleStructuredView => "structured.toggle_tree",
+            Command::FormatDocument => "structured.format",
+            Command::MinifyDocument => "structured.minify",
+            Command::ToggleTableView => "table.toggle",
+            Command::ToggleFold => "editor.toggle_fold",
+            Command::FoldAll => "editor.fold_all",
+            Command::UnfoldAll => "editor.unfold_all",
+            Command::ToggleMinimap => "editor.toggle_minimap",
+            Command::EditorFind => "editor.find",
+            Command::EditorFindNext => "editor.find_next",
+            Command::EditorFindPrevious => "editor.find_previous",
+            Command::ToggleSoftWrap => "editor.toggle_soft_wrap",
+            Command::JumpToMatchingBracket => "editor.jump_to_bracket",
+            Command::ToggleRainbowBrackets => "editor.toggle_rainbow_brackets",
+            Command::ToggleIndentGuides => "editor.toggle_indent_guides",
+            Command::ToggleWhitespace => "editor.toggle_whitespace",
+            Command::ConvertToLf => "editor.line_endings_lf",
+            Command::ConvertToCrlf => "editor.line_endings_crlf",
+            Command::IndentWithSpaces => "editor.indent_with_spaces",
+            Command::IndentWithTabs => "editor.indent_with_tabs",
+            Command::TabWidth2 => "editor.tab_width_2",
+            Command::TabWidth4 => "editor.tab_width_4",
+            Command::TabWidth8 => "editor.tab_width_8",
+        }
+    }
+
+    /// 根据标识查找命令
+    pub fn from_id(id: &str) -> Option<Command> {
+        Command::ALL.iter().copied().find(|command| command.id() == id)
+    }
+
+    /// 命令在面板中显示的名称
+    pub fn title(self) -> &'static str {
+        match self {
+            Command::ShowPalette => "显示命令面板",
+            Command::OpenFile => "文件: 打开文件...",
+            Command::QuickOpen => "文件: 快速打开...",
+            Command::OpenFolder => "文件: 打开文件夹...",
+            Command::SaveFile => "文件: 保存",
+            Command::SaveFileAs => "文件: 另存为...",
+            Command::ToggleEditMode => "编辑器: 切换编辑/语法高亮模式",
+            Command::ScrollToTop => "编辑器: 滚动到开头",
+            Command::ScrollToBottom => "编辑器: 滚动到末尾",
+            Command::ToggleFollowMode => "编辑器: 跟随文件末尾 (tail -f)",
+            Command::ChangeTheme => "视图: 更改主题...",
+            Command::ChangeEncoding => "文件: 以其他编码重新打开...",
+            Command::ToggleFileBrowser => "视图: 显示/隐藏目录面板",
+            Command::ToggleStatusBar => "视图: 显示/隐藏状态栏",
+            Command::ToggleSettings => "视图: 打开/关闭设置",
+            Command::OpenKeybindings => "设置: 快捷键",
+            Command::ResetKeybindings => "设置: 恢复默认快捷键",
+            Command::GoToParentDirectory => "目录: 返回上级",
+            Command::NavigateBack => "目录: 后退",
+            Command::NavigateForward => "目录: 前进",
+            Command::EditLocation => "目录: 输入路径...",
+            Command::RefreshDirectory => "目录: 刷新",
+            Command::ToggleHiddenFiles => "目录: 显示/隐藏隐藏文件",
+            Command::ToggleBookmark => "书签: 切换当前行书签",
+            Command::NextBookmark => "书签: 跳到下一个书签",
+            Command::PreviousBookmark => "书签: 跳到上一个书签",
+            Command::ToggleFavourite => "收藏: 添加/移除当前文件",
+            Command::ToggleBlame => "Git: 显示/隐藏逐行追溯 (Blame)",
+            Command::CompareWithSaved => "比较: 当前内容与磁盘上的文件",
+            Command::CompareWithHead => "比较: 工作副本与 Git HEAD",
+            Command::NextChange => "比较: 下一处差异",
+            Command::PreviousChange => "比较: 上一处差异",
+            Command::ToggleDiffLayout => "比较: 切换并排/内联显示",
+            Command::CloseDiff => "比较: 关闭比较视图",
+            Command::ToggleMarkdownPreview => "Markdown: 显示/隐藏并排预览",
+            Command::MarkdownPreviewOnly => "Markdown: 只显示预览",
+            Command::ZoomIn => "图像: 放大",
+            Command::ZoomOut => "图像: 缩小",
+            Command::ZoomToFit => "图像: 适应窗口",
+            Command::ZoomActualSize => "图像: 实际大小 (1:1)",
+            Command::TogglePixelGrid => "图像: 显示/隐藏像素网格",
+            Command::CloseImage => "图像: 关闭图像",
+            Command::HexGoToOffset => "十六进制: 转到偏移",
+            Command::HexFind => "十六进制: 查找字节",
+            Command::HexFindNext => "十六进制: 下一个匹配",
+            Command::HexFindPrevious => "十六进制: 上一个匹配",
+            Command::CloseHexView => "十六进制: 关闭查看器",
+            Command::ToggleStructuredView => "结构化: 切换树形视图",
+            Command::FormatDocument => "结构化: 格式化文档",
+            Command::MinifyDocument => "结构化: 压缩文档",
+            Command::ToggleTableView => "表格: 切换表格/原始文本",
+            Command::ToggleFold => "编辑器: 折叠/展开当前区域",
+            Command::FoldAll => "编辑器: 全部折叠",
+            Command::UnfoldAll => "编辑器: 全部展开",
+            Command::ToggleMinimap => "编辑器: 显示/隐藏缩略图",
+            Command::EditorFind => "编辑器: 查找",
+            Command::EditorFindNext => "编辑器: 下一个查找结果",
+            Command::EditorFindPrevious => "编辑器: 上一个查找结果",
+            Command::ToggleSoftWrap => "编辑器: 切换自动换行",
+            Command::JumpToMatchingBracket => "编辑器: 跳到匹配的括号",
+            Command::ToggleRainbowBrackets => "编辑器: 打开/关闭彩虹括号",
+            Command::ToggleIndentGuides => "编辑器: 显示/隐藏缩进参考线",
+            Command::ToggleWhitespace => "编辑器: 显示/隐藏空白字符",
+            Command::ConvertToLf => "编辑器: 换行符转换为 LF",
+            Command::ConvertToCrlf => "编辑器: 换行符转换为 CRLF",
+            Command::IndentWithSpaces => "编辑器: 使用空格重新缩进",
+            Command::IndentWithTabs => "编辑器: 使用制表符重新缩进",
+            Command::TabWidth2 => "编辑器: 制表符宽度设为 2",
+            Command::TabWidth4 => "编辑器: 制表符宽度设为 4",
+            Command::TabWidth8 => "编辑器: 制表符宽度设为 8",
+        }
+    }
+}
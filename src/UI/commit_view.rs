@@ -0,0 +1,77 @@
+use eframe::egui;
+use crate::directory_listing;
+use crate::git::CommitDetails;
+use crate::ui::commands::Command;
+use crate::ui::styles;
+
+/// 提交详情视图（只读，与比较视图一样显示在代码区）：提交说明和补丁
+pub struct CommitView {
+    details: CommitDetails,
+    lines: Vec<String>, // 补丁按行拆分，便于只渲染可见行
+}
+
+impl CommitView {
+    pub fn new(details: CommitDetails) -> Self {
+        let lines = details.patch.lines().map(str::to_string).collect();
+        Self { details, lines }
+    }
+
+    /// 渲染提交详情
+    pub fn render(&mut self, ui: &mut egui::Ui, available_height: f32, commands: &mut Vec<Command>) {
+        ui.set_width(ui.available_width());
+        ui.set_min_height(available_height);
+        let details = &self.details;
+
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new(format!("提交 {}", details.commit.short_id())).strong());
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.button("✖ 关闭").clicked() {
+                    commands.push(Command::CloseDiff);
+                }
+            });
+        });
+        ui.separator();
+
+        egui::Grid::new("commit_header").num_columns(2).show(ui, |ui| {
+            ui.label("提交");
+            ui.label(egui::RichText::new(&details.commit.id).monospace());
+            ui.end_row();
+
+            ui.label("作者");
+            ui.label(&details.commit.author);
+            ui.end_row();
+
+            ui.label("时间");
+            ui.label(format!("{} UTC", directory_listing::format_timestamp(details.commit.time)));
+            ui.end_row();
+        });
+        ui.add_space(4.0);
+        ui.label(&details.commit.message);
+        ui.separator();
+
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+        egui::ScrollArea::both()
+            .id_source("commit_patch")
+            .auto_shrink([false, false])
+            .show_rows(ui, row_height, self.lines.len(), |ui, row_range| {
+                for line in &self.lines[row_range] {
+                    ui.label(egui::RichText::new(line).monospace().color(patch_line_color(line, ui.visuals())));
+                }
+            });
+    }
+}
+
+/// 补丁行的颜色：新增、删除、区块头和文件头
+fn patch_line_color(line: &str, visuals: &egui::Visuals) -> egui::Color32 {
+    if line.starts_with("+++") || line.starts_with("---") || line.starts_with("diff ") {
+        visuals.strong_text_color()
+    } else if line.starts_with('+') {
+        styles::GIT_ADDED_COLOR
+    } else if line.starts_with('-') {
+        styles::GIT_DELETED_COLOR
+    } else if line.starts_with("@@") {
+        styles::GIT_MODIFIED_COLOR
+    } else {
+        visuals.text_color()
+    }
+}
@@ -1,11 +1,91 @@
 use eframe::egui;
-use std::path::PathBuf;
-use crate::DirectoryItem;
+use rayon::prelude::*;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::time::SystemTime;
+use crate::directory_listing::{self, DirectoryItem, ListingOptions, SortKey};
+use crate::bookmarks::Bookmarks;
+use crate::git::{Blame, FileStatus, GitRepository, LineChange};
+use crate::structured::ParseError;
+use crate::indentation::{self, Indent};
+use crate::line_ending::{self, LineEnding};
+use crate::{file_ops, file_types, folding, wrap};
+use crate::ui::commands::Command;
+use crate::ui::keymap::{format_sequence, shortcut_from_event, KeySequence, Keymap};
+use crate::ui::styles;
+use crate::ui::syntax_highlighter::SyntaxHighlighter;
+
+/// 树形列表中的一行
+#[derive(Clone)]
+struct TreeRow {
+    depth: usize,
+    item: DirectoryItem,
+    is_new_entry: bool, // 新建文件/文件夹时的输入行
+}
+
+/// 文件操作的结果，由布局同步到应用状态
+pub enum FileEvent {
+    Created(PathBuf),                     // 新建的文件
+    Moved { from: PathBuf, to: PathBuf }, // 重命名的文件或文件夹
+    Deleted(PathBuf),                     // 移到回收站的文件或文件夹
+    Compare { left: PathBuf, right: PathBuf }, // 比较两个文件
+    Error(String),                        // 操作失败的提示
+}
+
+/// 目录树中 Git 状态对应的文字颜色
+fn git_status_color(status: FileStatus, visuals: &egui::Visuals) -> egui::Color32 {
+    match status {
+        FileStatus::Ignored => visuals.weak_text_color(),
+        FileStatus::Untracked | FileStatus::Added => styles::GIT_ADDED_COLOR,
+        FileStatus::Modified => egui::Color32::from_rgb(210, 160, 60),
+        FileStatus::Conflicted => styles::GIT_DELETED_COLOR,
+    }
+}
 
-/// 文件管理器组件
+/// 行内输入框的用途
+enum InlineEditKind {
+    Rename(PathBuf),    // 重命名该条目
+    NewFile(PathBuf),   // 在该目录下新建文件
+    NewFolder(PathBuf), // 在该目录下新建文件夹
+}
+
+/// 行内输入框
+struct InlineEdit {
+    kind: InlineEditKind,
+    text: String,
+    request_focus: bool, // 首帧获取焦点并选中文件名
+}
+
+/// 文件管理器组件（以工作区目录为根的树形视图）
 pub struct FileBrowser {
-    pub current_directory: PathBuf,
-    pub directory_items: Vec<DirectoryItem>,
+    pub current_directory: PathBuf,          // 工作区根目录
+    pub directory_items: Vec<DirectoryItem>, // 根目录下的条目
+    expanded: HashSet<PathBuf>,              // 已展开的目录（折叠父目录后仍保留）
+    children: HashMap<PathBuf, Vec<DirectoryItem>>, // 已加载的子目录内容（展开时按需加载）
+    rows: Option<Rc<Vec<TreeRow>>>,          // 展平后的行（目录内容、展开状态或筛选条件变化时重建）
+    selected: Option<PathBuf>,               // 键盘导航选中的条目
+    revealed_file: Option<PathBuf>,          // 上次自动定位过的打开文件
+    has_focus: bool,                         // 是否接收键盘导航
+    scroll_to_row: Option<usize>,            // 需要滚动到可见区域的行
+    visible_rows: std::ops::Range<usize>,    // 上一帧可见的行范围
+    inline_edit: Option<InlineEdit>,         // 正在进行的重命名或新建
+    pending_delete: Option<PathBuf>,         // 等待确认删除的条目
+    events: Vec<FileEvent>,                  // 尚未被布局处理的文件操作结果
+    listing: ListingOptions,                 // 排序和隐藏文件选项
+    filter: String,                          // 文件名筛选（目录始终显示）
+    show_size_column: bool,                  // 显示大小列
+    show_modified_column: bool,              // 显示修改时间列
+    line_counts: HashMap<PathBuf, (Option<SystemTime>, Option<usize>)>, // 悬停时统计的行数（按修改时间失效）
+    line_count_jobs: HashMap<PathBuf, (Option<SystemTime>, Receiver<Option<usize>>)>, // 后台统计中的行数
+    back_history: Vec<PathBuf>,              // 后退历史（最近的在末尾）
+    forward_history: Vec<PathBuf>,           // 前进历史（最近的在末尾）
+    location_edit: Option<String>,           // 路径输入模式下的输入内容
+    location_focus: bool,                    // 下一帧让路径输入框获取焦点
+    location_candidates: Vec<String>,        // Tab 补全的候选目录
+    breadcrumb_menus: HashMap<PathBuf, Vec<DirectoryItem>>, // 打开的面包屑下拉菜单中的子目录（菜单关闭后清空）
+    compare_base: Option<PathBuf>,           // 已选择、等待与另一个文件比较的文件
 }
 
 impl FileBrowser {
@@ -13,6 +93,30 @@ impl FileBrowser {
         Self {
             current_directory,
             directory_items: Vec::new(),
+            expanded: HashSet::new(),
+            children: HashMap::new(),
+            rows: None,
+            selected: None,
+            revealed_file: None,
+            has_focus: false,
+            scroll_to_row: None,
+            visible_rows: 0..0,
+            inline_edit: None,
+            pending_delete: None,
+            events: Vec::new(),
+            listing: ListingOptions::default(),
+            filter: String::new(),
+            show_size_column: false,
+            show_modified_column: false,
+            line_counts: HashMap::new(),
+            line_count_jobs: HashMap::new(),
+            back_history: Vec::new(),
+            forward_history: Vec::new(),
+            location_edit: None,
+            location_focus: false,
+            location_candidates: Vec::new(),
+            breadcrumb_menus: HashMap::new(),
+            compare_base: None,
         }
     }
 
@@ -22,215 +126,2265 @@ impl FileBrowser {
         ui: &mut egui::Ui,
         file_path: &Option<PathBuf>,
         _available_height: f32,
-        show_settings: &mut bool,
+        bookmarks: &mut Bookmarks,
+        git: Option<&GitRepository>,
+        commands: &mut Vec<Command>,
     ) -> Option<PathBuf> {
         let mut file_to_load: Option<PathBuf> = None;
-        let mut directory_to_enter: Option<PathBuf> = None;
 
         ui.set_width(ui.available_width());
 
         // 固定的返回上级目录按钮 - 直接在顶部
         if self.current_directory.parent().is_some() {
             if ui.selectable_label(false, ".. 返回上级").clicked() {
-                if let Some(parent) = self.current_directory.parent() {
-                    directory_to_enter = Some(parent.to_path_buf());
-                }
+                commands.push(Command::GoToParentDirectory);
             }
             ui.separator();
         } else {
             // 没有返回按钮时显示设置按钮
-            let settings_text = if *show_settings { "[设置] " } else { "设置" };
-            if ui.selectable_label(*show_settings, settings_text).clicked() {
-                *show_settings = !*show_settings;
+            if ui.selectable_label(false, "设置").clicked() {
+                commands.push(Command::ToggleSettings);
             }
             ui.separator();
         }
 
+        // 路径栏：前进后退、面包屑或路径输入
+        if let Some(path) = self.render_location_bar(ui) {
+            file_to_load = Some(path);
+        }
+
+        // 筛选框和列表选项
+        ui.horizontal(|ui| {
+            let menu_width = ui.spacing().interact_size.x;
+            let filter = ui.add(
+                egui::TextEdit::singleline(&mut self.filter)
+                    .hint_text("🔍 筛选文件（支持 *.rs）")
+                    .desired_width(ui.available_width() - menu_width),
+            );
+            if filter.changed() {
+                self.rows = None;
+            }
+            ui.menu_button("⚙", |ui| self.render_listing_menu(ui));
+        });
+
+        // 固定在顶部的收藏列表
+        if let Some(path) = self.render_favourites(ui, file_path, bookmarks) {
+            file_to_load = Some(path);
+        }
+
+        // 打开的文件变化时展开其所在目录并选中
+        if file_path != &self.revealed_file {
+            self.revealed_file = file_path.clone();
+            if let Some(path) = file_path {
+                self.reveal(path);
+            }
+        }
+
+        let rows = self.collect_rows();
+
+        if let Some(path) = self.handle_keyboard(ui, &rows) {
+            file_to_load = Some(path);
+        }
+
+        let row_height = ui.spacing().interact_size.y.max(ui.text_style_height(&egui::TextStyle::Body) + 4.0);
+        let row_stride = row_height + ui.spacing().item_spacing.y;
+
         // 目录显示区域 - 使用剩余空间
-        egui::ScrollArea::vertical()
+        let mut scroll_area = egui::ScrollArea::vertical()
             .id_source("file_list")
             .auto_shrink([false, false])
-            .stick_to_bottom(false)
+            .stick_to_bottom(false);
+        if let Some(row) = self.scroll_to_row.take() {
+            // 目标行不在可见范围内时滚动，使其出现在视口中
+            if row < self.visible_rows.start || row + 1 >= self.visible_rows.end {
+                let viewport_rows = self.visible_rows.len().max(1);
+                let first_row = if row < self.visible_rows.start { row } else { (row + 2).saturating_sub(viewport_rows) };
+                scroll_area = scroll_area.vertical_scroll_offset(first_row as f32 * row_stride);
+            }
+        }
+
+        let list_response = scroll_area.show_rows(ui, row_height, rows.len(), |ui, row_range| {
+            self.visible_rows = row_range.clone();
+
+            if rows.is_empty() {
+                ui.add_space(20.0);
+                let root = self.current_directory.clone();
+                let message = if self.filter.trim().is_empty() { "目录为空" } else { "没有匹配的文件" };
+                ui.label(message).context_menu(|ui| self.render_new_entry_menu(ui, &root));
+                return;
+            }
+
+            for row in &rows[row_range] {
+                if row.is_new_entry || self.is_renaming(&row.item.path) {
+                    self.render_inline_edit(ui, row, row_height);
+                    continue;
+                }
+
+                let is_current_file = !row.item.is_directory && file_path.as_ref() == Some(&row.item.path);
+                let is_selected = self.selected.as_ref() == Some(&row.item.path);
+                let git_status = git.and_then(|git| git.status_of(&row.item.path));
+
+                let response = self
+                    .render_row(ui, row, row_height, is_current_file, is_selected, git_status)
+                    .on_hover_ui(|ui| self.render_tooltip(ui, &row.item, git_status))
+                    .context_menu(|ui| self.render_context_menu(ui, &row.item, bookmarks));
+                if response.clicked() {
+                    self.has_focus = true;
+                    self.selected = Some(row.item.path.clone());
+                    if row.item.error.is_some() {
+                        // 无法读取的条目只选中，不打开
+                    } else if row.item.is_directory {
+                        self.toggle_expanded(&row.item.path);
+                    } else {
+                        file_to_load = Some(row.item.path.clone());
+                    }
+                }
+            }
+        });
+
+        // 点击文件列表外部时释放键盘导航
+        let list_rect = list_response.inner_rect;
+        if ui.input(|i| i.pointer.any_pressed() && i.pointer.interact_pos().is_some_and(|pos| !list_rect.contains(pos))) {
+            self.has_focus = false;
+        }
+
+        self.render_delete_confirmation(ui.ctx());
+
+        file_to_load
+    }
+
+    /// 排序、过滤和列显示选项菜单
+    fn render_listing_menu(&mut self, ui: &mut egui::Ui) {
+        let mut changed = false;
+
+        ui.label("排序方式");
+        for &key in SortKey::ALL {
+            changed |= ui.radio_value(&mut self.listing.sort_key, key, key.title()).changed();
+        }
+        changed |= ui.checkbox(&mut self.listing.descending, "倒序").changed();
+        ui.separator();
+        changed |= ui.checkbox(&mut self.listing.show_hidden, "显示隐藏文件").changed();
+        changed |= ui.checkbox(&mut self.listing.respect_gitignore, "隐藏 .gitignore 忽略的文件").changed();
+        ui.separator();
+        ui.checkbox(&mut self.show_size_column, "显示大小列");
+        ui.checkbox(&mut self.show_modified_column, "显示修改时间列");
+
+        if changed {
+            self.refresh();
+        }
+    }
+
+    /// 显示或隐藏隐藏文件
+    pub fn toggle_hidden_files(&mut self) {
+        self.listing.show_hidden = !self.listing.show_hidden;
+        self.refresh();
+    }
+
+    /// 取出尚未处理的文件操作结果
+    pub fn take_events(&mut self) -> Vec<FileEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// 路径栏，输入的路径是文件时返回该文件
+    fn render_location_bar(&mut self, ui: &mut egui::Ui) -> Option<PathBuf> {
+        let mut navigate_to: Option<PathBuf> = None;
+        let mut file_to_load = None;
+
+        ui.horizontal(|ui| {
+            if ui.add_enabled(!self.back_history.is_empty(), egui::Button::new("◀").small()).on_hover_text("后退").clicked() {
+                self.go_back();
+            }
+            if ui.add_enabled(!self.forward_history.is_empty(), egui::Button::new("▶").small()).on_hover_text("前进").clicked() {
+                self.go_forward();
+            }
+
+            if self.location_edit.is_some() {
+                file_to_load = self.render_location_input(ui);
+                return;
+            }
+
+            if ui.small_button("✏").on_hover_text("输入路径").clicked() {
+                self.edit_location();
+            }
+            navigate_to = self.render_breadcrumbs(ui);
+        });
+
+        // Tab 补全有多个候选时列在输入框下方
+        if self.location_edit.is_some() && !self.location_candidates.is_empty() {
+            ui.horizontal_wrapped(|ui| {
+                for candidate in self.location_candidates.clone() {
+                    let name = candidate.trim_end_matches(['/', '\\']).rsplit(['/', '\\']).next().unwrap_or(&candidate).to_string();
+                    if ui.small_button(name).clicked() {
+                        self.location_edit = Some(candidate);
+                        self.location_candidates.clear();
+                        self.location_focus = true;
+                    }
+                }
+            });
+        }
+
+        if let Some(path) = navigate_to {
+            self.set_root(path);
+        }
+        file_to_load
+    }
+
+    /// 面包屑：每一级目录一个按钮，其后的下拉菜单列出该目录的子目录
+    fn render_breadcrumbs(&mut self, ui: &mut egui::Ui) -> Option<PathBuf> {
+        let mut navigate_to = None;
+        let mut menu_open = false;
+        let segments: Vec<PathBuf> = self
+            .current_directory
+            .ancestors()
+            .filter(|segment| !segment.as_os_str().is_empty())
+            .map(Path::to_path_buf)
+            .collect();
+
+        egui::ScrollArea::horizontal()
+            .id_source("breadcrumbs")
+            .stick_to_right(true)
             .show(ui, |ui| {
-                if self.directory_items.is_empty() {
-                    ui.add_space(20.0);
-                    ui.label("目录为空");
-                } else {
-                    // 高亮显示当前文件
-                    for item in &self.directory_items {
-                        let is_current_file = if !item.is_directory {
-                            if let Some(current_path) = file_path {
-                                current_path.file_name()
-                                    .and_then(|name| name.to_str())
-                                    .map(|current_name| current_name == item.name)
-                                    .unwrap_or(false)
-                            } else {
-                                false
+                ui.horizontal(|ui| {
+                    ui.spacing_mut().item_spacing.x = 0.0;
+                    for segment in segments.iter().rev() {
+                        let name = segment
+                            .file_name()
+                            .map_or_else(|| segment.display().to_string(), |name| name.to_string_lossy().into_owned());
+                        if ui.add(egui::Button::new(name).small().frame(false)).clicked() && segment != &self.current_directory {
+                            navigate_to = Some(segment.clone());
+                        }
+
+                        ui.menu_button("›", |ui| {
+                            menu_open = true;
+                            // 子目录只在菜单打开时读取一次
+                            let listing = &self.listing;
+                            let subdirectories = self.breadcrumb_menus.entry(segment.clone()).or_insert_with(|| {
+                                directory_listing::read_directory(segment, listing)
+                                    .into_iter()
+                                    .filter(|item| item.is_directory)
+                                    .collect()
+                            });
+                            egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                                if subdirectories.is_empty() {
+                                    ui.weak("没有子目录");
+                                }
+                                for item in subdirectories.iter() {
+                                    if ui.button(format!("📁 {}", item.name)).clicked() {
+                                        navigate_to = Some(item.path.clone());
+                                        ui.close_menu();
+                                    }
+                                }
+                            });
+                        });
+                    }
+                });
+            });
+
+        if !menu_open {
+            self.breadcrumb_menus.clear();
+        }
+        navigate_to
+    }
+
+    /// 路径输入框：Tab 补全，回车跳转，Esc 取消
+    fn render_location_input(&mut self, ui: &mut egui::Ui) -> Option<PathBuf> {
+        let id = ui.make_persistent_id("location_input");
+        let Some(text) = &mut self.location_edit else {
+            return None;
+        };
+
+        // 在输入框处理按键之前拦截 Tab，避免焦点移到下一个控件
+        let has_focus = ui.memory(|m| m.has_focus(id));
+        if has_focus && ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Tab)) {
+            let (completed, candidates) = Self::complete_directory(text, &self.current_directory);
+            *text = completed;
+            self.location_candidates = candidates;
+            self.location_focus = true;
+        }
+        if self.location_focus {
+            // 光标移到末尾
+            let mut state = egui::TextEdit::load_state(ui.ctx(), id).unwrap_or_default();
+            let end = egui::text::CCursor::new(text.chars().count());
+            state.set_ccursor_range(Some(egui::text::CCursorRange::one(end)));
+            state.store(ui.ctx(), id);
+        }
+
+        let response = ui.add(egui::TextEdit::singleline(text).id(id).desired_width(ui.available_width() - 24.0));
+        if std::mem::take(&mut self.location_focus) {
+            response.request_focus();
+        }
+        let cancel = ui.small_button("✖").on_hover_text("取消").clicked();
+
+        if cancel || (response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Escape))) {
+            self.location_edit = None;
+            self.location_candidates.clear();
+            return None;
+        }
+        if !(response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter))) {
+            return None;
+        }
+
+        let typed = text.trim();
+        let path = if Path::new(typed).is_absolute() {
+            PathBuf::from(typed)
+        } else {
+            self.current_directory.join(typed)
+        };
+        self.location_edit = None;
+        self.location_candidates.clear();
+
+        if path.is_dir() {
+            self.set_root(path);
+            None
+        } else if path.is_file() {
+            // 输入的是文件：跳转到所在目录并打开
+            if let Some(parent) = path.parent() {
+                self.set_root(parent.to_path_buf());
+            }
+            Some(path)
+        } else {
+            self.events.push(FileEvent::Error(format!("路径不存在: {}", path.display())));
+            None
+        }
+    }
+
+    /// 目录补全：唯一匹配时补全完整目录名，多个匹配时补全公共前缀并返回候选
+    fn complete_directory(typed: &str, base: &Path) -> (String, Vec<String>) {
+        let (dir_part, name_part) = match typed.rfind(['/', '\\']) {
+            Some(pos) => (&typed[..=pos], &typed[pos + 1..]),
+            None => ("", typed),
+        };
+        let dir = if Path::new(dir_part).is_absolute() { PathBuf::from(dir_part) } else { base.join(dir_part) };
+        let Ok(read_dir) = std::fs::read_dir(dir) else {
+            return (typed.to_string(), Vec::new());
+        };
+
+        let prefix = name_part.to_lowercase();
+        let mut names: Vec<String> = read_dir
+            .flatten()
+            .filter(|entry| entry.path().is_dir())
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .filter(|name| name.to_lowercase().starts_with(&prefix))
+            .collect();
+        names.sort_by(|a, b| directory_listing::natural_cmp(a, b));
+
+        let separator = if dir_part.ends_with('\\') { '\\' } else { '/' };
+        match names.as_slice() {
+            [] => (typed.to_string(), Vec::new()),
+            [name] => (format!("{}{}{}", dir_part, name, separator), Vec::new()),
+            [first, rest @ ..] => {
+                // 公共前缀（忽略大小写，按字符比较）
+                let common = rest.iter().fold(first.chars().count(), |len, name| {
+                    first
+                        .chars()
+                        .zip(name.chars())
+                        .take(len)
+                        .take_while(|(a, b)| a.to_lowercase().eq(b.to_lowercase()))
+                        .count()
+                });
+                let common: String = first.chars().take(common.max(name_part.chars().count())).collect();
+                let candidates = names.iter().map(|name| format!("{}{}{}", dir_part, name, separator)).collect();
+                (format!("{}{}", dir_part, common), candidates)
+            }
+        }
+    }
+
+    /// 切换到路径输入模式
+    pub fn edit_location(&mut self) {
+        self.location_edit = Some(format!("{}{}", self.current_directory.display(), std::path::MAIN_SEPARATOR));
+        self.location_focus = true;
+    }
+
+    /// 后退到上一个根目录
+    pub fn go_back(&mut self) {
+        if let Some(previous) = self.back_history.pop() {
+            let current = std::mem::replace(&mut self.current_directory, previous);
+            self.forward_history.push(current);
+            self.load_directory_content();
+        }
+    }
+
+    /// 前进到下一个根目录
+    pub fn go_forward(&mut self) {
+        if let Some(next) = self.forward_history.pop() {
+            let current = std::mem::replace(&mut self.current_directory, next);
+            self.back_history.push(current);
+            self.load_directory_content();
+        }
+    }
+
+    /// 收藏列表：点击文件夹将其设为根目录，点击文件打开
+    fn render_favourites(&mut self, ui: &mut egui::Ui, file_path: &Option<PathBuf>, bookmarks: &mut Bookmarks) -> Option<PathBuf> {
+        if bookmarks.favourites.is_empty() {
+            return None;
+        }
+
+        let mut file_to_load = None;
+        egui::CollapsingHeader::new("⭐ 收藏")
+            .id_source("favourites")
+            .default_open(true)
+            .show(ui, |ui| {
+                for path in bookmarks.favourites.clone() {
+                    let name = path.file_name().map_or_else(|| path.display().to_string(), |name| name.to_string_lossy().into_owned());
+                    let exists = path.exists();
+                    let icon = if !exists {
+                        "⚠️"
+                    } else if path.is_dir() {
+                        "📁"
+                    } else {
+                        file_types::registry().for_path(&path).icon.as_str()
+                    };
+
+                    let is_current = file_path.as_ref() == Some(&path) || path == self.current_directory;
+                    let mut text = egui::RichText::new(format!("{} {}", icon, name));
+                    if !exists {
+                        text = text.weak().strikethrough();
+                    }
+                    let response = ui
+                        .selectable_label(is_current, text)
+                        .on_hover_text(path.display().to_string())
+                        .context_menu(|ui| {
+                            if ui.button("从收藏中移除").clicked() {
+                                self.toggle_favourite(bookmarks, &path);
+                                ui.close_menu();
                             }
+                        });
+
+                    if response.clicked() && exists {
+                        if path.is_dir() {
+                            self.set_root(path.clone());
                         } else {
-                            false
-                        };
+                            file_to_load = Some(path.clone());
+                        }
+                    }
+                }
+            });
+        ui.separator();
 
-                        // 添加图标
-                        let icon = if item.is_directory {
-                            "📁 "
-                        } else if item.name.ends_with(".rs") { "🦀 " }
-                            else if item.name.ends_with(".py") { "🐍 " }
-                            else if item.name.ends_with(".js") { "🟨 " }
-                            else if item.name.ends_with(".html") || item.name.ends_with(".htm") { "🌐 " }
-                            else if item.name.ends_with(".css") { "🎨 " }
-                            else if item.name.ends_with(".json") || item.name.ends_with(".xml") { "📄 " }
-                            else if item.name.ends_with(".md") { "📝 " }
-                            else if item.name.ends_with(".gitignore") || item.name.starts_with('.') { "⚙️ " }
-                            else { "📄 " };
-
-                        let display_name = format!("{}{}", icon, item.name);
-
-                        if ui.selectable_label(is_current_file, display_name).clicked() {
-                            if item.is_directory {
-                                directory_to_enter = Some(item.path.clone());
-                            } else {
-                                file_to_load = Some(item.path.clone());
+        file_to_load
+    }
+
+    /// 添加或移除收藏并保存
+    fn toggle_favourite(&mut self, bookmarks: &mut Bookmarks, path: &Path) {
+        bookmarks.toggle_favourite(path);
+        if let Err(e) = bookmarks.save() {
+            self.events.push(FileEvent::Error(format!("保存收藏失败: {}", e)));
+        }
+    }
+
+    /// 条目的右键菜单
+    fn render_context_menu(&mut self, ui: &mut egui::Ui, item: &DirectoryItem, bookmarks: &mut Bookmarks) {
+        // 在文件上新建时放到其所在目录
+        let target_dir = if item.is_directory {
+            item.path.clone()
+        } else {
+            item.path.parent().map_or_else(|| self.current_directory.clone(), Path::to_path_buf)
+        };
+        self.render_new_entry_menu(ui, &target_dir);
+        ui.separator();
+
+        if ui.button("重命名").clicked() {
+            self.inline_edit = Some(InlineEdit {
+                kind: InlineEditKind::Rename(item.path.clone()),
+                text: item.name.clone(),
+                request_focus: true,
+            });
+            ui.close_menu();
+        }
+        if ui.button("创建副本").clicked() {
+            match file_ops::duplicate(&item.path) {
+                Ok(copy) => {
+                    self.refresh_parent_of(&copy);
+                    self.selected = Some(copy);
+                }
+                Err(e) => self.events.push(FileEvent::Error(format!("创建副本失败: {}", e))),
+            }
+            ui.close_menu();
+        }
+        if ui.button("删除").clicked() {
+            self.pending_delete = Some(item.path.clone());
+            ui.close_menu();
+        }
+        ui.separator();
+
+        if !item.is_directory {
+            self.render_compare_menu(ui, item);
+            ui.separator();
+        }
+
+        let favourite_label = if bookmarks.is_favourite(&item.path) { "从收藏中移除" } else { "添加到收藏" };
+        if ui.button(favourite_label).clicked() {
+            self.toggle_favourite(bookmarks, &item.path);
+            ui.close_menu();
+        }
+        ui.separator();
+
+        if ui.button("复制路径").clicked() {
+            ui.ctx().output_mut(|o| o.copied_text = item.path.display().to_string());
+            ui.close_menu();
+        }
+        if ui.button("复制相对路径").clicked() {
+            let relative = item.path.strip_prefix(&self.current_directory).unwrap_or(&item.path);
+            ui.ctx().output_mut(|o| o.copied_text = relative.to_string_lossy().replace('\\', "/"));
+            ui.close_menu();
+        }
+        ui.separator();
+
+        if ui.button("在文件管理器中显示").clicked() {
+            if let Err(e) = file_ops::reveal_in_file_manager(&item.path) {
+                self.events.push(FileEvent::Error(format!("无法打开文件管理器: {}", e)));
+            }
+            ui.close_menu();
+        }
+    }
+
+    /// 比较菜单项：先选择一个文件，再在另一个文件上选择与其比较
+    fn render_compare_menu(&mut self, ui: &mut egui::Ui, item: &DirectoryItem) {
+        if let Some(base) = self.compare_base.clone().filter(|base| base != &item.path) {
+            let name = base.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+            if ui.button(format!("与“{}”比较", name)).clicked() {
+                self.events.push(FileEvent::Compare { left: base, right: item.path.clone() });
+                self.compare_base = None;
+                ui.close_menu();
+            }
+        }
+        if ui.button("选择以进行比较").clicked() {
+            self.compare_base = Some(item.path.clone());
+            ui.close_menu();
+        }
+    }
+
+    /// 新建文件/文件夹菜单项
+    fn render_new_entry_menu(&mut self, ui: &mut egui::Ui, dir: &Path) {
+        if ui.button("新建文件").clicked() {
+            self.begin_new_entry(InlineEditKind::NewFile(dir.to_path_buf()));
+            ui.close_menu();
+        }
+        if ui.button("新建文件夹").clicked() {
+            self.begin_new_entry(InlineEditKind::NewFolder(dir.to_path_buf()));
+            ui.close_menu();
+        }
+    }
+
+    /// 展开目标目录并显示新建输入行
+    fn begin_new_entry(&mut self, kind: InlineEditKind) {
+        if let InlineEditKind::NewFile(dir) | InlineEditKind::NewFolder(dir) = &kind {
+            if dir != &self.current_directory {
+                self.expanded.insert(dir.clone());
+                self.rows = None;
+            }
+        }
+        self.inline_edit = Some(InlineEdit { kind, text: String::new(), request_focus: true });
+    }
+
+    fn is_renaming(&self, path: &Path) -> bool {
+        matches!(&self.inline_edit, Some(InlineEdit { kind: InlineEditKind::Rename(target), .. }) if target == path)
+    }
+
+    /// 绘制行内输入框：回车确认，Esc 或点击别处取消
+    fn render_inline_edit(&mut self, ui: &mut egui::Ui, row: &TreeRow, row_height: f32) {
+        let (rect, _) = ui.allocate_exact_size(egui::vec2(ui.available_width(), row_height), egui::Sense::hover());
+        let Some(edit) = &mut self.inline_edit else {
+            return;
+        };
+
+        let icon = if row.item.is_directory { "📁" } else { "📄" };
+        let indent = 6.0 + row.depth as f32 * 14.0 + 14.0;
+        ui.painter().text(
+            rect.left_center() + egui::vec2(indent, 0.0),
+            egui::Align2::LEFT_CENTER,
+            icon,
+            egui::TextStyle::Body.resolve(ui.style()),
+            ui.visuals().text_color(),
+        );
+
+        let text_rect = egui::Rect::from_min_max(rect.min + egui::vec2(indent + 22.0, 0.0), rect.max);
+        let request_focus = std::mem::take(&mut edit.request_focus);
+        let mut output = None;
+        ui.put(text_rect, |ui: &mut egui::Ui| {
+            let text_output = egui::TextEdit::singleline(&mut edit.text)
+                .margin(egui::vec2(2.0, 0.0))
+                .desired_width(f32::INFINITY)
+                .show(ui);
+            let response = text_output.response.clone();
+            output = Some(text_output);
+            response
+        });
+        let Some(mut output) = output else {
+            return;
+        };
+
+        if request_focus {
+            // 重命名时只选中扩展名之前的部分
+            let stem_len = match edit.text.rfind('.') {
+                Some(dot) if dot > 0 && !row.item.is_directory => edit.text[..dot].chars().count(),
+                _ => edit.text.chars().count(),
+            };
+            output.state.set_ccursor_range(Some(egui::text::CCursorRange::two(
+                egui::text::CCursor::new(0),
+                egui::text::CCursor::new(stem_len),
+            )));
+            output.state.store(ui.ctx(), output.response.id);
+            output.response.request_focus();
+        }
+
+        if output.response.lost_focus() {
+            if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                self.commit_inline_edit();
+            } else {
+                self.inline_edit = None;
+            }
+        }
+    }
+
+    /// 执行重命名或新建
+    fn commit_inline_edit(&mut self) {
+        let Some(edit) = self.inline_edit.take() else {
+            return;
+        };
+        let name = edit.text.trim();
+        if name.is_empty() {
+            return;
+        }
+        // 只接受单个文件名，不能借此移动到其他目录
+        if name.contains(['/', '\\']) || name == "." || name == ".." {
+            self.events.push(FileEvent::Error(format!("无效的文件名: {}", name)));
+            return;
+        }
+
+        match edit.kind {
+            InlineEditKind::Rename(from) => {
+                let to = from.with_file_name(name);
+                if to == from {
+                    return;
+                }
+                match file_ops::rename(&from, &to) {
+                    Ok(()) => {
+                        self.remap_paths(&from, &to);
+                        self.refresh_parent_of(&to);
+                        self.events.push(FileEvent::Moved { from, to });
+                    }
+                    Err(e) => self.events.push(FileEvent::Error(format!("重命名失败: {}", e))),
+                }
+            }
+            InlineEditKind::NewFile(dir) => {
+                let path = dir.join(name);
+                match file_ops::create_file(&path) {
+                    Ok(()) => {
+                        self.refresh_parent_of(&path);
+                        self.events.push(FileEvent::Created(path));
+                    }
+                    Err(e) => self.events.push(FileEvent::Error(format!("新建文件失败: {}", e))),
+                }
+            }
+            InlineEditKind::NewFolder(dir) => {
+                let path = dir.join(name);
+                match file_ops::create_directory(&path) {
+                    Ok(()) => {
+                        self.refresh_parent_of(&path);
+                        self.selected = Some(path);
+                    }
+                    Err(e) => self.events.push(FileEvent::Error(format!("新建文件夹失败: {}", e))),
+                }
+            }
+        }
+    }
+
+    /// 删除确认对话框
+    fn render_delete_confirmation(&mut self, ctx: &egui::Context) {
+        let Some(path) = self.pending_delete.clone() else {
+            return;
+        };
+        let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+
+        egui::Window::new("确认删除")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                ui.label(format!("确定要将 \"{}\" 移到回收站吗？", name));
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("移到回收站").clicked() {
+                        self.pending_delete = None;
+                        match file_ops::move_to_trash(&path) {
+                            Ok(()) => {
+                                self.expanded.retain(|dir| !dir.starts_with(&path));
+                                self.children.retain(|dir, _| !dir.starts_with(&path));
+                                self.rows = None;
+                                self.refresh_parent_of(&path);
+                                self.events.push(FileEvent::Deleted(path.clone()));
                             }
+                            Err(e) => self.events.push(FileEvent::Error(format!("删除失败: {}", e))),
                         }
                     }
-                }
+                    if ui.button("取消").clicked() {
+                        self.pending_delete = None;
+                    }
+                });
             });
+    }
+
+    /// 重命名后更新展开状态、缓存和选中项中的路径
+    fn remap_paths(&mut self, from: &Path, to: &Path) {
+        let remap = |path: &Path| match path.strip_prefix(from) {
+            Ok(rest) if rest.as_os_str().is_empty() => to.to_path_buf(),
+            Ok(rest) => to.join(rest),
+            Err(_) => path.to_path_buf(),
+        };
+
+        self.expanded = self.expanded.iter().map(|dir| remap(dir)).collect();
+        self.rows = None;
+        self.children = std::mem::take(&mut self.children)
+            .into_iter()
+            .map(|(dir, items)| {
+                let items = items
+                    .into_iter()
+                    .map(|item| DirectoryItem { path: remap(&item.path), ..item })
+                    .collect();
+                (remap(&dir), items)
+            })
+            .collect();
+        self.selected = self.selected.as_deref().map(remap);
+        self.revealed_file = self.revealed_file.as_deref().map(remap);
+    }
+
+    /// 重新读取条目所在的目录
+    fn refresh_parent_of(&mut self, path: &Path) {
+        if let Some(parent) = path.parent() {
+            self.refresh_directory(parent);
+        }
+    }
+
+    /// 绘制一行：缩进、展开标记、图标、名称和 Git 状态
+    fn render_row(
+        &self,
+        ui: &mut egui::Ui,
+        row: &TreeRow,
+        row_height: f32,
+        is_current_file: bool,
+        is_selected: bool,
+        git_status: Option<FileStatus>,
+    ) -> egui::Response {
+        let item = &row.item;
+        let (rect, response) = ui.allocate_exact_size(egui::vec2(ui.available_width(), row_height), egui::Sense::click());
+
+        let visuals = ui.visuals();
+        if is_current_file || (is_selected && self.has_focus) {
+            ui.painter().rect_filled(rect, 2.0, visuals.selection.bg_fill);
+        } else if is_selected || response.hovered() {
+            ui.painter().rect_filled(rect, 2.0, visuals.widgets.hovered.weak_bg_fill);
+        }
+
+        // 展开标记
+        let indent = 6.0 + row.depth as f32 * 14.0;
+        if item.is_directory {
+            let arrow = if self.expanded.contains(&item.path) { "▼" } else { "▶" };
+            ui.painter().text(
+                rect.left_center() + egui::vec2(indent, 0.0),
+                egui::Align2::LEFT_CENTER,
+                arrow,
+                egui::FontId::proportional(10.0),
+                visuals.weak_text_color(),
+            );
+        }
+
+        // 添加图标
+        let icon = if item.error.is_some() {
+            "⚠️"
+        } else if item.is_directory {
+            "📁"
+        } else {
+            file_types::registry().lookup(&item.name).icon.as_str()
+        };
+
+        // 右侧元数据列（从右向左排列）
+        let font = egui::TextStyle::Body.resolve(ui.style());
+        let mut columns_left = rect.right() - 4.0;
+
+        // Git 状态字母（目录只用颜色表示其中有变化）
+        let status_color = git_status.map(|status| git_status_color(status, visuals));
+        if let (Some(status), Some(color)) = (git_status, status_color) {
+            if !item.is_directory {
+                let galley_rect = ui.painter().text(
+                    egui::pos2(columns_left, rect.center().y),
+                    egui::Align2::RIGHT_CENTER,
+                    status.letter(),
+                    font.clone(),
+                    color,
+                );
+                columns_left = galley_rect.left() - 8.0;
+            }
+        }
+
+        let mut columns = Vec::new();
+        if self.show_modified_column {
+            columns.push(item.modified.map(directory_listing::format_modified).unwrap_or_default());
+        }
+        if self.show_size_column && !item.is_directory && item.error.is_none() {
+            columns.push(directory_listing::format_size(item.size));
+        }
+        for text in columns {
+            let galley_rect = ui.painter().text(
+                egui::pos2(columns_left, rect.center().y),
+                egui::Align2::RIGHT_CENTER,
+                text,
+                font.clone(),
+                visuals.weak_text_color(),
+            );
+            columns_left = galley_rect.left() - 10.0;
+        }
+
+        // 符号链接追加标记，无法读取的条目使用错误颜色
+        let link_mark = if item.symlink_target.is_some() { " ↪" } else { "" };
+        let display_name = format!("{} {}{}", icon, item.name, link_mark);
+        let text_color = if item.error.is_some() {
+            visuals.error_fg_color
+        } else {
+            status_color.unwrap_or(visuals.text_color())
+        };
+        let name_clip = egui::Rect::from_min_max(rect.min, egui::pos2(columns_left, rect.max.y));
+        ui.painter().with_clip_rect(name_clip.intersect(ui.clip_rect())).text(
+            rect.left_center() + egui::vec2(indent + 14.0, 0.0),
+            egui::Align2::LEFT_CENTER,
+            display_name,
+            font,
+            text_color,
+        );
+
+        response
+    }
+
+    /// 悬停提示：完整路径和元数据
+    fn render_tooltip(&mut self, ui: &mut egui::Ui, item: &DirectoryItem, git_status: Option<FileStatus>) {
+        ui.label(item.path.display().to_string());
+        if let Some(error) = &item.error {
+            ui.colored_label(ui.visuals().error_fg_color, format!("⚠ {}", error));
+        }
+        if let Some(target) = &item.symlink_target {
+            ui.label(format!("链接到: {}", target.display()));
+        }
+        if item.error.is_some() {
+            return;
+        }
+
+        egui::Grid::new("file_tooltip").num_columns(2).show(ui, |ui| {
+            if !item.is_directory {
+                ui.label("类型");
+                ui.label(&file_types::registry().lookup(&item.name).name);
+                ui.end_row();
+
+                ui.label("大小");
+                ui.label(format!("{} ({} 字节)", directory_listing::format_size(item.size), item.size));
+                ui.end_row();
+
+                ui.label("行数");
+                let lines = self.line_count(ui.ctx(), item);
+                ui.label(lines.map_or_else(|| "—".to_string(), |lines| lines.to_string()));
+                ui.end_row();
+            }
+            if let Some(modified) = item.modified {
+                ui.label("修改时间");
+                ui.label(format!("{} UTC", directory_listing::format_timestamp(modified)));
+                ui.end_row();
+            }
+            ui.label("权限");
+            ui.label(&item.permissions);
+            ui.end_row();
+
+            if let Some(status) = git_status {
+                ui.label("Git");
+                ui.colored_label(git_status_color(status, ui.visuals()), status.title());
+                ui.end_row();
+            }
+        });
+    }
+
+    /// 文件行数（首次悬停时在后台统计，文件修改后重新统计；统计完成前为 None）
+    fn line_count(&mut self, ctx: &egui::Context, item: &DirectoryItem) -> Option<usize> {
+        if let Some((modified, receiver)) = self.line_count_jobs.get(&item.path) {
+            let lines = match receiver.try_recv() {
+                Err(TryRecvError::Empty) => return None,
+                result => result.ok().flatten(),
+            };
+            let modified = *modified;
+            self.line_count_jobs.remove(&item.path);
+            self.line_counts.insert(item.path.clone(), (modified, lines));
+        }
+        match self.line_counts.get(&item.path) {
+            Some((modified, lines)) if *modified == item.modified => *lines,
+            _ => {
+                let receiver = directory_listing::spawn_count_lines(ctx.clone(), item.path.clone());
+                self.line_count_jobs.insert(item.path.clone(), (item.modified, receiver));
+                None
+            }
+        }
+    }
+
+    /// 将根目录及已展开目录展平为行列表（缓存到目录内容、展开状态或筛选条件变化）
+    fn collect_rows(&mut self) -> Rc<Vec<TreeRow>> {
+        let rows = match &self.rows {
+            Some(rows) => rows.clone(),
+            None => {
+                self.load_expanded_children();
+                let mut rows = Vec::new();
+                self.push_rows(&self.directory_items, 0, &mut rows);
+                let rows = Rc::new(rows);
+                self.rows = Some(rows.clone());
+                rows
+            }
+        };
+        // 新建输入行只在新建期间存在，不放入缓存
+        match &self.inline_edit {
+            Some(InlineEdit { kind: InlineEditKind::NewFile(_) | InlineEditKind::NewFolder(_), .. }) => {
+                let mut rows = rows.as_ref().clone();
+                self.insert_new_entry_row(&mut rows);
+                Rc::new(rows)
+            }
+            _ => rows,
+        }
+    }
+
+    /// 读取展开后可见、尚未加载的子目录（逐层加载，直到所有可见的展开目录都已加载）
+    fn load_expanded_children(&mut self) {
+        loop {
+            let mut missing = Vec::new();
+            self.find_unloaded(&self.directory_items, &mut missing);
+            if missing.is_empty() {
+                return;
+            }
+            for dir in missing {
+                let items = directory_listing::read_directory(&dir, &self.listing);
+                self.children.insert(dir, items);
+            }
+        }
+    }
+
+    fn find_unloaded(&self, items: &[DirectoryItem], missing: &mut Vec<PathBuf>) {
+        for item in items.iter().filter(|item| item.is_directory && self.expanded.contains(&item.path)) {
+            match self.children.get(&item.path) {
+                Some(children) => self.find_unloaded(children, missing),
+                None => missing.push(item.path.clone()),
+            }
+        }
+    }
+
+    /// 新建文件/文件夹时在目标目录的第一个子项位置插入输入行
+    fn insert_new_entry_row(&self, rows: &mut Vec<TreeRow>) {
+        let (dir, is_directory) = match &self.inline_edit {
+            Some(InlineEdit { kind: InlineEditKind::NewFile(dir), .. }) => (dir, false),
+            Some(InlineEdit { kind: InlineEditKind::NewFolder(dir), .. }) => (dir, true),
+            _ => return,
+        };
+
+        let (index, depth) = if dir == &self.current_directory {
+            (0, 0)
+        } else {
+            match rows.iter().position(|row| &row.item.path == dir) {
+                Some(index) => (index + 1, rows[index].depth + 1),
+                None => return,
+            }
+        };
+        let item = DirectoryItem { path: dir.clone(), is_directory, ..Default::default() };
+        rows.insert(index, TreeRow { depth, item, is_new_entry: true });
+    }
+
+    fn push_rows(&self, items: &[DirectoryItem], depth: usize, rows: &mut Vec<TreeRow>) {
+        for item in items {
+            if !item.is_directory && !directory_listing::matches_filter(&item.name, &self.filter) {
+                continue;
+            }
+            rows.push(TreeRow { depth, item: item.clone(), is_new_entry: false });
+            if item.is_directory && self.expanded.contains(&item.path) {
+                if let Some(children) = self.children.get(&item.path) {
+                    self.push_rows(children, depth + 1, rows);
+                }
+            }
+        }
+    }
+
+    /// 展开或折叠目录；折叠的目录不再被监视，丢弃其内容缓存，下次展开时重新读取
+    fn toggle_expanded(&mut self, dir: &Path) {
+        if self.expanded.remove(dir) {
+            self.children.remove(dir);
+        } else {
+            self.expanded.insert(dir.to_path_buf());
+        }
+        self.rows = None;
+    }
+
+    /// 展开文件所在的各级目录并选中该文件
+    fn reveal(&mut self, file: &Path) {
+        let Ok(relative) = file.strip_prefix(&self.current_directory) else {
+            return;
+        };
+
+        let mut dir = self.current_directory.clone();
+        if let Some(parent) = relative.parent() {
+            for component in parent.components() {
+                dir.push(component);
+                self.expanded.insert(dir.clone());
+            }
+        }
+        self.rows = None;
+        self.selected = Some(file.to_path_buf());
+
+        let rows = self.collect_rows();
+        self.scroll_to_row = rows.iter().position(|row| row.item.path == file);
+    }
+
+    /// 键盘导航：上下移动、右展开、左折叠或跳到父目录、回车打开、Delete 删除
+    fn handle_keyboard(&mut self, ui: &egui::Ui, rows: &[TreeRow]) -> Option<PathBuf> {
+        // 文本输入框获得焦点时不处理
+        if !self.has_focus || rows.is_empty() || ui.memory(|m| m.focus().is_some()) {
+            return None;
+        }
+
+        let (up, down, left, right, enter, delete) = ui.input_mut(|i| {
+            (
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowLeft),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowRight),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::Enter),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::Delete),
+            )
+        });
+
+        let current = self
+            .selected
+            .as_ref()
+            .and_then(|selected| rows.iter().position(|row| &row.item.path == selected));
+        let Some(index) = current else {
+            if up || down {
+                self.select_row(rows, 0);
+            }
+            return None;
+        };
+        let row = &rows[index];
+
+        if up && index > 0 {
+            self.select_row(rows, index - 1);
+        }
+        if down && index + 1 < rows.len() {
+            self.select_row(rows, index + 1);
+        }
+        if right && row.item.is_directory {
+            if self.expanded.contains(&row.item.path) {
+                // 已展开时移动到第一个子项
+                if rows.get(index + 1).is_some_and(|next| next.depth > row.depth) {
+                    self.select_row(rows, index + 1);
+                }
+            } else {
+                self.expanded.insert(row.item.path.clone());
+                self.rows = None;
+            }
+        }
+        if left {
+            if row.item.is_directory && self.expanded.contains(&row.item.path) {
+                self.toggle_expanded(&row.item.path);
+            } else if let Some(parent) = rows[..index].iter().rposition(|r| r.depth < row.depth) {
+                self.select_row(rows, parent);
+            }
+        }
+        if delete && !row.is_new_entry {
+            self.pending_delete = Some(row.item.path.clone());
+        }
+        if enter && row.item.error.is_none() {
+            if row.item.is_directory {
+                self.toggle_expanded(&row.item.path);
+            } else {
+                return Some(row.item.path.clone());
+            }
+        }
+        None
+    }
+
+    fn select_row(&mut self, rows: &[TreeRow], index: usize) {
+        self.selected = Some(rows[index].item.path.clone());
+        self.scroll_to_row = Some(index);
+    }
+
+    /// 需要监视的目录：根目录和所有已加载的子目录
+    pub fn watched_directories(&self) -> HashSet<PathBuf> {
+        let mut directories: HashSet<PathBuf> = self
+            .children
+            .keys()
+            .filter(|dir| self.expanded.contains(*dir))
+            .cloned()
+            .collect();
+        directories.insert(self.current_directory.clone());
+        directories
+    }
+
+    /// 目录内容在磁盘上发生变化时重新读取
+    pub fn refresh_directory(&mut self, dir: &Path) {
+        if dir == self.current_directory {
+            self.load_directory_content();
+        }
+        if let Some(children) = self.children.get_mut(dir) {
+            *children = directory_listing::read_directory(dir, &self.listing);
+            self.rows = None;
+        }
+    }
+
+    /// 切换工作区根目录
+    pub fn set_root(&mut self, root: PathBuf) {
+        if root != self.current_directory {
+            let previous = std::mem::replace(&mut self.current_directory, root);
+            self.back_history.push(previous);
+            self.forward_history.clear();
+        }
+        self.load_directory_content();
+    }
+
+    /// 返回上级目录
+    pub fn go_to_parent(&mut self) {
+        if let Some(parent) = self.current_directory.parent() {
+            self.set_root(parent.to_path_buf());
+        }
+    }
+
+    /// 重新读取根目录和所有已加载的子目录
+    pub fn refresh(&mut self) {
+        self.children.clear();
+        self.line_counts.clear();
+        self.line_count_jobs.clear();
+        self.load_directory_content();
+    }
+
+    /// 加载当前目录的内容
+    pub fn load_directory_content(&mut self) {
+        self.directory_items = directory_listing::read_directory(&self.current_directory, &self.listing);
+        self.rows = None;
+    }
+}
+
+/// blame 列宽度
+const BLAME_COLUMN_WIDTH: f32 = 240.0;
+/// 缩略图宽度、每行的最大高度和每个字符的宽度
+const MINIMAP_WIDTH: f32 = 90.0;
+const MINIMAP_ROW_HEIGHT: f32 = 2.0;
+const MINIMAP_CHAR_WIDTH: f32 = 1.0;
+/// 概览标尺宽度
+const OVERVIEW_RULER_WIDTH: f32 = 12.0;
+
+/// 缩略图中一段连续的非空白字符
+fn paint_minimap_run(painter: &egui::Painter, rect: egui::Rect, y: f32, height: f32, start: usize, end: usize, color: egui::Color32) {
+    let left = rect.left() + 2.0 + start as f32 * MINIMAP_CHAR_WIDTH;
+    let right = (rect.left() + 2.0 + end as f32 * MINIMAP_CHAR_WIDTH).min(rect.right());
+    if right > left {
+        painter.rect_filled(egui::Rect::from_x_y_ranges(left..=right, y..=y + height), 0.0, color.gamma_multiply(0.8));
+    }
+}
+
+/// 取出 LayoutJob 中的一段文本（保留各片段的格式；从片段中间截断时不保留片段前的空距）
+fn slice_layout_job(job: &egui::text::LayoutJob, range: std::ops::Range<usize>) -> egui::text::LayoutJob {
+    let mut slice = egui::text::LayoutJob::default();
+    for section in &job.sections {
+        let start = section.byte_range.start.max(range.start);
+        let end = section.byte_range.end.min(range.end);
+        if start < end {
+            let leading_space = if section.byte_range.start > range.start { section.leading_space } else { 0.0 };
+            slice.append(&job.text[start..end], leading_space, section.format.clone());
+        }
+    }
+    slice
+}
+
+/// 把制表符对齐到每 tab_width 列一个的制表位：epaint 中的制表符固定为 TAB_SIZE 个空格宽，
+/// 在每个制表符之后拆开片段，用下一片段前的空距补足差值（可以为负）
+fn expand_tabs(job: &mut egui::text::LayoutJob, tab_width: usize, space_width: f32) {
+    if !job.text.contains('\t') {
+        return;
+    }
+    let mut sections = Vec::with_capacity(job.sections.len());
+    let mut column = 0;
+    let mut carry = 0.0; // 片段末尾的制表符留给下一片段的空距
+    for section in std::mem::take(&mut job.sections) {
+        let mut start = section.byte_range.start;
+        let mut leading_space = section.leading_space + std::mem::take(&mut carry);
+        for (index, ch) in job.text[section.byte_range.clone()].char_indices() {
+            match ch {
+                '\t' => {
+                    let advance = wrap::tab_advance(column, tab_width);
+                    column += advance;
+                    let end = section.byte_range.start + index + 1;
+                    sections.push(egui::text::LayoutSection { leading_space, byte_range: start..end, format: section.format.clone() });
+                    start = end;
+                    leading_space = (advance as f32 - egui::text::TAB_SIZE as f32) * space_width;
+                }
+                '\n' => column = 0,
+                _ => column += wrap::char_columns(ch),
+            }
+        }
+        if start < section.byte_range.end {
+            sections.push(egui::text::LayoutSection { leading_space, byte_range: start..section.byte_range.end, format: section.format });
+        } else {
+            carry = leading_space;
+        }
+    }
+    job.sections = sections;
+}
+
+/// galley 中第 index 个字符所占的矩形（相对于 galley 左上角）
+fn galley_char_rect(galley: &egui::Galley, index: usize) -> egui::Rect {
+    let left = galley.pos_from_cursor(&galley.from_ccursor(egui::text::CCursor::new(index)));
+    let right = galley.pos_from_cursor(&galley.from_ccursor(egui::text::CCursor::new(index + 1)));
+    egui::Rect::from_x_y_ranges(left.left()..=right.left().max(left.left()), left.y_range())
+}
+
+/// galley 中各字符的位置，按源文本的行分组（自动换行的各显示行属于同一行）
+/// 字符的右边界取下一个字符的左边界，展开后的制表符才占满到制表位
+fn galley_lines(galley: &egui::Galley, origin: egui::Pos2) -> Vec<Vec<(egui::Rect, char)>> {
+    let mut lines = vec![Vec::new()];
+    for row in &galley.rows {
+        let line = lines.last_mut().expect("至少有一行");
+        for (index, glyph) in row.glyphs.iter().enumerate() {
+            let right = row.glyphs.get(index + 1).map_or(glyph.max_x(), |next| next.pos.x);
+            let rect = egui::Rect::from_x_y_ranges(glyph.pos.x..=right.max(glyph.pos.x), row.rect.y_range());
+            line.push((rect.translate(origin.to_vec2()), glyph.chr));
+        }
+        if row.ends_with_newline {
+            lines.push(Vec::new());
+        }
+    }
+    lines
+}
+
+/// 空白字符：空格画点、制表符画箭头，行尾空白和与文件缩进方式不一致的缩进加底色
+/// glyphs 为一行（或其中一段）的字符和位置，offset 为第一个字符在行中的字节位置
+fn paint_whitespace(painter: &egui::Painter, line: &str, offset: usize, glyphs: &[(egui::Rect, char)], indent: Indent, tab_width: usize, visuals: &egui::Visuals) {
+    let content = line.trim_end_matches('\r');
+    let trailing = content.trim_end_matches([' ', '\t']).len();
+    let leading = content.len() - content.trim_start_matches([' ', '\t']).len();
+    let inconsistent = indentation::is_inconsistent(&content[..leading], indent, tab_width);
+    let color = visuals.weak_text_color().gamma_multiply(0.6);
+    let mut byte = offset;
+    for &(rect, ch) in glyphs {
+        if ch == ' ' || ch == '\t' {
+            if byte >= trailing {
+                painter.rect_filled(rect, 0.0, visuals.error_fg_color.gamma_multiply(0.25));
+            } else if inconsistent && byte < leading {
+                painter.rect_filled(rect, 0.0, visuals.warn_fg_color.gamma_multiply(0.25));
+            }
+            let center = rect.center();
+            if ch == ' ' {
+                painter.circle_filled(center, 1.0, color);
+            } else {
+                let (left, right) = (rect.left() + 2.0, (rect.right() - 2.0).max(rect.left() + 6.0));
+                let stroke = egui::Stroke::new(1.0, color);
+                painter.hline(left..=right, center.y, stroke);
+                painter.line_segment([egui::pos2(right - 3.0, center.y - 3.0), egui::pos2(right, center.y)], stroke);
+                painter.line_segment([egui::pos2(right - 3.0, center.y + 3.0), egui::pos2(right, center.y)], stroke);
+            }
+        }
+        byte += ch.len_utf8();
+    }
+}
+
+/// 是否为参与匹配的括号
+fn is_bracket(byte: u8) -> bool {
+    b"()[]{}".contains(&byte)
+}
+
+/// 自动换行方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SoftWrap {
+    Off,
+    Window,         // 按窗口宽度
+    Column(usize),  // 按固定列数
+}
+
+impl SoftWrap {
+    /// 底栏中可选的换行方式
+    pub const CHOICES: [SoftWrap; 5] = [SoftWrap::Off, SoftWrap::Window, SoftWrap::Column(80), SoftWrap::Column(100), SoftWrap::Column(120)];
+
+    pub fn label(self) -> String {
+        match self {
+            SoftWrap::Off => "不换行".to_string(),
+            SoftWrap::Window => "按窗口宽度".to_string(),
+            SoftWrap::Column(columns) => format!("{} 列", columns),
+        }
+    }
+}
+
+/// 代码编辑器组件
+pub struct CodeEditor {
+    pub code: String,
+    syntax_highlighter: SyntaxHighlighter,
+    show_syntax_highlighting: bool, // true = 语法高亮只读, false = 编辑模式
+    cached_highlighted_lines: Vec<egui::text::LayoutJob>,
+    cached_line_hashes: Vec<u64>, // 高亮缓存中每行文本的哈希（找出变化的行）
+    layout_job_pool: Vec<egui::text::LayoutJob>, // LayoutJob对象内存池
+    last_code_hash: u64,
+    code_hash: u64, // 当前内容的哈希（语法高亮模式下由高亮缓存更新，编辑模式下在修改后更新）
+    cached_line_height: f32,
+    last_scroll_position: f32, // 上次滚动位置，用于检测滚动状态
+    is_scrolling: bool,         // 是否正在滚动
+    scroll_timer: u32,          // 滚动计时器，用于延迟语法高亮
+    pending_scroll_offset: Option<f32>, // 下一帧需要跳转到的滚动位置
+    scroll_offset: f32,         // 上一帧的滚动位置（同步滚动时使用）
+    pub follow_mode: bool,      // 跟随文件末尾（类似 tail -f）
+    language: Option<String>,   // 语法高亮使用的语言（来自文件类型注册表）
+    pub current_line: usize,    // 当前行（从 0 开始，书签操作的目标）
+    last_cursor_index: usize,   // 编辑模式下上一帧的光标字符位置
+    jump_line_height: f32,      // 当前模式下的行高（跳转到指定行时使用）
+    pub show_blame: bool,       // 是否显示逐行追溯列
+    pub blame: Option<Blame>,   // 后台计算完成的 blame（计算中为 None）
+    clicked_commit: Option<String>, // 在 blame 列中点击的提交
+    pub parse_error: Option<ParseError>, // 结构化文档的解析错误（在标记栏中标出）
+    column_delimiter: Option<u8>, // CSV/TSV 识别出的分隔符（按列着色）
+    fold_regions: Vec<folding::FoldRegion>, // 可折叠区域（按首行排序）
+    fold_hash: u64,             // 计算折叠区域时的代码哈希
+    fold_lines: Vec<u64>,       // 计算折叠区域时每行的哈希（修改后移动已折叠的区域）
+    folded: BTreeSet<usize>,    // 已折叠区域的首行
+    hidden_ranges: Vec<(usize, usize)>, // 折叠后隐藏的行区间
+    pub show_minimap: bool,     // 在代码右侧显示缩略图和概览标尺（语法高亮模式）
+    find_query: String,
+    find_matches: Vec<usize>,   // 包含查找文本的行
+    find_key: Option<(u64, String)>, // 计算查找结果时的代码哈希和查找文本（编辑模式下修改后清空）
+    focus_find: bool,           // 下一帧将焦点移到查找框
+    pub soft_wrap: SoftWrap,    // 自动换行方式
+    wrap_columns: usize,        // 语法高亮模式下生效的换行列数（0 表示不换行）
+    wrap_counts: Vec<usize>,    // 每行拆分出的显示行数
+    wrap_key: (u64, usize, usize), // 计算 wrap_counts 时的代码哈希、列数和制表符宽度
+    wrap_index: wrap::WrapIndex, // 可见行到显示行的换算
+    wrap_hidden: Vec<(usize, usize)>, // 建立 wrap_index 时的隐藏行
+    rainbow_brackets: bool,     // 括号按嵌套深度着色
+    bracket_depths: Vec<usize>, // 每行行首的括号嵌套深度（彩虹括号）
+    bracket_hover: Option<(usize, usize)>,  // 指针下的括号（行, 字节位置）
+    bracket_anchor: Option<(usize, usize)>, // 点击或跳转到的括号
+    bracket_key: (Option<(usize, usize)>, u64), // 计算 bracket_pair 时的目标括号和代码哈希
+    bracket_pair: Option<((usize, usize), (usize, usize))>, // 语法高亮模式下标出的括号对
+    edit_brackets: Option<(usize, usize)>, // 编辑模式下光标旁的括号和与之匹配的括号（字节位置）
+    pending_cursor: Option<usize>, // 编辑模式下一帧将光标移到的字符位置
+    pub show_indent_guides: bool, // 显示缩进参考线
+    indent: Indent,             // 识别出的缩进方式
+    line_indents: Vec<usize>,   // 每行的缩进列数
+    indent_hash: u64,           // 计算缩进时的代码哈希
+    text_info_dirty: bool,      // 编辑模式下文本已变化，需要重新识别缩进和换行符
+    line_ending: LineEnding,    // 识别出的换行符
+    inconsistent_indent: bool,  // 是否有与缩进方式不一致的行
+    pub show_whitespace: bool,  // 显示空格和制表符，标出行尾空白和不一致的缩进
+    tab_width: usize,           // 制表位间隔的列数
+    tab_width_fixed: bool,      // 制表符宽度来自 .editorconfig 或手动设置（不再按缩进推测）
+    space_width: f32,           // 语法高亮字体中空格的宽度（展开制表符时使用）
+    cursor_column: usize,       // 当前列（从 0 开始，制表符按制表位计算）
+}
+
+impl CodeEditor {
+    pub fn new(code: String) -> Self {
+        let code_hash = Self::calculate_code_hash(&code);
+        Self {
+            code,
+            syntax_highlighter: SyntaxHighlighter::new(),
+            show_syntax_highlighting: true, // 默认语法高亮模式
+            cached_highlighted_lines: Vec::new(),
+            cached_line_hashes: Vec::new(),
+            layout_job_pool: Vec::new(), // 初始化内存池
+            last_code_hash: code_hash,
+            code_hash,
+            cached_line_height: 0.0,
+            last_scroll_position: 0.0,
+            is_scrolling: false,
+            scroll_timer: 0,
+            pending_scroll_offset: None,
+            scroll_offset: 0.0,
+            follow_mode: false,
+            language: None,
+            current_line: 0,
+            last_cursor_index: 0,
+            jump_line_height: 0.0,
+            show_blame: false,
+            blame: None,
+            clicked_commit: None,
+            parse_error: None,
+            column_delimiter: None,
+            fold_regions: Vec::new(),
+            fold_hash: 0,
+            fold_lines: Vec::new(),
+            folded: BTreeSet::new(),
+            hidden_ranges: Vec::new(),
+            show_minimap: true,
+            find_query: String::new(),
+            find_matches: Vec::new(),
+            find_key: None,
+            focus_find: false,
+            soft_wrap: SoftWrap::Off,
+            wrap_columns: 0,
+            wrap_counts: Vec::new(),
+            wrap_key: (0, 0, 0),
+            wrap_index: wrap::WrapIndex::default(),
+            wrap_hidden: Vec::new(),
+            rainbow_brackets: false,
+            bracket_depths: Vec::new(),
+            bracket_hover: None,
+            bracket_anchor: None,
+            bracket_key: (None, 0),
+            bracket_pair: None,
+            edit_brackets: None,
+            pending_cursor: None,
+            show_indent_guides: true,
+            indent: Indent::Spaces(4),
+            line_indents: Vec::new(),
+            indent_hash: 0,
+            text_info_dirty: true,
+            line_ending: LineEnding::Lf,
+            inconsistent_indent: false,
+            show_whitespace: false,
+            tab_width: indentation::DEFAULT_TAB_WIDTH,
+            tab_width_fixed: false,
+            space_width: 0.0,
+            cursor_column: 0,
+        }
+    }
+
+    /// 显示或隐藏 blame 列（只在语法高亮模式下显示，打开时切换到该模式）
+    pub fn set_blame_visible(&mut self, visible: bool) {
+        self.show_blame = visible;
+        self.blame = None;
+        if visible {
+            self.show_syntax_highlighting = true;
+        }
+    }
+
+    /// 取出在 blame 列中点击的提交
+    pub fn take_clicked_commit(&mut self) -> Option<String> {
+        self.clicked_commit.take()
+    }
+
+    /// 切换语法高亮语言，语言变化时重新高亮全部行
+    pub fn set_language(&mut self, language: Option<String>) {
+        if self.language != language {
+            self.language = language;
+            self.last_code_hash = 0;
+        }
+    }
+
+    /// 设置 CSV/TSV 按列着色使用的分隔符，变化时重新高亮全部行
+    pub fn set_column_delimiter(&mut self, delimiter: Option<u8>) {
+        if self.column_delimiter != delimiter {
+            self.column_delimiter = delimiter;
+            self.last_code_hash = 0;
+        }
+    }
+
+    /// 渲染代码编辑器
+    pub fn render(
+        &mut self,
+        ui: &mut egui::Ui,
+        available_height: f32,
+        bookmarks: &BTreeSet<usize>,
+        line_changes: &HashMap<usize, LineChange>,
+        commands: &mut Vec<Command>,
+    ) {
+        ui.set_width(ui.available_width());
+        ui.set_min_height(available_height);
+
+        // 检测滚动状态
+        self.detect_scrolling_state(ui);
+
+        // 缩略图和概览标尺占用右侧，只在语法高亮模式下显示
+        let show_minimap = self.show_minimap && self.show_syntax_highlighting;
+        let full_rect = ui.available_rect_before_wrap();
+        let side_width = if show_minimap { MINIMAP_WIDTH + OVERVIEW_RULER_WIDTH } else { 0.0 };
+
+        // 代码显示区域 - 保留滚动条但限制只能上下拖动
+        let mut scroll_area = egui::ScrollArea::new([self.soft_wrap == SoftWrap::Off, true])
+            .id_source("code_content")
+            .auto_shrink([false, false])
+            .max_width(full_rect.width() - side_width)
+            .stick_to_bottom(false);
+        if let Some(offset) = self.pending_scroll_offset.take() {
+            scroll_area = scroll_area.vertical_scroll_offset(offset);
+        }
+        let output = scroll_area.show(ui, |ui| {
+            ui.set_width(ui.available_width());
+
+            // 简化方案：优先使用纯编辑模式
+            if !self.show_syntax_highlighting {
+                // 普通编辑模式 - 无语法高亮，性能最佳
+                self.jump_line_height = ui.text_style_height(&egui::TextStyle::Monospace);
+                // 文本框自行换行：不换行时换行宽度为无限
+                let font_id = egui::TextStyle::Monospace.resolve(ui.style());
+                let wrap_width = match self.soft_wrap {
+                    SoftWrap::Off => f32::INFINITY,
+                    SoftWrap::Window => ui.available_width(),
+                    SoftWrap::Column(columns) => ui.fonts(|f| f.glyph_width(&font_id, 'M')) * columns as f32,
+                };
+                let brackets = self.edit_brackets;
+                let tab_width = self.tab_width;
+                let space_width = ui.fonts(|f| f.glyph_width(&font_id, ' '));
+                let mut layouter = |ui: &egui::Ui, text: &str, _: f32| {
+                    let format = egui::TextFormat::simple(font_id.clone(), ui.visuals().widgets.inactive.text_color());
+                    let mut job = egui::text::LayoutJob::default();
+                    job.wrap.max_width = wrap_width;
+                    // 光标旁的括号和与之匹配的括号加底色
+                    let mut marks: Vec<usize> = brackets
+                        .into_iter()
+                        .flat_map(|(a, b)| [a, b])
+                        .filter(|&offset| text.as_bytes().get(offset).is_some_and(|&b| is_bracket(b)))
+                        .collect();
+                    marks.sort_unstable();
+                    let mut last = 0;
+                    for offset in marks {
+                        job.append(&text[last..offset], 0.0, format.clone());
+                        let background = ui.visuals().selection.bg_fill.gamma_multiply(0.6);
+                        job.append(&text[offset..offset + 1], 0.0, egui::TextFormat { background, ..format.clone() });
+                        last = offset + 1;
+                    }
+                    job.append(&text[last..], 0.0, format);
+                    expand_tabs(&mut job, tab_width, space_width);
+                    ui.fonts(|f| f.layout_job(job))
+                };
+                let output = egui::TextEdit::multiline(&mut self.code)
+                    .font(egui::TextStyle::Monospace)
+                    .code_editor()
+                    .desired_width(f32::INFINITY)
+                    .layouter(&mut layouter)
+                    .lock_focus(false)
+                    .interactive(true)
+                    .show(ui);
+
+                if output.response.changed() {
+                    self.text_info_dirty = true;
+                    self.find_key = None;
+                    self.code_hash = Self::calculate_code_hash(&self.code);
+                }
+                self.update_text_info();
+
+                // 空白字符只画在可见的行上
+                if self.show_whitespace {
+                    let clip = ui.clip_rect();
+                    let painter = ui.painter_at(clip);
+                    let lines = galley_lines(&output.galley, output.text_draw_pos);
+                    for (glyphs, text) in lines.iter().zip(self.code.split('\n')) {
+                        let (Some(first), Some(last)) = (glyphs.first(), glyphs.last()) else {
+                            continue;
+                        };
+                        if first.0.top() > clip.bottom() {
+                            break;
+                        }
+                        if last.0.bottom() >= clip.top() {
+                            paint_whitespace(&painter, text, 0, glyphs, self.indent, self.tab_width, ui.visuals());
+                        }
+                    }
+                }
+
+                // 光标移动或文本变化时更新当前行和括号对
+                if let Some(cursor_range) = output.cursor_range {
+                    let index = cursor_range.primary.ccursor.index;
+                    if index != self.last_cursor_index || output.response.changed() {
+                        self.last_cursor_index = index;
+                        self.current_line = self.code.chars().take(index).filter(|&c| c == '\n').count();
+                        let byte = self.code.char_indices().nth(index).map_or(self.code.len(), |(byte, _)| byte);
+                        let line_start = self.code[..byte].rfind('\n').map_or(0, |at| at + 1);
+                        self.cursor_column = wrap::display_column(&self.code[line_start..byte], self.tab_width);
+                        self.edit_brackets = self.bracket_pair_at_char(index);
+                        ui.ctx().request_repaint();
+                    }
+                }
+
+                // 跳到匹配的括号：下一帧移动光标
+                if let Some(index) = self.pending_cursor.take() {
+                    let mut state = output.state.clone();
+                    let cursor = egui::text::CCursor::new(index);
+                    state.set_ccursor_range(Some(egui::text_edit::CCursorRange::one(cursor)));
+                    state.store(ui.ctx(), output.response.id);
+                    output.response.request_focus();
+                    ui.ctx().request_repaint();
+                }
+            } else {
+                // 语法高亮只读模式 - 只渲染可见区域
+                self.render_visible_syntax_highlighted(ui, bookmarks, line_changes, commands);
+            }
+        });
+        self.scroll_offset = output.state.offset.y;
+
+        self.update_find_matches();
+        if show_minimap {
+            let strip = egui::Rect::from_min_max(
+                egui::pos2(full_rect.right() - side_width, output.inner_rect.top()),
+                egui::pos2(full_rect.right(), output.inner_rect.bottom()),
+            );
+            let (minimap_rect, ruler_rect) = strip.split_left_right_at_x(strip.right() - OVERVIEW_RULER_WIDTH);
+            self.render_minimap(ui, minimap_rect, output.inner_rect.height());
+            self.render_overview_ruler(ui, ruler_rect, output.inner_rect.height(), bookmarks, line_changes);
+        }
+
+        // 添加模式切换按钮
+        ui.horizontal(|ui| {
+            if ui.button(if self.show_syntax_highlighting { "切换到编辑模式" } else { "切换到语法高亮" }).clicked() {
+                commands.push(Command::ToggleEditMode);
+            }
+
+            ui.label(if self.show_syntax_highlighting {
+                "✨ 语法高亮模式（只读）"
+            } else {
+                "⚡ 编辑模式（可修改）"
+            });
+
+            if ui.selectable_label(self.follow_mode, "📜 跟随末尾").clicked() {
+                commands.push(Command::ToggleFollowMode);
+            }
+
+            if ui.selectable_label(self.show_blame, "👤 Blame").clicked() {
+                commands.push(Command::ToggleBlame);
+            }
+
+            if ui.selectable_label(self.show_minimap, "🗺 缩略图").clicked() {
+                commands.push(Command::ToggleMinimap);
+            }
+
+            egui::ComboBox::from_id_source("soft_wrap")
+                .selected_text(format!("↩ {}", self.soft_wrap.label()))
+                .show_ui(ui, |ui| {
+                    for choice in SoftWrap::CHOICES {
+                        ui.selectable_value(&mut self.soft_wrap, choice, choice.label());
+                    }
+                });
+
+            // 查找：回车跳到下一处，Shift+回车跳到上一处
+            let find = ui.add(egui::TextEdit::singleline(&mut self.find_query).hint_text("🔍 查找").desired_width(140.0));
+            if std::mem::take(&mut self.focus_find) {
+                find.request_focus();
+            }
+            if find.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                let backwards = ui.input(|i| i.modifiers.shift);
+                self.find_next(backwards);
+                find.request_focus();
+            }
+            if !self.find_query.is_empty() {
+                ui.weak(format!("{} 行", self.find_matches.len()));
+            }
+
+            // 解析错误：点击跳转到出错位置
+            if let Some(error) = &self.parse_error {
+                let line = error.line;
+                let text = egui::RichText::new(format!("✖ {}", error)).color(ui.visuals().error_fg_color);
+                if ui.add(egui::Label::new(text).truncate(true).sense(egui::Sense::click())).on_hover_text("跳转到出错位置").clicked() {
+                    self.go_to_line(line);
+                }
+            }
+        });
+    }
+
+    /// 将焦点移到查找框
+    pub fn focus_find(&mut self) {
+        self.focus_find = true;
+    }
+
+    /// 查找文本或代码变化时重新查找（不区分大小写，按行记录）
+    fn update_find_matches(&mut self) {
+        if self.find_key.as_ref().is_some_and(|(hash, query)| *hash == self.last_code_hash && *query == self.find_query) {
+            return;
+        }
+        self.find_key = Some((self.last_code_hash, self.find_query.clone()));
+        self.find_matches.clear();
+        if self.find_query.is_empty() {
+            return;
+        }
+        let needle = self.find_query.to_lowercase();
+        self.find_matches = self
+            .code
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| line.to_lowercase().contains(&needle))
+            .map(|(index, _)| index)
+            .collect();
+    }
+
+    /// 跳到当前行之后（或之前）的下一处查找结果，到末尾后从头开始
+    pub fn find_next(&mut self, backwards: bool) {
+        let line = self.current_line;
+        let target = if backwards {
+            self.find_matches.iter().rev().find(|&&m| m < line).or(self.find_matches.last())
+        } else {
+            self.find_matches.iter().find(|&&m| m > line).or(self.find_matches.first())
+        };
+        if let Some(&target) = target {
+            self.go_to_line(target);
+        }
+    }
+
+    /// 缩略图：按高亮缓存中每个片段的颜色画出整个文件（折叠后的可见行），点击或拖动滚动到对应位置
+    fn render_minimap(&mut self, ui: &mut egui::Ui, rect: egui::Rect, viewport_height: f32) {
+        let response = ui.interact(rect, ui.id().with("minimap"), egui::Sense::click_and_drag());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+
+        let row_count = folding::visible_count(self.cached_highlighted_lines.len(), &self.hidden_ranges);
+        if row_count == 0 || self.jump_line_height <= 0.0 {
+            return;
+        }
+        let row_height = (rect.height() / row_count as f32).min(MINIMAP_ROW_HEIGHT);
+        let top_row = self.row_at_offset(self.scroll_offset);
+        let viewport_rows = self.row_at_offset(self.scroll_offset + viewport_height) - top_row;
+
+        // 行数多于像素时每个像素行只画其中一行
+        let slots = row_count.min(rect.height().max(1.0) as usize);
+        let slot_height = (row_count as f32 * row_height / slots as f32).max(1.0);
+        for slot in 0..slots {
+            let row = slot * row_count / slots;
+            let line = folding::row_to_line(row, &self.hidden_ranges);
+            let Some(job) = self.cached_highlighted_lines.get(line) else {
+                continue;
+            };
+            let y = rect.top() + row as f32 * row_height;
+            let mut column = 0;
+            for section in &job.sections {
+                let mut run_start = None;
+                for ch in job.text[section.byte_range.clone()].chars() {
+                    match (ch.is_whitespace(), run_start) {
+                        (false, None) => run_start = Some(column),
+                        (true, Some(start)) => {
+                            paint_minimap_run(&painter, rect, y, slot_height, start, column, section.format.color);
+                            run_start = None;
+                        }
+                        _ => {}
+                    }
+                    column += match ch {
+                        '\t' => wrap::tab_advance(column, self.tab_width),
+                        _ => 1,
+                    };
+                }
+                if let Some(start) = run_start {
+                    paint_minimap_run(&painter, rect, y, slot_height, start, column, section.format.color);
+                }
+                if rect.left() + column as f32 * MINIMAP_CHAR_WIDTH > rect.right() {
+                    break;
+                }
+            }
+        }
+
+        // 可见区域框
+        let viewport = egui::Rect::from_min_size(
+            egui::pos2(rect.left(), rect.top() + top_row * row_height),
+            egui::vec2(rect.width(), (viewport_rows * row_height).max(4.0)),
+        );
+        let hovered = response.hovered() || response.dragged();
+        painter.rect_filled(viewport, 0.0, ui.visuals().weak_text_color().gamma_multiply(if hovered { 0.3 } else { 0.15 }));
+
+        // 以指针位置为可见区域中心
+        if let Some(pointer) = response.interact_pointer_pos().filter(|_| response.clicked() || response.dragged()) {
+            let center_row = (pointer.y - rect.top()) / row_height;
+            self.pending_scroll_offset = Some((self.offset_of_row(center_row.max(0.0)) - viewport_height / 2.0).max(0.0));
+        }
+    }
+
+    /// 概览标尺：按在整个文件中的位置标出 Git 变化（左）、查找结果（中）、书签（右）和解析错误（整宽），点击跳转
+    fn render_overview_ruler(
+        &mut self,
+        ui: &mut egui::Ui,
+        rect: egui::Rect,
+        viewport_height: f32,
+        bookmarks: &BTreeSet<usize>,
+        line_changes: &HashMap<usize, LineChange>,
+    ) {
+        let response = ui.interact(rect, ui.id().with("overview_ruler"), egui::Sense::click_and_drag());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 0.0, ui.visuals().faint_bg_color);
+        painter.vline(rect.left(), rect.y_range(), ui.visuals().widgets.noninteractive.bg_stroke);
+
+        let line_count = self.cached_highlighted_lines.len();
+        let row_count = folding::visible_count(line_count, &self.hidden_ranges);
+        if row_count == 0 || self.jump_line_height <= 0.0 {
+            return;
+        }
+        let hidden = &self.hidden_ranges;
+        let y_of = |line: usize| rect.top() + (folding::line_to_row(line, hidden) as f32 + 0.5) / row_count as f32 * rect.height();
+        let third = rect.width() / 3.0;
+        let mark = |column: f32, width: f32, line: usize, color: egui::Color32| {
+            let y = y_of(line);
+            painter.rect_filled(
+                egui::Rect::from_min_size(egui::pos2(rect.left() + column * third, y - 1.0), egui::vec2(width * third, 3.0)),
+                0.0,
+                color,
+            );
+        };
+
+        // 可见区域
+        let top_row = self.row_at_offset(self.scroll_offset);
+        let viewport_rows = self.row_at_offset(self.scroll_offset + viewport_height) - top_row;
+        let top = rect.top() + top_row / row_count as f32 * rect.height();
+        let height = (viewport_rows / row_count as f32 * rect.height()).clamp(4.0, rect.height());
+        painter.rect_filled(
+            egui::Rect::from_min_size(egui::pos2(rect.left(), top), egui::vec2(rect.width(), height)),
+            0.0,
+            ui.visuals().weak_text_color().gamma_multiply(0.15),
+        );
+
+        for (&line, change) in line_changes.iter().filter(|(&line, _)| line < line_count) {
+            let color = match change {
+                LineChange::Added => styles::GIT_ADDED_COLOR,
+                LineChange::Modified => styles::GIT_MODIFIED_COLOR,
+                LineChange::Deleted => styles::GIT_DELETED_COLOR,
+            };
+            mark(0.0, 1.0, line, color);
+        }
+        for &line in &self.find_matches {
+            mark(1.0, 1.0, line, egui::Color32::from_rgb(230, 160, 40));
+        }
+        for &line in bookmarks.range(..line_count) {
+            mark(2.0, 1.0, line, egui::Color32::from_rgb(80, 160, 255));
+        }
+        if let Some(error) = &self.parse_error {
+            mark(0.0, 3.0, error.line, ui.visuals().error_fg_color);
+        }
+
+        // 以点击位置为可见区域中心
+        if let Some(pointer) = response.interact_pointer_pos().filter(|_| response.clicked() || response.dragged()) {
+            let center_row = (pointer.y - rect.top()) / rect.height() * row_count as f32;
+            self.pending_scroll_offset = Some((self.offset_of_row(center_row.max(0.0)) - viewport_height / 2.0).max(0.0));
+        }
+        if let Some(pointer) = response.hover_pos() {
+            let row = ((pointer.y - rect.top()) / rect.height() * row_count as f32) as usize;
+            response.on_hover_text(format!("第 {} 行", folding::row_to_line(row.min(row_count - 1), hidden) + 1));
+        }
+    }
+
+    /// 切换编辑模式和语法高亮模式
+    pub fn toggle_mode(&mut self) {
+        self.show_syntax_highlighting = !self.show_syntax_highlighting;
+    }
+
+    /// 滚动到开头
+    pub fn scroll_to_top(&mut self) {
+        self.pending_scroll_offset = Some(0.0);
+    }
+
+    /// 跳转到指定行（上方保留几行上下文，所在的折叠会被展开）
+    pub fn go_to_line(&mut self, line: usize) {
+        self.current_line = line;
+        self.cursor_column = 0;
+        self.reveal_line(line);
+        let row = folding::line_to_row(line, self.hidden());
+        self.pending_scroll_offset = Some((self.offset_of_row(row as f32) - 3.0 * self.jump_line_height).max(0.0));
+    }
+
+    /// 可见区域顶部所在的行（可以是小数）
+    pub fn top_line(&self) -> f32 {
+        if self.jump_line_height > 0.0 {
+            let row = self.row_at_offset(self.scroll_offset);
+            folding::row_to_line(row as usize, self.hidden()) as f32 + row.fract()
+        } else {
+            0.0
+        }
+    }
+
+    /// 滚动到指定行（不改变当前行）
+    pub fn scroll_to_line(&mut self, line: f32) {
+        let row = folding::line_to_row(line as usize, self.hidden()) as f32 + line.fract();
+        self.pending_scroll_offset = Some(self.offset_of_row(row));
+    }
+
+    /// 语法高亮模式下是否由编辑器拆分长行
+    fn wraps(&self) -> bool {
+        self.show_syntax_highlighting && self.wrap_columns > 0
+    }
+
+    /// 滚动位置对应的可见行（折叠后的行，可以是小数；自动换行时按该行拆分出的显示行数折算）
+    fn row_at_offset(&self, offset: f32) -> f32 {
+        if self.jump_line_height <= 0.0 {
+            return 0.0;
+        }
+        let display_row = offset / self.jump_line_height;
+        if !self.wraps() {
+            return display_row;
+        }
+        let (row, sub) = self.wrap_index.locate(display_row as usize);
+        let rows = self.wrap_index.row_start(row + 1).saturating_sub(self.wrap_index.row_start(row)).max(1);
+        row as f32 + ((sub as f32 + display_row.fract()) / rows as f32).min(1.0)
+    }
+
+    /// 可见行（可以是小数）对应的滚动位置
+    fn offset_of_row(&self, row: f32) -> f32 {
+        if !self.wraps() {
+            return row * self.jump_line_height;
+        }
+        let start = self.wrap_index.row_start(row as usize);
+        let next = self.wrap_index.row_start(row as usize + 1);
+        (start as f32 + row.fract() * (next - start) as f32) * self.jump_line_height
+    }
+
+    /// 当前的换行列数（0 表示不换行）；按窗口宽度换行时减去行号等左侧各栏的宽度
+    fn current_wrap_columns(&self, ui: &egui::Ui, lines_count: usize) -> usize {
+        let char_width = ui.fonts(|f| f.glyph_width(&egui::FontId::monospace(12.0), 'M'));
+        match self.soft_wrap {
+            SoftWrap::Off => 0,
+            SoftWrap::Column(columns) => columns,
+            SoftWrap::Window => {
+                let spacing = ui.spacing().item_spacing.x;
+                let digits = lines_count.to_string().len().max(4);
+                let mut gutter = 12.0 + 4.0 + digits as f32 * char_width + 12.0 + spacing * 4.0;
+                if self.show_blame {
+                    gutter += BLAME_COLUMN_WIDTH + spacing;
+                }
+                let columns = ((ui.available_width() - gutter) / char_width).max(0.0) as usize;
+                columns.saturating_sub(1).max(wrap::MIN_COLUMNS)
+            }
+        }
+    }
+
+    /// 代码或列数变化时重新计算每行的显示行数，折叠变化时重建换算索引
+    fn update_wrap_index(&mut self, lines_count: usize) {
+        let key = (self.last_code_hash, self.wrap_columns, self.tab_width);
+        let counts_changed = key != self.wrap_key || self.wrap_counts.len() != lines_count;
+        if counts_changed {
+            self.wrap_key = key;
+            let (columns, tab_width) = (self.wrap_columns, self.tab_width);
+            self.wrap_counts = self.code.par_lines().map(|line| wrap::row_starts(line, columns, tab_width).len()).collect();
+        }
+        if counts_changed || self.wrap_hidden != self.hidden_ranges {
+            self.wrap_hidden = self.hidden_ranges.clone();
+            self.wrap_index = wrap::WrapIndex::build(&self.wrap_counts, &self.hidden_ranges);
+        }
+    }
+
+    /// 当前生效的隐藏行（折叠只在语法高亮模式下生效）
+    fn hidden(&self) -> &[(usize, usize)] {
+        if self.show_syntax_highlighting {
+            &self.hidden_ranges
+        } else {
+            &[]
+        }
+    }
+
+    /// 代码变化后重新计算折叠区域，已折叠的区域随增删的行移动，被修改的区域取消折叠
+    fn update_fold_regions(&mut self) {
+        if self.fold_hash == self.last_code_hash {
+            return;
+        }
+        self.fold_hash = self.last_code_hash;
+        let line_comment = SyntaxHighlighter::with_language(self.language.as_deref()).line_comment();
+        let regions = folding::regions(&self.code, line_comment, self.tab_width);
+        let lines: Vec<u64> = self.code.par_lines().map(Self::calculate_code_hash).collect();
+        self.folded = folding::remap_folded(&self.folded, &self.fold_regions, &regions, &self.fold_lines, &lines);
+        self.fold_regions = regions;
+        self.fold_lines = lines;
+        self.hidden_ranges = folding::hidden_ranges(&self.fold_regions, &self.folded);
+    }
+
+    /// 以 line 为首行的折叠区域
+    fn fold_region_at(&self, line: usize) -> Option<folding::FoldRegion> {
+        let index = self.fold_regions.binary_search_by_key(&line, |region| region.start).ok()?;
+        Some(self.fold_regions[index])
+    }
+
+    fn set_folded(&mut self, start: usize, folded: bool) {
+        if folded {
+            self.folded.insert(start);
+        } else {
+            self.folded.remove(&start);
+        }
+        self.hidden_ranges = folding::hidden_ranges(&self.fold_regions, &self.folded);
+    }
+
+    /// 折叠或展开当前行所在的区域（当前行不是区域首行时取包含它的最内层区域）
+    pub fn toggle_fold(&mut self) {
+        let line = self.current_line;
+        let region = self.fold_region_at(line).or_else(|| {
+            self.fold_regions
+                .iter()
+                .rev()
+                .find(|region| region.start < line && line <= region.end)
+                .copied()
+        });
+        if let Some(region) = region {
+            self.current_line = region.start;
+            self.set_folded(region.start, !self.folded.contains(&region.start));
+        }
+    }
+
+    /// 折叠全部区域
+    pub fn fold_all(&mut self) {
+        self.folded = self.fold_regions.iter().map(|region| region.start).collect();
+        self.hidden_ranges = folding::hidden_ranges(&self.fold_regions, &self.folded);
+    }
+
+    /// 展开全部区域
+    pub fn unfold_all(&mut self) {
+        self.folded.clear();
+        self.hidden_ranges.clear();
+    }
 
-        // 处理目录切换
-        if let Some(dir_path) = directory_to_enter {
-            self.current_directory = dir_path;
-            self.load_directory_content();
+    /// 展开包含指定行的折叠
+    fn reveal_line(&mut self, line: usize) {
+        let regions = &self.fold_regions;
+        let before = self.folded.len();
+        self.folded.retain(|&start| {
+            !regions
+                .binary_search_by_key(&start, |region| region.start)
+                .is_ok_and(|index| start < line && line <= regions[index].end)
+        });
+        if self.folded.len() != before {
+            self.hidden_ranges = folding::hidden_ranges(&self.fold_regions, &self.folded);
         }
+    }
 
-        file_to_load
+    /// 代码变化后重新识别缩进方式、每行的缩进和换行符
+    /// （语法高亮模式下按代码哈希判断，编辑模式下由文本框的修改标记）
+    fn update_text_info(&mut self) {
+        if !self.text_info_dirty && self.indent_hash == self.last_code_hash {
+            return;
+        }
+        self.text_info_dirty = false;
+        self.indent_hash = self.last_code_hash;
+        self.indent = indentation::detect(&self.code);
+        if !self.tab_width_fixed {
+            let tab_width = indentation::guess_tab_width(self.indent);
+            if tab_width != self.tab_width {
+                // 制表符宽度变化后重新生成各行的布局
+                self.tab_width = tab_width;
+                self.last_code_hash = 0;
+            }
+        }
+        self.line_indents = indentation::line_indents(&self.code, self.tab_width);
+        self.line_ending = line_ending::detect(&self.code);
+        self.inconsistent_indent = indentation::has_inconsistent(&self.code, self.indent, self.tab_width);
     }
 
-    /// 加载当前目录的内容
-    pub fn load_directory_content(&mut self) {
-        self.directory_items.clear();
-
-        if let Ok(entries) = std::fs::read_dir(&self.current_directory) {
-            let mut directories = Vec::new();
-            let mut files = Vec::new();
-
-            for entry in entries.flatten() {
-                let path = entry.path();
-                let is_directory = path.is_dir();
-
-                if let Some(file_name) = path.file_name() {
-                    if let Some(name_str) = file_name.to_str() {
-                        let item = DirectoryItem {
-                            name: name_str.to_string(),
-                            path: path.clone(),
-                            is_directory,
-                        };
+    /// 制表符宽度（每个制表位间隔的列数）
+    pub fn tab_width(&self) -> usize {
+        self.tab_width
+    }
 
-                        if is_directory {
-                            directories.push(item);
-                        } else {
-                            files.push(item);
-                        }
-                    }
-                }
-            }
+    /// 手动设置制表符宽度（之后不再按缩进推测）
+    pub fn set_tab_width(&mut self, tab_width: usize) {
+        self.tab_width = tab_width.max(1);
+        self.tab_width_fixed = true;
+        self.last_code_hash = 0;
+        self.text_info_dirty = true;
+    }
 
-            // 排序：目录在前，文件在后，都按字母顺序排序
-            directories.sort_by(|a, b| a.name.cmp(&b.name));
-            files.sort_by(|a, b| a.name.cmp(&b.name));
+    /// 打开新文件时重置制表符宽度：有 .editorconfig 设置时使用它，否则按文件的缩进推测
+    pub fn reset_tab_width(&mut self, editorconfig: Option<usize>) {
+        self.tab_width = editorconfig.unwrap_or(indentation::DEFAULT_TAB_WIDTH).max(1);
+        self.tab_width_fixed = editorconfig.is_some();
+        self.last_code_hash = 0;
+        self.text_info_dirty = true;
+    }
 
-            // 合并列表
-            self.directory_items.extend(directories);
-            self.directory_items.extend(files);
-        }
+    /// 光标所在的行和列（都从 0 开始，列按制表位计算）
+    pub fn cursor_position(&self) -> (usize, usize) {
+        (self.current_line, self.cursor_column)
     }
-}
 
-use crate::ui::syntax_highlighter::SyntaxHighlighter;
+    /// 替换全部代码（加载文件或整体转换后）
+    pub fn set_code(&mut self, code: String) {
+        self.code_hash = Self::calculate_code_hash(&code);
+        self.code = code;
+        self.text_info_dirty = true;
+        self.find_key = None;
+    }
 
-/// 代码编辑器组件
-pub struct CodeEditor {
-    pub code: String,
-    syntax_highlighter: SyntaxHighlighter,
-    show_syntax_highlighting: bool, // true = 语法高亮只读, false = 编辑模式
-    cached_highlighted_lines: Vec<egui::text::LayoutJob>,
-    layout_job_pool: Vec<egui::text::LayoutJob>, // LayoutJob对象内存池
-    last_code_hash: u64,
-    cached_line_height: f32,
-    last_scroll_position: f32, // 上次滚动位置，用于检测滚动状态
-    is_scrolling: bool,         // 是否正在滚动
-    scroll_timer: u32,          // 滚动计时器，用于延迟语法高亮
-}
+    /// 当前内容的哈希（不需要每帧重新计算，后台任务用它判断内容是否变化）
+    pub fn code_hash(&self) -> u64 {
+        self.code_hash
+    }
 
-impl CodeEditor {
-    pub fn new(code: String) -> Self {
-        let code_hash = Self::calculate_code_hash(&code);
-        Self {
-            code,
-            syntax_highlighter: SyntaxHighlighter::new(),
-            show_syntax_highlighting: true, // 默认语法高亮模式
-            cached_highlighted_lines: Vec::new(),
-            layout_job_pool: Vec::new(), // 初始化内存池
-            last_code_hash: code_hash,
-            cached_line_height: 0.0,
-            last_scroll_position: 0.0,
-            is_scrolling: false,
-            scroll_timer: 0,
+    /// 识别出的换行符
+    pub fn line_ending(&self) -> LineEnding {
+        self.line_ending
+    }
+
+    /// 识别出的缩进方式，以及是否有与之不一致的行
+    pub fn indentation(&self) -> (Indent, bool) {
+        (self.indent, self.inconsistent_indent)
+    }
+
+    /// 将所有换行符统一为 LF 或 CRLF，返回是否有修改
+    pub fn convert_line_endings(&mut self, target: LineEnding) -> bool {
+        let converted = line_ending::convert(&self.code, target);
+        let changed = converted != self.code;
+        if changed {
+            self.set_code(converted);
         }
+        changed
     }
 
-    /// 渲染代码编辑器
-    pub fn render(&mut self, ui: &mut egui::Ui, available_height: f32) {
-        ui.set_width(ui.available_width());
-        ui.set_min_height(available_height);
+    /// 按识别出的缩进方式重新缩进为空格或制表符，返回是否有修改
+    pub fn reindent(&mut self, target: Indent) -> bool {
+        let reindented = indentation::reindent(&self.code, self.indent, target, self.tab_width);
+        let changed = reindented != self.code;
+        if changed {
+            self.set_code(reindented);
+        }
+        changed
+    }
 
-        // 检测滚动状态
-        self.detect_scrolling_state(ui);
+    /// 一段行文本：悬停或点击括号时记录目标括号，标出当前括号对，在行首画缩进参考线
+    /// chunk_start 为这段文本在行中的起始字节位置（自动换行时每个显示行是一段）
+    fn render_line_text(&mut self, ui: &mut egui::Ui, line_idx: usize, chunk_start: usize, job: egui::text::LayoutJob) {
+        let galley = ui.fonts(|f| f.layout_job(job));
+        let response = ui.add(egui::Label::new(galley.clone()).sense(egui::Sense::click()));
+        let origin = response.rect.min;
+        let text = galley.text();
+
+        // 指针下的字符：取光标位置前后两个字符中包含指针的一个
+        let pointed = response.hover_pos().and_then(|pointer| {
+            let caret = galley.cursor_from_pos(pointer - origin).ccursor.index;
+            [caret.saturating_sub(1), caret]
+                .into_iter()
+                .find(|&index| galley_char_rect(&galley, index).translate(origin.to_vec2()).x_range().contains(pointer.x))
+                .and_then(|index| text.char_indices().nth(index))
+                .filter(|&(_, ch)| ch.is_ascii() && is_bracket(ch as u8))
+                .map(|(byte, _)| (line_idx, chunk_start + byte))
+        });
+        if pointed.is_some() {
+            self.bracket_hover = pointed;
+        }
+        if response.clicked() {
+            self.current_line = line_idx;
+            self.bracket_anchor = pointed;
+            if let Some(pointer) = response.interact_pointer_pos() {
+                let index = galley.cursor_from_pos(pointer - origin).ccursor.index;
+                let byte = chunk_start + text.char_indices().nth(index).map_or(text.len(), |(byte, _)| byte);
+                if let Some(line) = self.code.lines().nth(line_idx) {
+                    self.cursor_column = wrap::display_column(&line[..byte.min(line.len())], self.tab_width);
+                }
+            }
+        }
 
-        // 代码显示区域 - 保留滚动条但限制只能上下拖动
-        egui::ScrollArea::both()
-            .id_source("code_content")
-            .auto_shrink([false, false])
-            .stick_to_bottom(false)
-            .show(ui, |ui| {
-                ui.set_width(ui.available_width());
-
-                // 简化方案：优先使用纯编辑模式
-                if !self.show_syntax_highlighting {
-                    // 普通编辑模式 - 无语法高亮，性能最佳
-                    ui.add(
-                        egui::TextEdit::multiline(&mut self.code)
-                            .font(egui::TextStyle::Monospace)
-                            .code_editor()
-                            .desired_width(f32::INFINITY)
-                            .lock_focus(false)
-                            .interactive(true)
-                    );
-                } else {
-                    // 语法高亮只读模式 - 只渲染可见区域
-                    self.render_visible_syntax_highlighted(ui);
+        // 当前括号对
+        if let Some((a, b)) = self.bracket_pair {
+            for (line, byte) in [a, b] {
+                if line == line_idx && byte >= chunk_start && byte < chunk_start + text.len() {
+                    let index = text[..byte - chunk_start].chars().count();
+                    let rect = galley_char_rect(&galley, index).translate(origin.to_vec2()).expand(0.5);
+                    ui.painter().rect_stroke(rect, 2.0, egui::Stroke::new(1.0, ui.visuals().strong_text_color().gamma_multiply(0.7)));
                 }
-            });
+            }
+        }
 
-        // 添加模式切换按钮
-        ui.horizontal(|ui| {
-            if ui.button(if self.show_syntax_highlighting { "切换到编辑模式" } else { "切换到语法高亮" }).clicked() {
-                self.show_syntax_highlighting = !self.show_syntax_highlighting;
+        if self.show_whitespace {
+            if let Some(line) = self.cached_highlighted_lines.get(line_idx) {
+                let glyphs = galley_lines(&galley, origin).concat();
+                paint_whitespace(ui.painter(), &line.text, chunk_start, &glyphs, self.indent, self.tab_width, ui.visuals());
             }
+        }
 
-            ui.label(if self.show_syntax_highlighting {
-                "✨ 语法高亮模式（只读）"
-            } else {
-                "⚡ 编辑模式（可修改）"
-            });
+        // 缩进参考线：每级缩进的起始列一条（自动换行的后续显示行不画）
+        if self.show_indent_guides && chunk_start == 0 {
+            let indent = self.line_indents.get(line_idx).copied().unwrap_or(0);
+            let step = self.indent.columns(self.tab_width).max(1);
+            let char_width = ui.fonts(|f| f.glyph_width(&egui::FontId::monospace(12.0), 'M'));
+            let y_range = egui::Rangef::new(origin.y, origin.y + self.cached_line_height);
+            let stroke = egui::Stroke::new(1.0, ui.visuals().widgets.noninteractive.bg_stroke.color.gamma_multiply(0.7));
+            for level in 0..indent / step {
+                let x = origin.x + (level * step) as f32 * char_width + 0.5;
+                ui.painter().vline(x, y_range, stroke);
+            }
+        }
+    }
+
+    /// 与指定括号匹配的括号（行, 字节位置）；按嵌套深度匹配，不区分括号种类
+    /// 从该括号起向后（或向前）计数，只扫描到匹配的括号为止，不计算整个文件的嵌套深度
+    fn matching_bracket(&self, line: usize, byte: usize) -> Option<(usize, usize)> {
+        let highlighter = SyntaxHighlighter::with_language(self.language.as_deref());
+        let line_start: usize = self.code.split_inclusive('\n').take(line).map(str::len).sum();
+        let text = self.code.get(line_start..)?.lines().next()?;
+        let position = highlighter.brackets(text).iter().position(|&(index, _)| index == byte)?;
+        let opening = matches!(text.as_bytes()[byte], b'(' | b'[' | b'{');
+
+        let mut depth = 0usize;
+        if opening {
+            // 向后找第一个回到同一深度的右括号
+            for (offset, text) in self.code[line_start..].lines().enumerate() {
+                let brackets = highlighter.brackets(text);
+                let skip = if offset == 0 { position } else { 0 };
+                for &(index, ch) in &brackets[skip..] {
+                    depth = if matches!(ch, '(' | '[' | '{') { depth + 1 } else { depth - 1 };
+                    if depth == 0 {
+                        return Some((line + offset, index));
+                    }
+                }
+            }
+        } else {
+            // 向前找最后一个在同一深度打开的左括号（多余的右括号没有匹配）
+            for (offset, text) in self.code[..line_start + text.len()].lines().rev().enumerate() {
+                let brackets = highlighter.brackets(text);
+                let take = if offset == 0 { position + 1 } else { brackets.len() };
+                for &(index, ch) in brackets[..take].iter().rev() {
+                    depth = if matches!(ch, '(' | '[' | '{') { depth - 1 } else { depth + 1 };
+                    if depth == 0 {
+                        return Some((line - offset, index));
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// 编辑模式下光标旁的括号（先看光标后的字符，再看光标前的字符）和与之匹配的括号的字节位置
+    fn bracket_pair_at_char(&self, index: usize) -> Option<(usize, usize)> {
+        let caret = self.code.char_indices().nth(index).map_or(self.code.len(), |(byte, _)| byte);
+        let before = self.code[..caret].chars().next_back().map(|ch| caret - ch.len_utf8());
+        let line_start = |line: usize| -> usize { self.code.split_inclusive('\n').take(line).map(str::len).sum() };
+        [Some(caret), before].into_iter().flatten().find_map(|offset| {
+            if !self.code.as_bytes().get(offset).is_some_and(|&b| is_bracket(b)) {
+                return None;
+            }
+            let line = self.code[..offset].bytes().filter(|&b| b == b'\n').count();
+            let (match_line, match_byte) = self.matching_bracket(line, offset - line_start(line))?;
+            Some((offset, line_start(match_line) + match_byte))
+        })
+    }
+
+    /// 跳到与当前括号匹配的括号：编辑模式下为光标旁的括号，
+    /// 语法高亮模式下为点击过的括号或当前行的第一个括号
+    pub fn jump_to_matching_bracket(&mut self) {
+        if !self.show_syntax_highlighting {
+            if let Some((_, target)) = self.edit_brackets {
+                self.pending_cursor = Some(self.code[..target].chars().count());
+            }
+            return;
+        }
+        let line = self.current_line;
+        let source = self.bracket_anchor.filter(|&(anchor_line, _)| anchor_line == line).or_else(|| {
+            let text = self.code.lines().nth(line)?;
+            let (byte, _) = SyntaxHighlighter::with_language(self.language.as_deref()).brackets(text).into_iter().next()?;
+            Some((line, byte))
         });
+        if let Some(target) = source.and_then(|(line, byte)| self.matching_bracket(line, byte)) {
+            self.bracket_anchor = Some(target);
+            self.go_to_line(target.0);
+        }
+    }
+
+    /// 打开或关闭彩虹括号，重新高亮全部行
+    pub fn toggle_rainbow_brackets(&mut self) {
+        self.rainbow_brackets = !self.rainbow_brackets;
+        self.bracket_depths.clear();
+        self.last_code_hash = 0;
+    }
+
+    /// 滚动到末尾（滚动区域会自动限制到最大偏移）
+    pub fn scroll_to_bottom(&mut self) {
+        self.pending_scroll_offset = Some(f32::MAX);
     }
 
     /// 渲染可见区域的语法高亮（超高效版本）
-    fn render_visible_syntax_highlighted(&mut self, ui: &mut egui::Ui) {
+    fn render_visible_syntax_highlighted(
+        &mut self,
+        ui: &mut egui::Ui,
+        bookmarks: &BTreeSet<usize>,
+        line_changes: &HashMap<usize, LineChange>,
+        commands: &mut Vec<Command>,
+    ) {
+        // 字体中空格的宽度变化后重新生成各行的布局（制表符按它展开）
+        let space_width = ui.fonts(|f| f.glyph_width(&egui::FontId::monospace(12.0), ' '));
+        if space_width != self.space_width {
+            self.space_width = space_width;
+            self.last_code_hash = 0;
+        }
+
         // 只在非滚动状态或滚动停止时更新缓存
         if !self.is_scrolling || self.scroll_timer == 0 {
             self.update_cached_lines();
+            self.update_fold_regions();
+            self.update_text_info();
         }
 
         // 先收集行信息，避免同时借用
@@ -239,55 +2393,273 @@ impl CodeEditor {
             return;
         }
 
-        // 获取行高（使用缓存避免频繁查询字体）
+        // 获取行高（使用缓存避免频繁查询字体）；行间不留空隙，占位高度才与实际一致
         let line_height = self.get_cached_line_height(ui);
+        self.jump_line_height = line_height;
+        ui.spacing_mut().item_spacing.y = 0.0;
+
+        // 自动换行时每行可能占多个显示行，按显示行计算可见区域
+        self.wrap_columns = self.current_wrap_columns(ui, lines_count);
+        if self.wrap_columns > 0 {
+            self.update_wrap_index(lines_count);
+        }
+        let wraps = self.wrap_columns > 0;
 
-        // 只在滚动时重新计算可见区域（性能优化）
-        let (start_line, end_line) = self.calculate_visible_lines(ui, line_height, lines_count);
+        // 只在滚动时重新计算可见区域（性能优化），折叠后按可见行计算
+        let row_count = folding::visible_count(lines_count, &self.hidden_ranges);
+        let display_count = if wraps { self.wrap_index.total() } else { row_count };
+        let (start_display, end_display) = self.calculate_visible_lines(ui, line_height, display_count);
 
         // 为顶部空间占位
-        let top_space = (start_line as f32) * line_height;
+        let top_space = (start_display as f32) * line_height;
         if top_space > 0.0 {
             ui.add_space(top_space);
         }
 
-        // 只渲染可见区域的行
-        for line_idx in start_line..end_line {
+        // 只渲染可见区域的行；第一行可能从中间的显示行开始
+        let (start_row, mut first_sub) = if wraps { self.wrap_index.locate(start_display) } else { (start_display, 0) };
+        let mut line_idx = folding::row_to_line(start_row, &self.hidden_ranges);
+        let mut display_row = start_display;
+        let mut previous_line = None;
+        let mut toggled_fold = None;
+        self.bracket_hover = None;
+        while display_row < end_display && line_idx < lines_count {
             let line_num = line_idx + 1;
+            let line_rows = if wraps { self.wrap_counts.get(line_idx).copied().unwrap_or(1) } else { 1 };
+            let subs = first_sub..line_rows.min(first_sub + end_display - display_row);
 
-            ui.horizontal(|ui| {
-                // 行号
-                ui.label(
-                    egui::RichText::new(format!("{:>4}", line_num))
-                        .monospace()
-                        .color(egui::Color32::GRAY)
-                        .size(12.0)
+            // 当前行背景在行内容之后才能确定范围，先占位
+            let background = ui.painter().add(egui::Shape::Noop);
+
+            let row = ui.horizontal(|ui| {
+                if self.show_blame {
+                    self.render_blame_cell(ui, line_idx, previous_line, line_height);
+                }
+
+                // 书签标记栏：点击切换书签
+                let (marker_rect, marker_response) =
+                    ui.allocate_exact_size(egui::vec2(12.0, line_height), egui::Sense::click());
+                let error = self.parse_error.as_ref().filter(|e| e.line == line_idx);
+                if error.is_some() {
+                    ui.painter().text(
+                        marker_rect.center(),
+                        egui::Align2::CENTER_CENTER,
+                        "✖",
+                        egui::FontId::proportional(11.0),
+                        ui.visuals().error_fg_color,
+                    );
+                } else if bookmarks.contains(&line_idx) {
+                    ui.painter().circle_filled(marker_rect.center(), 4.0, egui::Color32::from_rgb(80, 160, 255));
+                } else if marker_response.hovered() {
+                    ui.painter().circle_stroke(
+                        marker_rect.center(),
+                        4.0,
+                        egui::Stroke::new(1.0, egui::Color32::GRAY),
+                    );
+                }
+                let marker_response = match error {
+                    Some(error) => marker_response.on_hover_text(format!("解析错误：{}", error)),
+                    None => marker_response,
+                };
+                if marker_response.clicked() {
+                    self.current_line = line_idx;
+                    commands.push(Command::ToggleBookmark);
+                }
+
+                // 与 HEAD 比较的变化标记
+                let (change_rect, _) = ui.allocate_exact_size(egui::vec2(4.0, line_height), egui::Sense::hover());
+                match line_changes.get(&line_idx) {
+                    Some(LineChange::Added) => {
+                        ui.painter().rect_filled(change_rect.shrink2(egui::vec2(0.5, 0.0)), 0.0, styles::GIT_ADDED_COLOR);
+                    }
+                    Some(LineChange::Modified) => {
+                        ui.painter().rect_filled(change_rect.shrink2(egui::vec2(0.5, 0.0)), 0.0, styles::GIT_MODIFIED_COLOR);
+                    }
+                    Some(LineChange::Deleted) => {
+                        // 删除位置画在行的上边缘
+                        let top = change_rect.left_top();
+                        ui.painter().add(egui::Shape::convex_polygon(
+                            vec![top, top + egui::vec2(5.0, 0.0), top + egui::vec2(0.0, 5.0)],
+                            styles::GIT_DELETED_COLOR,
+                            egui::Stroke::NONE,
+                        ));
+                    }
+                    None => {}
+                }
+
+                // 行号：点击设为当前行
+                let line_number = ui.add(
+                    egui::Label::new(
+                        egui::RichText::new(format!("{:>4}", line_num))
+                            .monospace()
+                            .color(egui::Color32::GRAY)
+                            .size(12.0),
+                    )
+                    .sense(egui::Sense::click()),
                 );
+                if line_number.clicked() {
+                    self.current_line = line_idx;
+                    self.cursor_column = 0;
+                }
+
+                // 折叠标记：点击折叠或展开
+                let region = self.fold_region_at(line_idx);
+                let is_folded = region.is_some() && self.folded.contains(&line_idx);
+                let (fold_rect, fold_response) = ui.allocate_exact_size(egui::vec2(12.0, line_height), egui::Sense::click());
+                if region.is_some() {
+                    let color = match is_folded || fold_response.hovered() {
+                        true => ui.visuals().strong_text_color(),
+                        false => ui.visuals().weak_text_color(),
+                    };
+                    ui.painter().text(
+                        fold_rect.center(),
+                        egui::Align2::CENTER_CENTER,
+                        if is_folded { "▸" } else { "▾" },
+                        egui::FontId::proportional(12.0),
+                        color,
+                    );
+                    if fold_response.on_hover_text(if is_folded { "展开" } else { "折叠" }).clicked() {
+                        toggled_fold = Some((line_idx, !is_folded));
+                    }
+                }
+
+                // 折叠的首行末尾显示隐藏的行数，点击展开
+                let mut fold_label = |ui: &mut egui::Ui| {
+                    if let (true, Some(region)) = (is_folded, region) {
+                        let text = egui::RichText::new(format!(" ⋯ {} 行 ", region.end - region.start))
+                            .monospace()
+                            .size(11.0)
+                            .background_color(ui.visuals().faint_bg_color);
+                        if ui.add(egui::Label::new(text).sense(egui::Sense::click())).on_hover_text("展开").clicked() {
+                            toggled_fold = Some((line_idx, false));
+                        }
+                    }
+                };
 
                 // 使用缓存的语法高亮
-                if line_idx < self.cached_highlighted_lines.len() {
-                    ui.add(egui::Label::new(self.cached_highlighted_lines[line_idx].clone()));
-                } else {
-                    // 如果缓存中没有该行，显示原始文本（防止内容截断）
-                    // 需要重新获取行内容，避免借用冲突
-                    if let Some(line_text) = self.code.lines().nth(line_idx) {
-                        ui.label(
-                            egui::RichText::new(line_text)
-                                .monospace()
-                                .size(12.0)
-                        );
+                let job = match self.cached_highlighted_lines.get(line_idx) {
+                    Some(job) => job.clone(),
+                    None => {
+                        // 如果缓存中没有该行，显示原始文本（防止内容截断）
+                        let line_text = self.code.lines().nth(line_idx).unwrap_or("");
+                        let format = egui::TextFormat::simple(egui::FontId::monospace(12.0), ui.visuals().text_color());
+                        egui::text::LayoutJob::single_section(line_text.to_string(), format)
                     }
+                };
+                if wraps {
+                    // 自动换行：只渲染可见的显示行，每个显示行一个标签
+                    let starts = wrap::row_starts(&job.text, self.wrap_columns, self.tab_width);
+                    ui.vertical(|ui| {
+                        for sub in subs.clone() {
+                            let Some(&start) = starts.get(sub) else {
+                                break;
+                            };
+                            let end = starts.get(sub + 1).copied().unwrap_or(job.text.len());
+                            let chunk = slice_layout_job(&job, start..end);
+                            if sub + 1 == starts.len() {
+                                ui.horizontal(|ui| {
+                                    self.render_line_text(ui, line_idx, start, chunk);
+                                    fold_label(ui);
+                                });
+                            } else {
+                                self.render_line_text(ui, line_idx, start, chunk);
+                            }
+                        }
+                    });
+                } else {
+                    self.render_line_text(ui, line_idx, 0, job);
+                    fold_label(ui);
                 }
             });
+
+            let rect = egui::Rect::from_x_y_ranges(ui.max_rect().x_range(), row.response.rect.y_range());
+            if self.parse_error.as_ref().is_some_and(|e| e.line == line_idx) {
+                ui.painter().set(background, egui::Shape::rect_filled(rect, 0.0, ui.visuals().error_fg_color.gamma_multiply(0.15)));
+            } else if line_idx == self.current_line {
+                ui.painter().set(background, egui::Shape::rect_filled(rect, 0.0, ui.visuals().faint_bg_color));
+            } else if self.find_matches.binary_search(&line_idx).is_ok() {
+                ui.painter().set(background, egui::Shape::rect_filled(rect, 0.0, egui::Color32::from_rgb(230, 160, 40).gamma_multiply(0.15)));
+            }
+
+            previous_line = Some(line_idx);
+            display_row += subs.len().max(1);
+            first_sub = 0;
+            line_idx = folding::next_visible_line(line_idx, &self.hidden_ranges);
+        }
+        if let Some((start, folded)) = toggled_fold {
+            self.set_folded(start, folded);
+        }
+
+        // 目标括号变化时重新查找匹配的括号（下一帧标出）
+        let target = self.bracket_hover.or(self.bracket_anchor);
+        if self.bracket_key != (target, self.last_code_hash) {
+            self.bracket_key = (target, self.last_code_hash);
+            self.bracket_pair = target.and_then(|target| Some((target, self.matching_bracket(target.0, target.1)?)));
+            ui.ctx().request_repaint();
         }
 
         // 为底部空间占位（确保滚动条正确工作）
-        let bottom_space = ((lines_count - end_line) as f32) * line_height;
+        let bottom_space = (display_count.saturating_sub(display_row) as f32) * line_height;
         if bottom_space > 0.0 {
             ui.add_space(bottom_space);
         }
     }
 
+    /// blame 列的一格：同一提交的连续行只在第一行显示，悬停显示完整说明，点击查看提交
+    /// previous_line 为上方显示的行（折叠时不一定相邻），None 表示第一个可见行
+    fn render_blame_cell(&mut self, ui: &mut egui::Ui, line_idx: usize, previous_line: Option<usize>, line_height: f32) {
+        let (rect, response) = ui.allocate_exact_size(egui::vec2(BLAME_COLUMN_WIDTH, line_height), egui::Sense::click());
+        let Some(blame) = &self.blame else {
+            if previous_line.is_none() {
+                ui.painter().text(
+                    rect.left_center(),
+                    egui::Align2::LEFT_CENTER,
+                    "正在计算 blame...",
+                    egui::FontId::proportional(12.0),
+                    ui.visuals().weak_text_color(),
+                );
+            }
+            return;
+        };
+
+        let index = blame.lines.get(line_idx).copied().flatten();
+        let starts_run = previous_line.is_none_or(|previous| blame.lines.get(previous).copied().flatten() != index);
+        let commit = blame.commit_of(line_idx);
+
+        if starts_run {
+            let text = match commit {
+                Some(commit) => {
+                    let author: String = commit.author.chars().take(10).collect();
+                    let date = &directory_listing::format_timestamp(commit.time)[..10];
+                    format!("{:<10} {} {}", author, date, commit.short_id())
+                }
+                None => "未提交".to_string(),
+            };
+            ui.painter().with_clip_rect(rect.intersect(ui.clip_rect())).text(
+                rect.left_center(),
+                egui::Align2::LEFT_CENTER,
+                text,
+                egui::FontId::monospace(12.0),
+                ui.visuals().weak_text_color(),
+            );
+        }
+
+        let Some(commit) = commit else {
+            return;
+        };
+        let id = commit.id.clone();
+        let response = response.on_hover_ui(|ui| {
+            ui.label(egui::RichText::new(commit.short_id()).monospace().strong());
+            ui.label(format!("{} · {} UTC", commit.author, directory_listing::format_timestamp(commit.time)));
+            ui.separator();
+            ui.label(&commit.message);
+            ui.weak("点击查看提交的改动");
+        });
+        if response.clicked() {
+            self.clicked_commit = Some(id);
+        }
+    }
+
     /// 检测滚动状态并更新计时器
     fn detect_scrolling_state(&mut self, ui: &egui::Ui) {
         // 获取当前滚动位置
@@ -313,45 +2685,46 @@ impl CodeEditor {
     /// 更新缓存的语法高亮行（只在代码变化时）
     fn update_cached_lines(&mut self) {
         let current_hash = Self::calculate_code_hash(&self.code);
+        self.code_hash = current_hash;
 
         // 如果代码没有变化，使用缓存
         if current_hash == self.last_code_hash && !self.cached_highlighted_lines.is_empty() {
             return;
         }
 
-        // 先收集所有需要的信息，避免同时借用
-        let old_lines: Vec<String> = if self.last_code_hash != 0 {
-            self.code.lines().map(|s| s.to_string()).collect()
-        } else {
-            Vec::new()
-        };
-        
-        let lines: Vec<String> = self.code.lines().map(|s| s.to_string()).collect();
+        // 与上次高亮时每行的哈希比较找出变化的行；last_code_hash 为 0 时全部重新高亮
+        let rehighlight_all = self.last_code_hash == 0;
+        let lines: Vec<&str> = self.code.lines().collect();
+        let line_hashes: Vec<u64> = lines.par_iter().map(|line| Self::calculate_code_hash(line)).collect();
         self.last_code_hash = current_hash;
 
-        // 如果行数减少，截断缓存并释放内存
-        if lines.len() < self.cached_highlighted_lines.len() {
-            let removed_jobs = self.cached_highlighted_lines.split_off(lines.len());
-            for job in removed_jobs {
-                self.return_layout_job_to_pool(job);
-            }
+        // 彩虹括号：行首深度变化的行也要重新着色
+        let old_depths = std::mem::take(&mut self.bracket_depths);
+        if self.rainbow_brackets {
+            self.bracket_depths = SyntaxHighlighter::with_language(self.language.as_deref()).line_depths(&self.code);
         }
 
         // 收集需要更新的行信息
         let mut lines_to_update = Vec::new();
         
         for (line_idx, line) in lines.iter().enumerate() {
-            let needs_update = if line_idx < old_lines.len() {
-                // 检查行是否发生变化
-                line_idx >= self.cached_highlighted_lines.len() || 
-                old_lines.get(line_idx) != Some(line)
-            } else {
-                // 新行
-                true
-            };
+            let needs_update = rehighlight_all
+                || line_idx >= self.cached_highlighted_lines.len()
+                || self.cached_line_hashes.get(line_idx) != Some(&line_hashes[line_idx]);
 
-            if needs_update {
-                lines_to_update.push((line_idx, line.clone()));
+            let depth_changed = self.rainbow_brackets && old_depths.get(line_idx) != self.bracket_depths.get(line_idx);
+            if needs_update || depth_changed {
+                lines_to_update.push((line_idx, line.to_string()));
+            }
+        }
+        self.cached_line_hashes = line_hashes;
+        let line_count = lines.len();
+
+        // 如果行数减少，截断缓存并释放内存
+        if line_count < self.cached_highlighted_lines.len() {
+            let removed_jobs = self.cached_highlighted_lines.split_off(line_count);
+            for job in removed_jobs {
+                self.return_layout_job_to_pool(job);
             }
         }
 
@@ -373,7 +2746,7 @@ impl CodeEditor {
         self.cached_line_height
     }
 
-    /// 计算可见行范围（只在滚动时重新计算）
+    /// 计算可见区域的行范围（折叠后的可见行，由调用方换算为源文件行）
     fn calculate_visible_lines(&mut self, ui: &egui::Ui, line_height: f32, total_lines: usize) -> (usize, usize) {
         // 获取视口信息
         let scroll_area_rect = ui.max_rect();
@@ -395,7 +2768,8 @@ impl CodeEditor {
     /// 从内存池获取或创建LayoutJob
     fn get_layout_job_from_pool(&mut self) -> egui::text::LayoutJob {
         if let Some(mut job) = self.layout_job_pool.pop() {
-            // 清空重用对象
+            // 清空重用对象（保留已分配的容量）
+            job.text.clear();
             job.sections.clear();
             job
         } else {
@@ -417,11 +2791,15 @@ impl CodeEditor {
         let font_id = egui::FontId::monospace(12.0);
         
         // 创建独立的语法高亮器实例用于并行处理
-        let highlighter = SyntaxHighlighter::new();
+        let mut highlighter = SyntaxHighlighter::with_language(self.language.as_deref());
+        if let Some(delimiter) = self.column_delimiter {
+            highlighter.set_delimiter(delimiter);
+        }
+        highlighter.set_rainbow_brackets(self.rainbow_brackets);
         
-        // 转换为引用格式用于并行解析
-        let lines_refs: Vec<(usize, &str)> = lines_to_update.iter()
-            .map(|(idx, s)| (*idx, s.as_str()))
+        // 转换为引用格式用于并行解析（附带行首的括号嵌套深度）
+        let lines_refs: Vec<(usize, &str, usize)> = lines_to_update.iter()
+            .map(|(idx, s)| (*idx, s.as_str(), self.bracket_depths.get(*idx).copied().unwrap_or(0)))
             .collect();
         
         // 使用并行解析
@@ -441,6 +2819,7 @@ impl CodeEditor {
                     },
                 );
             }
+            expand_tabs(&mut job, self.tab_width, self.space_width);
 
             if line_idx < self.cached_highlighted_lines.len() {
                 // 将旧的LayoutJob返回到内存池
@@ -467,21 +2846,88 @@ impl CodeEditor {
 pub struct StatusBar {
     pub file_path: Option<PathBuf>,
     pub status: String,
+    pub encoding_name: &'static str, // 当前文件编码
+    pub is_modified: bool,           // 是否有未保存的修改
+    pub file_type: String,           // 文件类型名称
+    pub branch: Option<String>,      // 当前 Git 分支
+    pub line_ending: LineEnding,     // 当前文件的换行符
+    pub indentation: (Indent, bool), // 当前文件的缩进方式，是否有不一致的行
+    pub tab_width: usize,            // 制表符宽度
+    pub position: (usize, usize),    // 光标所在的行和列（从 0 开始）
 }
 
 impl StatusBar {
     pub fn new(file_path: Option<PathBuf>, status: String) -> Self {
-        Self { file_path, status }
+        Self {
+            file_path,
+            status,
+            encoding_name: "UTF-8",
+            is_modified: false,
+            file_type: String::new(),
+            branch: None,
+            line_ending: LineEnding::Lf,
+            indentation: (Indent::Spaces(4), false),
+            tab_width: indentation::DEFAULT_TAB_WIDTH,
+            position: (0, 0),
+        }
     }
 
     /// 渲染状态栏
-    pub fn render(&mut self, ui: &mut egui::Ui) {
+    pub fn render(&mut self, ui: &mut egui::Ui, commands: &mut Vec<Command>) {
         if let Some(path) = &self.file_path {
             ui.horizontal(|ui| {
                 ui.label(format!("文件: {}", path.display()));
+                if self.is_modified {
+                    ui.label("● 未保存");
+                }
                 if !self.status.is_empty() {
                     ui.label(format!("状态: {}", self.status));
                 }
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.label(self.encoding_name);
+                    ui.separator();
+
+                    // 换行符和缩进：点击选择转换方式
+                    ui.menu_button(self.line_ending.name(), |ui| {
+                        for command in [Command::ConvertToLf, Command::ConvertToCrlf] {
+                            if ui.button(command.title()).clicked() {
+                                commands.push(command);
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                    ui.separator();
+                    let (indent, inconsistent) = self.indentation;
+                    let text = match inconsistent {
+                        true => egui::RichText::new(format!("{}（不一致）", indent.name())).color(ui.visuals().warn_fg_color),
+                        false => egui::RichText::new(indent.name()),
+                    };
+                    ui.menu_button(text, |ui| {
+                        for command in [Command::IndentWithSpaces, Command::IndentWithTabs] {
+                            if ui.button(command.title()).clicked() {
+                                commands.push(command);
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                    ui.separator();
+                    ui.menu_button(format!("制表符宽度: {}", self.tab_width), |ui| {
+                        for command in [Command::TabWidth2, Command::TabWidth4, Command::TabWidth8] {
+                            if ui.button(command.title()).clicked() {
+                                commands.push(command);
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                    ui.separator();
+                    ui.label(format!("行 {}, 列 {}", self.position.0 + 1, self.position.1 + 1));
+                    ui.separator();
+                    ui.label(&self.file_type);
+                    if let Some(branch) = &self.branch {
+                        ui.separator();
+                        ui.label(format!("⎇ {}", branch));
+                    }
+                });
             });
             // 移除分割线以减少额外的空间占用
         }
@@ -489,21 +2935,34 @@ impl StatusBar {
 }
 
 /// 设置框组件
-pub struct SettingsPanel;
+pub struct SettingsPanel {
+    capturing: Option<Command>, // 正在录制快捷键的命令
+    captured: KeySequence,      // 已录制的按键序列
+    save_error: Option<String>, // 保存快捷键配置时的错误
+}
 
 impl SettingsPanel {
     pub fn new() -> Self {
-        Self
+        Self {
+            capturing: None,
+            captured: Vec::new(),
+            save_error: None,
+        }
+    }
+
+    /// 是否正在录制快捷键（录制期间不应触发其他快捷键）
+    pub fn is_capturing(&self) -> bool {
+        self.capturing.is_some()
     }
 
     /// 渲染设置面板
-    pub fn render(&mut self, ui: &mut egui::Ui, available_height: f32, show_settings: &mut bool) {
+    pub fn render(&mut self, ui: &mut egui::Ui, available_height: f32, keymap: &mut Keymap, commands: &mut Vec<Command>) {
         ui.set_width(ui.available_width());
         ui.set_min_height(available_height);
 
         // 顶部返回按钮 - 紧贴顶部
         if ui.selectable_label(false, "返回文件列表").clicked() {
-            *show_settings = false;
+            commands.push(Command::ToggleSettings);
         }
         ui.separator();
 
@@ -516,8 +2975,236 @@ impl SettingsPanel {
                 ui.heading("界面设置");
                 ui.separator();
 
+                ui.horizontal_wrapped(|ui| {
+                    for command in [Command::ChangeTheme, Command::ChangeEncoding, Command::ToggleStatusBar] {
+                        if ui.button(command.title()).clicked() {
+                            commands.push(command);
+                        }
+                    }
+                });
+
                 ui.add_space(20.0);
-                ui.label("设置功能开发中...");
+                ui.heading("快捷键");
+                ui.separator();
+
+                self.render_keybindings(ui, keymap, commands);
+            });
+    }
+
+    /// 渲染快捷键列表和录制界面
+    fn render_keybindings(&mut self, ui: &mut egui::Ui, keymap: &mut Keymap, commands: &mut Vec<Command>) {
+        for error in &keymap.load_errors {
+            ui.colored_label(egui::Color32::from_rgb(230, 80, 80), format!("配置文件: {}", error));
+        }
+        if let Some(error) = &self.save_error {
+            ui.colored_label(egui::Color32::from_rgb(230, 80, 80), error);
+        }
+
+        // 录制中：收集按下的组合键，最多两个组合键
+        if self.capturing.is_some() && self.captured.len() < 2 {
+            ui.input_mut(|i| {
+                i.events.retain(|event| match event {
+                    egui::Event::Key { key, pressed: true, modifiers, .. } => {
+                        self.captured.push(shortcut_from_event(*key, *modifiers));
+                        false
+                    }
+                    _ => true,
+                })
+            });
+            self.captured.truncate(2);
+        }
+
+        let mut changed = false;
+        let conflicts = keymap.conflicts();
+
+        egui::Grid::new("keybinding_grid")
+            .num_columns(2)
+            .striped(true)
+            .show(ui, |ui| {
+                for command in Command::ALL.iter().copied() {
+                    ui.vertical(|ui| {
+                        ui.label(command.title());
+                        for other in Keymap::conflicts_of(&conflicts, command) {
+                            ui.colored_label(
+                                egui::Color32::from_rgb(230, 160, 60),
+                                format!("⚠ 与“{}”冲突", other.title()),
+                            );
+                        }
+                    });
+
+                    ui.horizontal(|ui| {
+                        if self.capturing == Some(command) {
+                            let text = if self.captured.is_empty() {
+                                "请按下快捷键...".to_string()
+                            } else {
+                                format_sequence(&self.captured)
+                            };
+                            ui.label(egui::RichText::new(text).monospace().strong());
+                            if ui.add_enabled(!self.captured.is_empty(), egui::Button::new("确认")).clicked() {
+                                keymap.set_binding(command, Some(std::mem::take(&mut self.captured)));
+                                self.capturing = None;
+                                changed = true;
+                            }
+                            if ui.button("取消").clicked() {
+                                self.capturing = None;
+                                self.captured.clear();
+                            }
+                        } else {
+                            let text = keymap.binding_text(command);
+                            ui.label(egui::RichText::new(if text.is_empty() { "—" } else { &text }).monospace());
+                            if ui.small_button("修改").clicked() {
+                                self.capturing = Some(command);
+                                self.captured.clear();
+                            }
+                            if !text.is_empty() && ui.small_button("移除").clicked() {
+                                keymap.set_binding(command, None);
+                                changed = true;
+                            }
+                            if !keymap.is_default(command) && ui.small_button("默认").clicked() {
+                                keymap.reset(command);
+                                changed = true;
+                            }
+                        }
+                    });
+                    ui.end_row();
+                }
+            });
+
+        ui.add_space(10.0);
+        if ui.button(Command::ResetKeybindings.title()).clicked() {
+            commands.push(Command::ResetKeybindings);
+        }
+
+        if changed {
+            self.save_error = keymap.save().err().map(|e| format!("保存快捷键配置失败: {}", e));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cached_text(editor: &CodeEditor) -> Vec<&str> {
+        editor.cached_highlighted_lines.iter().map(|job| job.text.as_str()).collect()
+    }
+
+    #[test]
+    fn set_code_rehighlights_changed_lines() {
+        let mut editor = CodeEditor::new("let a = 1;\nlet b = 2;\n".to_string());
+        editor.update_cached_lines();
+        assert_eq!(cached_text(&editor), ["let a = 1;", "let b = 2;"]);
+
+        // 行数不变、只修改其中一行
+        editor.set_code("fn x() {}\nlet b = 2;\n".to_string());
+        editor.update_cached_lines();
+        assert_eq!(cached_text(&editor), ["fn x() {}", "let b = 2;"]);
+
+        editor.set_code("fn x() {}\nlet c = 3;\nlet d = 4;\n".to_string());
+        editor.update_cached_lines();
+        assert_eq!(cached_text(&editor), ["fn x() {}", "let c = 3;", "let d = 4;"]);
+
+        editor.set_code("only".to_string());
+        editor.update_cached_lines();
+        assert_eq!(cached_text(&editor), ["only"]);
+    }
+
+    #[test]
+    fn reindent_and_line_ending_conversion_refresh_highlighted_lines() {
+        let mut editor = CodeEditor::new("fn a() {\n\tx;\n}\n".to_string());
+        editor.update_cached_lines();
+        editor.update_text_info();
+        assert_eq!(editor.indentation().0, Indent::Tabs);
+
+        assert!(editor.reindent(Indent::Spaces(4)));
+        editor.update_cached_lines();
+        assert_eq!(cached_text(&editor), ["fn a() {", "    x;", "}"]);
+
+        assert!(editor.convert_line_endings(LineEnding::CrLf));
+        assert_eq!(editor.code, "fn a() {\r\n    x;\r\n}\r\n");
+        editor.update_cached_lines();
+        assert_eq!(cached_text(&editor), ["fn a() {", "    x;", "}"]);
+    }
+
+    #[test]
+    fn reload_from_disk_updates_displayed_lines() {
+        let dir = std::env::temp_dir().join(format!("code_notebook_reload_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("main.rs");
+        std::fs::write(&path, "let a = 1;\nlet b = 2;\n").unwrap();
+
+        let mut app_state = crate::AppState { file_path: Some(path.clone()), ..Default::default() };
+        assert!(app_state.reload_from_disk());
+        let mut editor = CodeEditor::new(app_state.code.clone());
+        editor.update_cached_lines();
+        assert_eq!(cached_text(&editor), ["let a = 1;", "let b = 2;"]);
+
+        // 外部修改后重新读取，与布局同步状态时一样替换编辑器内容
+        std::fs::write(&path, "fn x() {}\nlet b = 2;\n").unwrap();
+        assert!(app_state.reload_from_disk());
+        editor.set_code(app_state.code.clone());
+        editor.update_cached_lines();
+        assert_eq!(cached_text(&editor), ["fn x() {}", "let b = 2;"]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn formatted_document_replaces_highlighted_lines() {
+        // 格式化不改变行数时每行内容仍然更新
+        let mut editor = CodeEditor::new("{\n\"a\":1\n}".to_string());
+        editor.update_cached_lines();
+        let pretty = crate::structured::pretty(crate::structured::Format::Json, &editor.code).unwrap();
+        assert_eq!(pretty.lines().count(), 3);
+        editor.set_code(pretty.clone());
+        editor.update_cached_lines();
+        assert_eq!(cached_text(&editor), pretty.lines().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn file_browser_rows_follow_expansion_and_disk_changes() {
+        let root = std::env::temp_dir().join(format!("code_notebook_tree_{}", std::process::id()));
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("src").join("main.rs"), "").unwrap();
+        std::fs::write(root.join("README.md"), "").unwrap();
+
+        let mut browser = FileBrowser::new(root.clone());
+        browser.load_directory_content();
+        let names = |browser: &mut FileBrowser| -> Vec<String> {
+            browser.collect_rows().iter().map(|row| row.item.name.clone()).collect()
+        };
+        assert_eq!(names(&mut browser), ["src", "README.md"]);
+
+        browser.toggle_expanded(&root.join("src"));
+        assert_eq!(names(&mut browser), ["src", "main.rs", "README.md"]);
+
+        // 展开目录中新增的文件在重新读取该目录后出现
+        std::fs::write(root.join("src").join("lib.rs"), "").unwrap();
+        browser.refresh_directory(&root.join("src"));
+        assert_eq!(names(&mut browser), ["src", "lib.rs", "main.rs", "README.md"]);
+
+        browser.toggle_expanded(&root.join("src"));
+        assert_eq!(names(&mut browser), ["src", "README.md"]);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn inline_rename_rejects_paths() {
+        let root = std::env::temp_dir().join(format!("code_notebook_rename_{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        let file = root.join("a.txt");
+        std::fs::write(&file, "").unwrap();
+
+        let mut browser = FileBrowser::new(root.clone());
+        for name in ["../b.txt", "sub/b.txt", "sub\\b.txt", ".", ".."] {
+            browser.inline_edit = Some(InlineEdit {
+                kind: InlineEditKind::Rename(file.clone()),
+                text: name.to_string(),
+                request_focus: false,
             });
+            browser.commit_inline_edit();
+            assert!(matches!(browser.take_events().as_slice(), [FileEvent::Error(_)]), "{}", name);
+            assert!(file.exists());
+        }
+        let _ = std::fs::remove_dir_all(&root);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,275 @@
+use eframe::egui;
+use crate::diff::{DiffLine, FileDiff, RowKind};
+use crate::ui::commands::Command;
+use crate::ui::styles;
+use crate::ui::syntax_highlighter::SyntaxHighlighter;
+
+/// 行号栏宽度
+const LINE_NUMBER_WIDTH: f32 = 44.0;
+
+/// 比较视图的显示方式
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum DiffLayout {
+    SideBySide, // 左右并排
+    Inline,     // 上下交错（先删除后新增）
+}
+
+/// 内联显示的一行：对应并排行的哪一侧
+#[derive(Clone, Copy)]
+enum InlineSide {
+    Both,  // 相同的行只显示一次
+    Left,  // 删除的行
+    Right, // 新增的行
+}
+
+/// 比较视图组件（只读）
+pub struct DiffView {
+    left_title: String,
+    right_title: String,
+    diff: FileDiff,
+    highlighter: SyntaxHighlighter,
+    layout: DiffLayout,
+    inline_rows: Vec<(usize, InlineSide)>, // 内联显示的行：并排行下标和显示的一侧
+    current_hunk: Option<usize>,
+    scroll_to_row: Option<usize>, // 下一帧滚动到的行（当前显示方式下的下标）
+}
+
+impl DiffView {
+    pub fn new(left_title: String, right_title: String, diff: FileDiff, language: Option<&str>) -> Self {
+        let inline_rows = Self::build_inline_rows(&diff);
+        Self {
+            left_title,
+            right_title,
+            diff,
+            highlighter: SyntaxHighlighter::with_language(language),
+            layout: DiffLayout::SideBySide,
+            inline_rows,
+            current_hunk: None,
+            scroll_to_row: None,
+        }
+    }
+
+    /// 内联显示时每个变化块先列出全部删除的行，再列出全部新增的行
+    fn build_inline_rows(diff: &FileDiff) -> Vec<(usize, InlineSide)> {
+        let mut rows = Vec::with_capacity(diff.rows.len());
+        let mut block_start = 0;
+        for (index, row) in diff.rows.iter().enumerate() {
+            if row.kind != RowKind::Equal {
+                continue;
+            }
+            Self::push_inline_block(diff, block_start..index, &mut rows);
+            rows.push((index, InlineSide::Both));
+            block_start = index + 1;
+        }
+        Self::push_inline_block(diff, block_start..diff.rows.len(), &mut rows);
+        rows
+    }
+
+    fn push_inline_block(diff: &FileDiff, block: std::ops::Range<usize>, rows: &mut Vec<(usize, InlineSide)>) {
+        rows.extend(block.clone().filter(|&i| diff.rows[i].left.is_some()).map(|i| (i, InlineSide::Left)));
+        rows.extend(block.filter(|&i| diff.rows[i].right.is_some()).map(|i| (i, InlineSide::Right)));
+    }
+
+    /// 切换并排和内联显示，保持当前变化块可见
+    pub fn toggle_layout(&mut self) {
+        self.layout = match self.layout {
+            DiffLayout::SideBySide => DiffLayout::Inline,
+            DiffLayout::Inline => DiffLayout::SideBySide,
+        };
+        if let Some(hunk) = self.current_hunk {
+            self.scroll_to_hunk(hunk);
+        }
+    }
+
+    /// 跳转到下一个变化块（到末尾后从头开始）
+    pub fn next_hunk(&mut self) {
+        if self.diff.hunks.is_empty() {
+            return;
+        }
+        let next = self.current_hunk.map_or(0, |hunk| (hunk + 1) % self.diff.hunks.len());
+        self.scroll_to_hunk(next);
+    }
+
+    /// 跳转到上一个变化块（到开头后从末尾开始）
+    pub fn previous_hunk(&mut self) {
+        if self.diff.hunks.is_empty() {
+            return;
+        }
+        let count = self.diff.hunks.len();
+        let previous = self.current_hunk.map_or(count - 1, |hunk| (hunk + count - 1) % count);
+        self.scroll_to_hunk(previous);
+    }
+
+    fn scroll_to_hunk(&mut self, hunk: usize) {
+        self.current_hunk = Some(hunk);
+        let row = self.diff.hunks[hunk];
+        self.scroll_to_row = Some(match self.layout {
+            DiffLayout::SideBySide => row,
+            DiffLayout::Inline => self.inline_rows.iter().position(|&(index, _)| index >= row).unwrap_or(0),
+        });
+    }
+
+    /// 渲染比较视图
+    pub fn render(&mut self, ui: &mut egui::Ui, available_height: f32, commands: &mut Vec<Command>) {
+        ui.set_width(ui.available_width());
+        ui.set_min_height(available_height);
+
+        self.render_toolbar(ui, commands);
+        ui.separator();
+
+        let font = egui::FontId::monospace(12.0);
+        let row_height = ui.fonts(|f| f.row_height(&font)) + 2.0;
+        let row_count = match self.layout {
+            DiffLayout::SideBySide => self.diff.rows.len(),
+            DiffLayout::Inline => self.inline_rows.len(),
+        };
+
+        // 行之间不留间距，滚动偏移可以直接按行高计算
+        ui.spacing_mut().item_spacing.y = 0.0;
+        let mut scroll_area = egui::ScrollArea::vertical()
+            .id_source("diff_rows")
+            .auto_shrink([false, false]);
+        if let Some(row) = self.scroll_to_row.take() {
+            scroll_area = scroll_area.vertical_scroll_offset(row.saturating_sub(3) as f32 * row_height);
+        }
+
+        // 两侧在同一个滚动区域中，滚动天然同步
+        scroll_area.show_rows(ui, row_height, row_count, |ui, row_range| {
+            for index in row_range {
+                let (rect, _) = ui.allocate_exact_size(egui::vec2(ui.available_width(), row_height), egui::Sense::hover());
+                match self.layout {
+                    DiffLayout::SideBySide => self.paint_side_by_side_row(ui, rect, index, &font),
+                    DiffLayout::Inline => self.paint_inline_row(ui, rect, index, &font),
+                }
+            }
+        });
+    }
+
+    fn render_toolbar(&mut self, ui: &mut egui::Ui, commands: &mut Vec<Command>) {
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new(format!("{}  ↔  {}", self.left_title, self.right_title)).strong());
+            ui.colored_label(styles::GIT_ADDED_COLOR, format!("+{}", self.diff.added));
+            ui.colored_label(styles::GIT_DELETED_COLOR, format!("-{}", self.diff.removed));
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.button("✖ 关闭").clicked() {
+                    commands.push(Command::CloseDiff);
+                }
+                let layout_label = match self.layout {
+                    DiffLayout::SideBySide => "内联显示",
+                    DiffLayout::Inline => "并排显示",
+                };
+                if ui.button(layout_label).clicked() {
+                    commands.push(Command::ToggleDiffLayout);
+                }
+                if ui.button("⬇ 下一处").clicked() {
+                    commands.push(Command::NextChange);
+                }
+                if ui.button("⬆ 上一处").clicked() {
+                    commands.push(Command::PreviousChange);
+                }
+
+                let position = match (self.current_hunk, self.diff.hunks.len()) {
+                    (_, 0) => "没有差异".to_string(),
+                    (Some(hunk), count) => format!("第 {}/{} 处", hunk + 1, count),
+                    (None, count) => format!("共 {} 处", count),
+                };
+                ui.label(position);
+            });
+        });
+    }
+
+    fn paint_side_by_side_row(&self, ui: &egui::Ui, rect: egui::Rect, index: usize, font: &egui::FontId) {
+        let row = &self.diff.rows[index];
+        let half = rect.width() / 2.0;
+        let left_rect = egui::Rect::from_min_size(rect.min, egui::vec2(half - 1.0, rect.height()));
+        let right_rect = egui::Rect::from_min_size(rect.min + egui::vec2(half + 1.0, 0.0), egui::vec2(half - 1.0, rect.height()));
+
+        let left_color = matches!(row.kind, RowKind::Deleted | RowKind::Changed).then_some(styles::GIT_DELETED_COLOR);
+        let right_color = matches!(row.kind, RowKind::Inserted | RowKind::Changed).then_some(styles::GIT_ADDED_COLOR);
+        let number = |line: &Option<DiffLine>| line.as_ref().map(|line| format!("{:>4}", line.number + 1)).unwrap_or_default();
+        self.paint_line(ui, left_rect, row.left.as_ref(), number(&row.left), LINE_NUMBER_WIDTH, left_color, font);
+        self.paint_line(ui, right_rect, row.right.as_ref(), number(&row.right), LINE_NUMBER_WIDTH, right_color, font);
+
+        ui.painter().vline(rect.center().x, rect.y_range(), ui.visuals().widgets.noninteractive.bg_stroke);
+    }
+
+    fn paint_inline_row(&self, ui: &egui::Ui, rect: egui::Rect, index: usize, font: &egui::FontId) {
+        let (row_index, side) = self.inline_rows[index];
+        let row = &self.diff.rows[row_index];
+        let number = |line: &Option<DiffLine>| line.as_ref().map(|line| (line.number + 1).to_string()).unwrap_or_default();
+
+        // 内联显示时同时列出两侧行号
+        let (line, numbers, color) = match side {
+            InlineSide::Both => (&row.right, (number(&row.left), number(&row.right)), None),
+            InlineSide::Left => (&row.left, (number(&row.left), String::new()), Some(styles::GIT_DELETED_COLOR)),
+            InlineSide::Right => (&row.right, (String::new(), number(&row.right)), Some(styles::GIT_ADDED_COLOR)),
+        };
+        let number_text = format!("{:>4} {:>4}", numbers.0, numbers.1);
+        self.paint_line(ui, rect, line.as_ref(), number_text, LINE_NUMBER_WIDTH * 2.0, color, font);
+    }
+
+    /// 绘制一行：背景、行号和带语法高亮的内容；没有内容的一侧画成空白
+    #[allow(clippy::too_many_arguments)]
+    fn paint_line(
+        &self,
+        ui: &egui::Ui,
+        rect: egui::Rect,
+        line: Option<&DiffLine>,
+        number: String,
+        gutter: f32,
+        change_color: Option<egui::Color32>,
+        font: &egui::FontId,
+    ) {
+        let painter = ui.painter().with_clip_rect(rect.intersect(ui.clip_rect()));
+        let Some(line) = line else {
+            painter.rect_filled(rect, 0.0, ui.visuals().faint_bg_color);
+            return;
+        };
+        if let Some(color) = change_color {
+            painter.rect_filled(rect, 0.0, color.gamma_multiply(0.15));
+        }
+
+        painter.text(
+            rect.left_center() + egui::vec2(4.0, 0.0),
+            egui::Align2::LEFT_CENTER,
+            number,
+            font.clone(),
+            ui.visuals().weak_text_color(),
+        );
+
+        let emphasis_color = change_color.map_or(egui::Color32::TRANSPARENT, |color| color.gamma_multiply(0.45));
+        let job = self.line_job(line, emphasis_color, font);
+        let galley = ui.fonts(|f| f.layout_job(job));
+        let text_pos = egui::pos2(rect.left() + gutter, rect.center().y - galley.size().y / 2.0);
+        painter.galley(text_pos, galley);
+    }
+
+    /// 语法高亮的行内容，行内变化的片段加深背景
+    fn line_job(&self, line: &DiffLine, emphasis_color: egui::Color32, font: &egui::FontId) -> egui::text::LayoutJob {
+        let mut job = egui::text::LayoutJob::default();
+        for token in self.highlighter.parse_line_public(&line.text, 0) {
+            let mut start = token.start_col;
+            while start < token.end_col {
+                // 在变化片段的边界处拆分 token
+                let (end, emphasized) = match line.emphasis.iter().find(|range| range.end > start) {
+                    Some(range) if range.start <= start => (range.end.min(token.end_col), true),
+                    Some(range) => (range.start.min(token.end_col), false),
+                    None => (token.end_col, false),
+                };
+                job.append(
+                    &line.text[start..end],
+                    0.0,
+                    egui::TextFormat {
+                        font_id: font.clone(),
+                        color: token.color,
+                        background: if emphasized { emphasis_color } else { egui::Color32::TRANSPARENT },
+                        ..Default::default()
+                    },
+                );
+                start = end;
+            }
+        }
+        job
+    }
+}
@@ -0,0 +1,406 @@
+use eframe::egui;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use crate::directory_listing;
+use crate::ui::commands::Command;
+use crate::ui::styles;
+
+/// 每行显示的字节数
+const BYTES_PER_ROW: usize = 16;
+/// 最多记录的匹配数
+const MAX_MATCHES: usize = 100_000;
+
+/// 下一帧获得焦点的输入框
+#[derive(Clone, Copy)]
+enum HexInput {
+    Offset,
+    Search,
+}
+
+/// 十六进制查看器组件（只读）
+pub struct HexView {
+    path: PathBuf,
+    data: Vec<u8>,
+    error: Option<String>,               // 读取失败的原因
+    selection: Option<(usize, usize)>,   // 选择的起点和终点（含两端）
+    dragging: bool,                      // 正在拖动选择
+    offset_input: String,
+    search_input: String,
+    matches: Vec<usize>,                 // 查找结果的起始偏移（升序）
+    pattern_len: usize,
+    current_match: Option<usize>,
+    message: Option<String>,             // 转到和查找的结果提示
+    focus: Option<HexInput>,
+    scroll_to_row: Option<usize>,
+}
+
+impl HexView {
+    pub fn open(path: PathBuf) -> Self {
+        let (data, error) = match std::fs::read(&path) {
+            Ok(data) => (data, None),
+            Err(e) => (Vec::new(), Some(e.to_string())),
+        };
+        Self {
+            path,
+            data,
+            error,
+            selection: None,
+            dragging: false,
+            offset_input: String::new(),
+            search_input: String::new(),
+            matches: Vec::new(),
+            pattern_len: 0,
+            current_match: None,
+            message: None,
+            focus: None,
+            scroll_to_row: None,
+        }
+    }
+
+    /// 打开的文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 聚焦到偏移输入框
+    pub fn focus_offset_input(&mut self) {
+        self.focus = Some(HexInput::Offset);
+    }
+
+    /// 聚焦到查找输入框
+    pub fn focus_search_input(&mut self) {
+        self.focus = Some(HexInput::Search);
+    }
+
+    fn selected_range(&self) -> Option<RangeInclusive<usize>> {
+        self.selection.map(|(anchor, cursor)| anchor.min(cursor)..=anchor.max(cursor))
+    }
+
+    /// 选中并滚动到指定范围
+    fn select(&mut self, start: usize, len: usize) {
+        self.selection = Some((start, start + len.max(1) - 1));
+        self.scroll_to_row = Some(start / BYTES_PER_ROW);
+    }
+
+    /// 转到输入的偏移（十进制，或以 0x 开头的十六进制）
+    fn go_to_offset(&mut self) {
+        let text = self.offset_input.trim();
+        let offset = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+            Some(hex) => usize::from_str_radix(hex, 16),
+            None => text.parse(),
+        };
+        self.message = match offset {
+            Ok(offset) if offset < self.data.len() => {
+                self.select(offset, 1);
+                None
+            }
+            Ok(_) => Some(format!("偏移超出文件大小（{} 字节）", self.data.len())),
+            Err(_) => Some("无效的偏移".to_string()),
+        };
+    }
+
+    /// 按输入的字节序列查找，跳到选择之后的第一个匹配
+    fn search(&mut self) {
+        self.matches.clear();
+        self.current_match = None;
+        let Some(pattern) = parse_pattern(&self.search_input) else {
+            self.message = None;
+            return;
+        };
+        self.pattern_len = pattern.len();
+        self.matches = self
+            .data
+            .windows(pattern.len())
+            .enumerate()
+            .filter(|(_, window)| *window == pattern.as_slice())
+            .map(|(offset, _)| offset)
+            .take(MAX_MATCHES)
+            .collect();
+        if self.matches.is_empty() {
+            self.message = Some("未找到".to_string());
+            return;
+        }
+        let from = self.selected_range().map_or(0, |range| *range.start());
+        let index = self.matches.partition_point(|&offset| offset < from) % self.matches.len();
+        self.show_match(index);
+    }
+
+    /// 跳转到下一个匹配（到末尾后从头开始）
+    pub fn find_next(&mut self) {
+        if self.matches.is_empty() {
+            self.search();
+            return;
+        }
+        let next = self.current_match.map_or(0, |index| (index + 1) % self.matches.len());
+        self.show_match(next);
+    }
+
+    /// 跳转到上一个匹配（到开头后从末尾开始）
+    pub fn find_previous(&mut self) {
+        if self.matches.is_empty() {
+            self.search();
+            return;
+        }
+        let count = self.matches.len();
+        let previous = self.current_match.map_or(count - 1, |index| (index + count - 1) % count);
+        self.show_match(previous);
+    }
+
+    fn show_match(&mut self, index: usize) {
+        self.current_match = Some(index);
+        self.select(self.matches[index], self.pattern_len);
+        let more = if self.matches.len() == MAX_MATCHES { "+" } else { "" };
+        self.message = Some(format!("第 {}/{}{} 个匹配", index + 1, self.matches.len(), more));
+    }
+
+    /// 渲染十六进制查看器
+    pub fn render(&mut self, ui: &mut egui::Ui, available_height: f32, commands: &mut Vec<Command>) {
+        ui.set_width(ui.available_width());
+        ui.set_min_height(available_height);
+
+        self.render_toolbar(ui, commands);
+        ui.separator();
+        if let Some(e) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, format!("读取失败: {}", e));
+            return;
+        }
+
+        let font = egui::FontId::monospace(13.0);
+        let char_width = ui.fonts(|f| f.glyph_width(&font, '0'));
+        let row_height = ui.fonts(|f| f.row_height(&font)) + 2.0;
+        let row_count = self.data.len().div_ceil(BYTES_PER_ROW);
+
+        // 底部留出数值解释区域
+        let inspector_height = ui.text_style_height(&egui::TextStyle::Body) * 5.0 + 24.0;
+        let rows_height = (ui.available_height() - inspector_height).max(row_height * 4.0);
+
+        ui.scope(|ui| {
+            ui.set_max_height(rows_height);
+            ui.spacing_mut().item_spacing.y = 0.0;
+            let mut scroll_area = egui::ScrollArea::vertical()
+                .id_source("hex_rows")
+                .auto_shrink([false, false])
+                .max_height(rows_height);
+            if let Some(row) = self.scroll_to_row.take() {
+                scroll_area = scroll_area.vertical_scroll_offset(row.saturating_sub(3) as f32 * row_height);
+            }
+            scroll_area.show_rows(ui, row_height, row_count, |ui, row_range| {
+                for row in row_range {
+                    self.render_row(ui, row, row_height, char_width, &font);
+                }
+            });
+        });
+        if !ui.input(|i| i.pointer.primary_down()) {
+            self.dragging = false;
+        }
+
+        ui.separator();
+        self.render_inspector(ui);
+    }
+
+    fn render_toolbar(&mut self, ui: &mut egui::Ui, commands: &mut Vec<Command>) {
+        ui.horizontal(|ui| {
+            let name = self.path.file_name().map_or_else(|| self.path.display().to_string(), |name| name.to_string_lossy().into_owned());
+            ui.label(egui::RichText::new(name).strong()).on_hover_text(self.path.display().to_string());
+            ui.weak(format!("{} 字节 · {}", self.data.len(), directory_listing::format_size(self.data.len() as u64)));
+
+            ui.separator();
+            ui.label("转到:");
+            let offset_edit = ui.add(
+                egui::TextEdit::singleline(&mut self.offset_input)
+                    .desired_width(90.0)
+                    .hint_text("0x1F0"),
+            ).on_hover_text("十进制偏移，或以 0x 开头的十六进制偏移");
+            if offset_edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                self.go_to_offset();
+            }
+
+            ui.label("查找:");
+            let search_edit = ui.add(
+                egui::TextEdit::singleline(&mut self.search_input)
+                    .desired_width(160.0)
+                    .hint_text("DE AD BE EF"),
+            ).on_hover_text("十六进制字节序列；不是十六进制或以引号开头时按文本查找");
+            if search_edit.changed() {
+                self.matches.clear();
+                self.current_match = None;
+            }
+            if search_edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                self.find_next();
+                search_edit.request_focus();
+            }
+            match self.focus.take() {
+                Some(HexInput::Offset) => offset_edit.request_focus(),
+                Some(HexInput::Search) => search_edit.request_focus(),
+                None => {}
+            }
+            if ui.button("⬆").on_hover_text("上一个匹配").clicked() {
+                commands.push(Command::HexFindPrevious);
+            }
+            if ui.button("⬇").on_hover_text("下一个匹配").clicked() {
+                commands.push(Command::HexFindNext);
+            }
+            if let Some(message) = &self.message {
+                ui.label(message);
+            }
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.button("✖ 关闭").clicked() {
+                    commands.push(Command::CloseHexView);
+                }
+            });
+        });
+    }
+
+    /// 一行：偏移、十六进制和 ASCII 三列
+    fn render_row(&mut self, ui: &mut egui::Ui, row: usize, row_height: f32, char_width: f32, font: &egui::FontId) {
+        let (rect, response) = ui.allocate_exact_size(egui::vec2(ui.available_width(), row_height), egui::Sense::click_and_drag());
+        let start = row * BYTES_PER_ROW;
+        let end = (start + BYTES_PER_ROW).min(self.data.len());
+
+        // 各列的横向位置（以字符宽度为单位）：偏移 8 位，十六进制每字节 3 位且中间多空一位
+        let hex_left = rect.left() + 10.0 * char_width;
+        let ascii_left = hex_left + (BYTES_PER_ROW * 3 + 2) as f32 * char_width;
+        let hex_x = |column: usize| hex_left + (column * 3 + usize::from(column >= BYTES_PER_ROW / 2)) as f32 * char_width;
+
+        // 按下开始选择（Shift 扩展已有选择），拖动时更新终点
+        let pointer_index = ui.input(|i| i.pointer.hover_pos()).and_then(|pos| {
+            let column = if pos.x >= ascii_left {
+                ((pos.x - ascii_left) / char_width) as usize
+            } else if pos.x >= hex_left {
+                let mut cell = ((pos.x - hex_left) / char_width) as usize;
+                if cell >= BYTES_PER_ROW / 2 * 3 {
+                    cell = cell.saturating_sub(1);
+                }
+                cell / 3
+            } else {
+                return None;
+            };
+            (column < end - start).then_some(start + column)
+        });
+        if response.hovered() && ui.input(|i| i.pointer.primary_pressed()) {
+            if let Some(index) = pointer_index {
+                let shift = ui.input(|i| i.modifiers.shift);
+                self.selection = match self.selection {
+                    Some((anchor, _)) if shift => Some((anchor, index)),
+                    _ => Some((index, index)),
+                };
+                self.dragging = true;
+            }
+        } else if self.dragging && ui.rect_contains_pointer(rect) {
+            if let (Some((anchor, _)), Some(index)) = (self.selection, pointer_index) {
+                self.selection = Some((anchor, index));
+            }
+        }
+
+        let painter = ui.painter().with_clip_rect(rect.intersect(ui.clip_rect()));
+        let visuals = ui.visuals();
+        painter.text(
+            egui::pos2(rect.left(), rect.center().y),
+            egui::Align2::LEFT_CENTER,
+            format!("{:08X}", start),
+            font.clone(),
+            visuals.weak_text_color(),
+        );
+
+        let selection = self.selected_range();
+        let current_match = self.current_match.map(|index| self.matches[index]..self.matches[index] + self.pattern_len);
+        for (column, &byte) in self.data[start..end].iter().enumerate() {
+            let index = start + column;
+            let background = if selection.as_ref().is_some_and(|range| range.contains(&index)) {
+                Some(visuals.selection.bg_fill)
+            } else if current_match.as_ref().is_some_and(|range| range.contains(&index)) {
+                Some(styles::GIT_MODIFIED_COLOR.gamma_multiply(0.4))
+            } else {
+                None
+            };
+
+            let hex_pos = egui::pos2(hex_x(column), rect.top());
+            let ascii_pos = egui::pos2(ascii_left + column as f32 * char_width, rect.top());
+            if let Some(color) = background {
+                painter.rect_filled(egui::Rect::from_min_size(hex_pos, egui::vec2(char_width * 2.0, row_height)), 0.0, color);
+                painter.rect_filled(egui::Rect::from_min_size(ascii_pos, egui::vec2(char_width, row_height)), 0.0, color);
+            }
+
+            // 零字节和不可打印字符显示得淡一些
+            let color = if byte == 0 { visuals.weak_text_color() } else { visuals.text_color() };
+            painter.text(egui::pos2(hex_pos.x, rect.center().y), egui::Align2::LEFT_CENTER, format!("{:02X}", byte), font.clone(), color);
+            let (ch, color) = if byte.is_ascii_graphic() || byte == b' ' {
+                (byte as char, visuals.text_color())
+            } else {
+                ('.', visuals.weak_text_color())
+            };
+            painter.text(egui::pos2(ascii_pos.x, rect.center().y), egui::Align2::LEFT_CENTER, ch, font.clone(), color);
+        }
+    }
+
+    /// 选择起点处的字节按各种数值类型解释
+    fn render_inspector(&self, ui: &mut egui::Ui) {
+        let Some(range) = self.selected_range() else {
+            ui.weak("单击或拖动选择字节，Shift+单击扩展选择");
+            return;
+        };
+        let start = *range.start();
+        let len = range.end() - start + 1;
+        ui.label(format!("选择: 0x{:X} ({})，{} 字节", start, start, len));
+
+        // 两列并排：8 和 16 位整数、32 位整数和浮点数
+        let values = interpretations(&self.data[start..]);
+        ui.horizontal_top(|ui| {
+            for (grid, chunk) in values.chunks(4).enumerate() {
+                egui::Grid::new(("hex_inspector", grid)).num_columns(3).spacing([16.0, 2.0]).striped(true).show(ui, |ui| {
+                    ui.strong("类型");
+                    ui.strong("小端");
+                    ui.strong("大端");
+                    ui.end_row();
+                    for (name, little, big) in chunk {
+                        ui.label(*name);
+                        ui.monospace(little);
+                        ui.monospace(big);
+                        ui.end_row();
+                    }
+                });
+                ui.add_space(24.0);
+            }
+        });
+    }
+}
+
+/// 从指定位置开始的字节按 u8/i8/u16/i16/u32/i32/f32 解释：(类型, 小端, 大端)，剩余字节不足的类型省略
+fn interpretations(bytes: &[u8]) -> Vec<(&'static str, String, String)> {
+    let mut values = Vec::new();
+    if let Some(&[b0]) = bytes.get(..1) {
+        values.push(("u8", b0.to_string(), b0.to_string()));
+        values.push(("i8", (b0 as i8).to_string(), (b0 as i8).to_string()));
+    }
+    if let Some(&[b0, b1]) = bytes.get(..2) {
+        let b = [b0, b1];
+        values.push(("u16", u16::from_le_bytes(b).to_string(), u16::from_be_bytes(b).to_string()));
+        values.push(("i16", i16::from_le_bytes(b).to_string(), i16::from_be_bytes(b).to_string()));
+    }
+    if let Some(&[b0, b1, b2, b3]) = bytes.get(..4) {
+        let b = [b0, b1, b2, b3];
+        values.push(("u32", u32::from_le_bytes(b).to_string(), u32::from_be_bytes(b).to_string()));
+        values.push(("i32", i32::from_le_bytes(b).to_string(), i32::from_be_bytes(b).to_string()));
+        values.push(("f32", f32::from_le_bytes(b).to_string(), f32::from_be_bytes(b).to_string()));
+    }
+    values
+}
+
+/// 查找内容：十六进制字节序列（可含空格），否则按 UTF-8 文本；以引号开头时总是按文本
+fn parse_pattern(text: &str) -> Option<Vec<u8>> {
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+    if let Some(quoted) = text.strip_prefix('"') {
+        let quoted = quoted.strip_suffix('"').unwrap_or(quoted);
+        return (!quoted.is_empty()).then(|| quoted.as_bytes().to_vec());
+    }
+    let digits: Vec<u8> = text.bytes().filter(|byte| !byte.is_ascii_whitespace()).collect();
+    if digits.len().is_multiple_of(2) && digits.iter().all(u8::is_ascii_hexdigit) {
+        let hex = std::str::from_utf8(&digits).ok()?;
+        return (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok()).collect();
+    }
+    Some(text.as_bytes().to_vec())
+}
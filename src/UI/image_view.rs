@@ -0,0 +1,275 @@
+use eframe::egui;
+use std::path::{Path, PathBuf};
+use crate::directory_listing;
+use crate::ui::commands::Command;
+
+/// 缩放范围
+const MIN_ZOOM: f32 = 0.02;
+const MAX_ZOOM: f32 = 64.0;
+/// 工具栏按钮每次缩放的倍数
+const ZOOM_STEP: f32 = 1.25;
+/// 放大到该倍数以上时才显示像素网格
+const GRID_MIN_ZOOM: f32 = 8.0;
+
+/// 解码后的图像及其信息
+struct LoadedImage {
+    pixels: image::RgbaImage,
+    format: String,     // 文件格式（按内容识别）
+    color_type: String, // 原始的颜色类型，如 Rgba8
+    file_size: u64,
+}
+
+/// 图像查看器组件（只读）
+pub struct ImageView {
+    path: PathBuf,
+    image: Result<LoadedImage, String>,
+    texture: Option<egui::TextureHandle>, // 首次渲染时创建
+    zoom: f32,
+    offset: egui::Vec2,   // 图像中心相对画布中心的偏移
+    fit: bool,            // 适应窗口：画布大小变化时重新计算缩放
+    canvas: egui::Rect,   // 上一帧的画布区域（工具栏缩放时以其中心为基准）
+    pub show_grid: bool,  // 高倍放大时显示像素网格
+    hovered_pixel: Option<(u32, u32, [u8; 4])>, // 鼠标下的像素坐标和颜色
+    copied_color: Option<String>, // 最近一次单击复制的颜色值
+}
+
+impl ImageView {
+    pub fn open(path: PathBuf) -> Self {
+        let image = Self::load(&path);
+        Self {
+            path,
+            image,
+            texture: None,
+            zoom: 1.0,
+            offset: egui::Vec2::ZERO,
+            fit: true,
+            canvas: egui::Rect::NOTHING,
+            show_grid: true,
+            hovered_pixel: None,
+            copied_color: None,
+        }
+    }
+
+    fn load(path: &Path) -> Result<LoadedImage, String> {
+        let reader = image::io::Reader::open(path)
+            .and_then(|reader| reader.with_guessed_format())
+            .map_err(|e| e.to_string())?;
+        let format = reader.format().map_or_else(|| "未知".to_string(), |format| format!("{:?}", format).to_uppercase());
+        let decoded = reader.decode().map_err(|e| e.to_string())?;
+        Ok(LoadedImage {
+            color_type: format!("{:?}", decoded.color()),
+            pixels: decoded.to_rgba8(),
+            format,
+            file_size: std::fs::metadata(path).map(|m| m.len()).unwrap_or(0),
+        })
+    }
+
+    /// 打开的图像路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn zoom_in(&mut self) {
+        self.zoom_around(self.canvas.center(), ZOOM_STEP);
+    }
+
+    pub fn zoom_out(&mut self) {
+        self.zoom_around(self.canvas.center(), 1.0 / ZOOM_STEP);
+    }
+
+    /// 缩小到完整显示在窗口中（小图像保持原始大小）
+    pub fn zoom_to_fit(&mut self) {
+        self.fit = true;
+    }
+
+    /// 按原始大小（1:1）居中显示
+    pub fn zoom_actual_size(&mut self) {
+        self.fit = false;
+        self.zoom = 1.0;
+        self.offset = egui::Vec2::ZERO;
+    }
+
+    /// 以画布上的某一点为中心缩放，该点下的像素保持不动
+    fn zoom_around(&mut self, anchor: egui::Pos2, factor: f32) {
+        if !self.canvas.is_positive() {
+            return;
+        }
+        let zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+        let image_point = (anchor - (self.canvas.center() + self.offset)) / self.zoom;
+        self.offset = anchor - image_point * zoom - self.canvas.center();
+        self.zoom = zoom;
+        self.fit = false;
+    }
+
+    /// 渲染图像查看器
+    pub fn render(&mut self, ui: &mut egui::Ui, available_height: f32, commands: &mut Vec<Command>) {
+        ui.set_width(ui.available_width());
+        ui.set_min_height(available_height);
+
+        self.render_toolbar(ui, commands);
+        ui.separator();
+
+        let loaded = match &self.image {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                ui.colored_label(ui.visuals().error_fg_color, format!("无法加载图像: {}", e));
+                return;
+            }
+        };
+        let (width, height) = loaded.pixels.dimensions();
+        let texture_id = self.texture.get_or_insert_with(|| {
+            let color_image = egui::ColorImage::from_rgba_unmultiplied([width as usize, height as usize], loaded.pixels.as_raw());
+            // 放大时不插值，像素边界清晰
+            let options = egui::TextureOptions {
+                magnification: egui::TextureFilter::Nearest,
+                minification: egui::TextureFilter::Linear,
+            };
+            ui.ctx().load_texture(self.path.display().to_string(), color_image, options)
+        }).id();
+
+        // 画布占据工具栏和底部信息栏之间的全部空间
+        let info_bar_height = ui.spacing().interact_size.y + ui.spacing().item_spacing.y * 2.0;
+        let canvas_size = egui::vec2(ui.available_width(), (ui.available_height() - info_bar_height).max(100.0));
+        let (rect, response) = ui.allocate_exact_size(canvas_size, egui::Sense::click_and_drag());
+        self.canvas = rect;
+
+        let image_size = egui::vec2(width as f32, height as f32);
+        if self.fit {
+            self.zoom = (rect.width() / image_size.x).min(rect.height() / image_size.y).clamp(MIN_ZOOM, 1.0);
+            self.offset = egui::Vec2::ZERO;
+        }
+
+        // 拖动平移，滚轮（或 Ctrl+滚轮、触控板捏合）以鼠标位置为中心缩放，双击在适应窗口和 1:1 之间切换
+        if response.dragged() {
+            self.offset += response.drag_delta();
+            self.fit = false;
+        }
+        if let Some(pointer) = response.hover_pos() {
+            let factor = ui.input(|i| i.zoom_delta() * (i.scroll_delta.y / 200.0).exp());
+            if factor != 1.0 {
+                self.zoom_around(pointer, factor);
+            }
+        }
+        if response.double_clicked() {
+            if self.fit {
+                self.zoom_actual_size();
+            } else {
+                self.zoom_to_fit();
+            }
+        }
+
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+        let image_rect = egui::Rect::from_center_size(rect.center() + self.offset, image_size * self.zoom);
+        let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+        painter.image(texture_id, image_rect, uv, egui::Color32::WHITE);
+
+        if self.show_grid && self.zoom >= GRID_MIN_ZOOM {
+            let visible = image_rect.intersect(rect);
+            let stroke = egui::Stroke::new(1.0, egui::Color32::from_gray(128).gamma_multiply(0.5));
+            let columns = ((visible.left() - image_rect.left()) / self.zoom).floor() as u32
+                ..=((visible.right() - image_rect.left()) / self.zoom).ceil() as u32;
+            for x in columns {
+                painter.vline(image_rect.left() + x as f32 * self.zoom, visible.y_range(), stroke);
+            }
+            let rows = ((visible.top() - image_rect.top()) / self.zoom).floor() as u32
+                ..=((visible.bottom() - image_rect.top()) / self.zoom).ceil() as u32;
+            for y in rows {
+                painter.hline(visible.x_range(), image_rect.top() + y as f32 * self.zoom, stroke);
+            }
+        }
+
+        // 取色：鼠标下的像素，单击复制颜色值
+        let pixels = match &self.image {
+            Ok(loaded) => &loaded.pixels,
+            Err(_) => return,
+        };
+        self.hovered_pixel = response.hover_pos().and_then(|pos| {
+            let point = (pos - image_rect.min) / self.zoom;
+            let (x, y) = (point.x.floor(), point.y.floor());
+            (x >= 0.0 && y >= 0.0 && x < image_size.x && y < image_size.y)
+                .then(|| (x as u32, y as u32, pixels.get_pixel(x as u32, y as u32).0))
+        });
+        if let Some((x, y, color)) = self.hovered_pixel {
+            if self.zoom >= GRID_MIN_ZOOM {
+                let pixel_rect = egui::Rect::from_min_size(
+                    image_rect.min + egui::vec2(x as f32, y as f32) * self.zoom,
+                    egui::Vec2::splat(self.zoom),
+                );
+                painter.rect_stroke(pixel_rect, 0.0, egui::Stroke::new(2.0, ui.visuals().selection.stroke.color));
+            }
+            if response.clicked() {
+                let hex = color_hex(color);
+                ui.ctx().output_mut(|o| o.copied_text = hex.clone());
+                self.copied_color = Some(hex);
+            }
+        }
+
+        self.render_info_bar(ui);
+    }
+
+    fn render_toolbar(&mut self, ui: &mut egui::Ui, commands: &mut Vec<Command>) {
+        ui.horizontal(|ui| {
+            let name = self.path.file_name().map_or_else(|| self.path.display().to_string(), |name| name.to_string_lossy().into_owned());
+            ui.label(egui::RichText::new(name).strong()).on_hover_text(self.path.display().to_string());
+            if let Ok(loaded) = &self.image {
+                let (width, height) = loaded.pixels.dimensions();
+                ui.label(format!("{} × {} 像素", width, height));
+                ui.weak(format!("{} · {} · {}", loaded.format, loaded.color_type, directory_listing::format_size(loaded.file_size)));
+            }
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.button("✖ 关闭").clicked() {
+                    commands.push(Command::CloseImage);
+                }
+                if ui.selectable_label(self.show_grid, "▦ 像素网格").on_hover_text(format!("放大到 {:.0}% 以上时显示", GRID_MIN_ZOOM * 100.0)).clicked() {
+                    commands.push(Command::TogglePixelGrid);
+                }
+                if ui.button("1:1").clicked() {
+                    commands.push(Command::ZoomActualSize);
+                }
+                if ui.selectable_label(self.fit, "适应窗口").clicked() {
+                    commands.push(Command::ZoomToFit);
+                }
+                if ui.button("➕").clicked() {
+                    commands.push(Command::ZoomIn);
+                }
+                ui.label(format!("{:.0}%", self.zoom * 100.0));
+                if ui.button("➖").clicked() {
+                    commands.push(Command::ZoomOut);
+                }
+            });
+        });
+    }
+
+    fn render_info_bar(&self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            match self.hovered_pixel {
+                Some((x, y, color)) => {
+                    let [r, g, b, a] = color;
+                    let (swatch, _) = ui.allocate_exact_size(egui::Vec2::splat(14.0), egui::Sense::hover());
+                    ui.painter().rect_filled(swatch, 2.0, egui::Color32::from_rgba_unmultiplied(r, g, b, a));
+                    ui.painter().rect_stroke(swatch, 2.0, ui.visuals().widgets.noninteractive.bg_stroke);
+                    ui.label(egui::RichText::new(format!("({}, {})  RGBA({}, {}, {}, {})  {}", x, y, r, g, b, a, color_hex(color))).monospace());
+                }
+                None => {
+                    ui.weak("拖动平移，滚轮缩放，双击切换适应窗口和 1:1；单击像素复制颜色值");
+                }
+            }
+            if let Some(hex) = &self.copied_color {
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.weak(format!("已复制 {}", hex));
+                });
+            }
+        });
+    }
+}
+
+/// 颜色的十六进制表示（不透明时省略 alpha）
+fn color_hex([r, g, b, a]: [u8; 4]) -> String {
+    if a == 255 {
+        format!("#{:02X}{:02X}{:02X}", r, g, b)
+    } else {
+        format!("#{:02X}{:02X}{:02X}{:02X}", r, g, b, a)
+    }
+}
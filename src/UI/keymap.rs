@@ -0,0 +1,376 @@
+use eframe::egui;
+use egui::{Event, Key, KeyboardShortcut, Modifiers};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use crate::config;
+use crate::ui::commands::Command;
+
+/// 快捷键配置文件名（位于用户配置目录）
+const KEYMAP_FILE: &str = "keybindings.conf";
+
+/// 多键序列中两次按键的最长间隔（秒）
+const SEQUENCE_TIMEOUT: f64 = 2.0;
+
+/// 按键序列，例如 Ctrl+K Ctrl+C
+pub type KeySequence = Vec<KeyboardShortcut>;
+
+/// 默认快捷键
+const DEFAULT_BINDINGS: &[(Command, &str)] = &[
+    (Command::ShowPalette, "Ctrl+Shift+P"),
+    (Command::OpenFile, "Ctrl+O"),
+    (Command::QuickOpen, "Ctrl+P"),
+    (Command::OpenFolder, "Ctrl+K Ctrl+O"),
+    (Command::SaveFile, "Ctrl+S"),
+    (Command::SaveFileAs, "Ctrl+Shift+S"),
+    (Command::ToggleEditMode, "Ctrl+E"),
+    (Command::ChangeTheme, "Ctrl+K Ctrl+T"),
+    (Command::ToggleFileBrowser, "Ctrl+B"),
+    (Command::OpenKeybindings, "Ctrl+K Ctrl+S"),
+    (Command::GoToParentDirectory, "Alt+Up"),
+    (Command::NavigateBack, "Alt+Left"),
+    (Command::NavigateForward, "Alt+Right"),
+    (Command::EditLocation, "Ctrl+L"),
+    (Command::RefreshDirectory, "F5"),
+    (Command::ToggleBookmark, "Ctrl+F2"),
+    (Command::NextBookmark, "F2"),
+    (Command::PreviousBookmark, "Shift+F2"),
+    (Command::ToggleBlame, "Ctrl+K Ctrl+B"),
+    (Command::CompareWithSaved, "Ctrl+K Ctrl+D"),
+    (Command::NextChange, "F7"),
+    (Command::PreviousChange, "Shift+F7"),
+    (Command::ToggleMarkdownPreview, "Ctrl+K V"),
+    (Command::MarkdownPreviewOnly, "Ctrl+Shift+V"),
+    (Command::HexGoToOffset, "Ctrl+G"),
+    (Command::ToggleStructuredView, "Ctrl+K J"),
+    (Command::FormatDocument, "Alt+Shift+F"),
+    (Command::ToggleTableView, "Ctrl+K T"),
+    (Command::ToggleFold, "Ctrl+K Ctrl+L"),
+    (Command::FoldAll, "Ctrl+K Ctrl+0"),
+    (Command::UnfoldAll, "Ctrl+K Ctrl+J"),
+    (Command::EditorFind, "Ctrl+F"),
+    (Command::EditorFindNext, "F3"),
+    (Command::EditorFindPrevious, "Shift+F3"),
+    (Command::ToggleSoftWrap, "Alt+Z"),
+    (Command::JumpToMatchingBracket, "Ctrl+M"),
+];
+
+/// 可绑定的按键（egui 0.24 没有按名称查找按键的接口）
+const ALL_KEYS: &[Key] = &[
+    Key::ArrowDown, Key::ArrowLeft, Key::ArrowRight, Key::ArrowUp,
+    Key::Escape, Key::Tab, Key::Backspace, Key::Enter, Key::Space,
+    Key::Insert, Key::Delete, Key::Home, Key::End, Key::PageUp, Key::PageDown,
+    Key::Minus, Key::PlusEquals,
+    Key::Num0, Key::Num1, Key::Num2, Key::Num3, Key::Num4,
+    Key::Num5, Key::Num6, Key::Num7, Key::Num8, Key::Num9,
+    Key::A, Key::B, Key::C, Key::D, Key::E, Key::F, Key::G, Key::H, Key::I,
+    Key::J, Key::K, Key::L, Key::M, Key::N, Key::O, Key::P, Key::Q, Key::R,
+    Key::S, Key::T, Key::U, Key::V, Key::W, Key::X, Key::Y, Key::Z,
+    Key::F1, Key::F2, Key::F3, Key::F4, Key::F5, Key::F6, Key::F7, Key::F8, Key::F9, Key::F10,
+    Key::F11, Key::F12, Key::F13, Key::F14, Key::F15, Key::F16, Key::F17, Key::F18, Key::F19, Key::F20,
+];
+
+/// 快捷键映射：按键序列 -> 命令
+pub struct Keymap {
+    bindings: HashMap<Command, KeySequence>,
+    pending: KeySequence, // 多键序列中已按下的部分
+    pending_since: f64,
+    pub load_errors: Vec<String>, // 读取配置文件时的错误
+}
+
+impl Keymap {
+    /// 使用默认快捷键创建
+    pub fn with_defaults() -> Self {
+        Self {
+            bindings: default_bindings(),
+            pending: Vec::new(),
+            pending_since: 0.0,
+            load_errors: Vec::new(),
+        }
+    }
+
+    /// 加载默认快捷键，并用用户配置文件覆盖
+    pub fn load() -> Self {
+        let mut keymap = Self::with_defaults();
+        match std::fs::read_to_string(config::config_file(KEYMAP_FILE)) {
+            Ok(content) => keymap.apply_config(&content),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => keymap.load_errors.push(format!("读取快捷键配置失败: {}", e)),
+        }
+        keymap
+    }
+
+    /// 解析配置内容，每行格式为 `命令标识 = 快捷键`，值为空表示取消绑定
+    fn apply_config(&mut self, content: &str) {
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let line_num = index + 1;
+            let Some((id, value)) = line.split_once('=') else {
+                self.load_errors.push(format!("第{}行: 缺少 '='", line_num));
+                continue;
+            };
+            let Some(command) = Command::from_id(id.trim()) else {
+                self.load_errors.push(format!("第{}行: 未知命令 {}", line_num, id.trim()));
+                continue;
+            };
+
+            let value = value.trim();
+            if value.is_empty() {
+                self.bindings.remove(&command);
+                continue;
+            }
+            match parse_sequence(value) {
+                Ok(sequence) => {
+                    self.bindings.insert(command, sequence);
+                }
+                Err(e) => self.load_errors.push(format!("第{}行: {}", line_num, e)),
+            }
+        }
+    }
+
+    /// 保存与默认值不同的快捷键到用户配置文件
+    pub fn save(&self) -> std::io::Result<()> {
+        let defaults = default_bindings();
+        let mut content = String::from("# Code Notebook 快捷键配置\n# 格式：命令标识 = 快捷键（多键序列用空格分隔，留空表示不绑定）\n");
+        for command in Command::ALL {
+            if self.bindings.get(command) != defaults.get(command) {
+                let value = self.bindings.get(command).map(|s| format_sequence(s)).unwrap_or_default();
+                let _ = writeln!(content, "{} = {}", command.id(), value);
+            }
+        }
+        config::write_config_file(KEYMAP_FILE, &content)
+    }
+
+    /// 命令当前绑定的按键序列
+    pub fn binding(&self, command: Command) -> Option<&KeySequence> {
+        self.bindings.get(&command)
+    }
+
+    /// 命令当前绑定的快捷键文本（未绑定时为空）
+    pub fn binding_text(&self, command: Command) -> String {
+        self.binding(command).map(|s| format_sequence(s)).unwrap_or_default()
+    }
+
+    /// 设置或取消命令的快捷键
+    pub fn set_binding(&mut self, command: Command, sequence: Option<KeySequence>) {
+        match sequence {
+            Some(sequence) if !sequence.is_empty() => {
+                self.bindings.insert(command, sequence);
+            }
+            _ => {
+                self.bindings.remove(&command);
+            }
+        }
+    }
+
+    /// 命令是否使用默认快捷键
+    pub fn is_default(&self, command: Command) -> bool {
+        self.bindings.get(&command) == default_bindings().get(&command)
+    }
+
+    /// 恢复单个命令的默认快捷键
+    pub fn reset(&mut self, command: Command) {
+        let sequence = default_bindings().remove(&command);
+        self.set_binding(command, sequence);
+    }
+
+    /// 恢复全部默认快捷键
+    pub fn reset_all(&mut self) {
+        self.bindings = default_bindings();
+    }
+
+    /// 检测冲突：两个命令的序列相同，或一个是另一个的前缀（较长的序列将无法触发）
+    pub fn conflicts(&self) -> Vec<(Command, Command)> {
+        let mut conflicts = Vec::new();
+        for (i, a) in Command::ALL.iter().enumerate() {
+            let Some(seq_a) = self.bindings.get(a) else { continue };
+            for b in &Command::ALL[i + 1..] {
+                let Some(seq_b) = self.bindings.get(b) else { continue };
+                if seq_a.starts_with(seq_b) || seq_b.starts_with(seq_a) {
+                    conflicts.push((*a, *b));
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// conflicts() 的结果中与指定命令冲突的其他命令
+    pub fn conflicts_of(conflicts: &[(Command, Command)], command: Command) -> Vec<Command> {
+        conflicts
+            .iter()
+            .filter_map(|&(a, b)| {
+                if a == command {
+                    Some(b)
+                } else if b == command {
+                    Some(a)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// 多键序列等待中时的提示文本
+    pub fn pending_text(&self) -> Option<String> {
+        if self.pending.is_empty() {
+            None
+        } else {
+            Some(format_sequence(&self.pending))
+        }
+    }
+
+    /// 处理本帧的按键事件，消费匹配的按键并返回触发的命令
+    pub fn process_input(&mut self, ctx: &egui::Context) -> Vec<Command> {
+        let now = ctx.input(|i| i.time);
+        if !self.pending.is_empty() && now - self.pending_since > SEQUENCE_TIMEOUT {
+            self.pending.clear();
+        }
+
+        let mut triggered = Vec::new();
+        let mut events = ctx.input_mut(|i| std::mem::take(&mut i.events));
+        // 被消费的按键随后的文本输入也要去掉，否则 Ctrl+K V 这类序列的第二个键会输入到文本框
+        let mut drop_text = false;
+        events.retain(|event| match event {
+            Event::Key { key, pressed: true, modifiers, .. } => {
+                match self.step(*key, *modifiers, now) {
+                    Some(command) => {
+                        triggered.extend(command);
+                        drop_text = true;
+                        false
+                    }
+                    None => {
+                        drop_text = false;
+                        true
+                    }
+                }
+            }
+            Event::Text(_) => !std::mem::take(&mut drop_text),
+            _ => true,
+        });
+        ctx.input_mut(|i| i.events = events);
+
+        // 等待序列的下一个按键时，超时后需要刷新界面清除提示
+        if !self.pending.is_empty() {
+            ctx.request_repaint_after(std::time::Duration::from_secs_f64(SEQUENCE_TIMEOUT));
+        }
+        triggered
+    }
+
+    /// 推进序列匹配：返回 None 表示按键未被使用；Some(None) 表示已消费但序列尚未完成
+    fn step(&mut self, key: Key, modifiers: Modifiers, now: f64) -> Option<Option<Command>> {
+        let depth = self.pending.len();
+        let mut candidates: Vec<(Command, &KeySequence)> = self
+            .bindings
+            .iter()
+            .filter(|(_, sequence)| {
+                sequence.len() > depth
+                    && sequence[..depth] == self.pending[..]
+                    && sequence[depth].key == key
+                    && modifiers.matches(sequence[depth].modifiers)
+            })
+            .map(|(command, sequence)| (*command, sequence))
+            .collect();
+
+        if candidates.is_empty() {
+            if depth > 0 {
+                // 序列中断，把本次按键当作新序列的开始重新匹配
+                self.pending.clear();
+                return self.step(key, modifiers, now);
+            }
+            return None;
+        }
+
+        // 完整匹配优先（与前缀冲突时较短的序列获胜）
+        candidates.sort_by_key(|(_, sequence)| sequence.len());
+        let (command, sequence) = candidates[0];
+        if sequence.len() == depth + 1 {
+            self.pending.clear();
+            Some(Some(command))
+        } else {
+            let next = sequence[depth];
+            self.pending.push(next);
+            self.pending_since = now;
+            Some(None)
+        }
+    }
+}
+
+fn default_bindings() -> HashMap<Command, KeySequence> {
+    DEFAULT_BINDINGS
+        .iter()
+        .filter_map(|(command, text)| Some((*command, parse_sequence(text).ok()?)))
+        .collect()
+}
+
+/// 将按键事件转换为快捷键（Ctrl 与 Command 统一处理）
+pub fn shortcut_from_event(key: Key, modifiers: Modifiers) -> KeyboardShortcut {
+    let mut normalized = Modifiers::NONE;
+    if modifiers.ctrl || modifiers.command {
+        normalized = normalized | Modifiers::COMMAND;
+    }
+    if modifiers.alt {
+        normalized = normalized | Modifiers::ALT;
+    }
+    if modifiers.shift {
+        normalized = normalized | Modifiers::SHIFT;
+    }
+    KeyboardShortcut::new(normalized, key)
+}
+
+/// 解析按键序列，如 "Ctrl+K Ctrl+C"
+pub fn parse_sequence(text: &str) -> Result<KeySequence, String> {
+    let sequence: KeySequence = text
+        .split_whitespace()
+        .map(parse_shortcut)
+        .collect::<Result<_, _>>()?;
+    if sequence.is_empty() {
+        return Err("快捷键为空".to_string());
+    }
+    Ok(sequence)
+}
+
+/// 解析单个组合键，如 "Ctrl+Shift+P"
+fn parse_shortcut(text: &str) -> Result<KeyboardShortcut, String> {
+    let mut parts: Vec<&str> = text.split('+').map(str::trim).collect();
+    let key_name = parts.pop().filter(|name| !name.is_empty()).ok_or_else(|| format!("无效的快捷键: {}", text))?;
+
+    let mut modifiers = Modifiers::NONE;
+    for part in parts {
+        modifiers = modifiers | match part.to_ascii_lowercase().as_str() {
+            "ctrl" | "cmd" | "command" => Modifiers::COMMAND,
+            "alt" | "option" => Modifiers::ALT,
+            "shift" => Modifiers::SHIFT,
+            _ => return Err(format!("未知的修饰键: {}", part)),
+        };
+    }
+
+    let key = ALL_KEYS
+        .iter()
+        .copied()
+        .find(|key| key.name().eq_ignore_ascii_case(key_name) || key.symbol_or_name() == key_name)
+        .ok_or_else(|| format!("未知的按键: {}", key_name))?;
+    Ok(KeyboardShortcut::new(modifiers, key))
+}
+
+/// 格式化按键序列（与配置文件格式一致）
+pub fn format_sequence(sequence: &[KeyboardShortcut]) -> String {
+    sequence.iter().map(format_shortcut).collect::<Vec<_>>().join(" ")
+}
+
+fn format_shortcut(shortcut: &KeyboardShortcut) -> String {
+    let mut text = String::new();
+    if shortcut.modifiers.command || shortcut.modifiers.ctrl {
+        text.push_str("Ctrl+");
+    }
+    if shortcut.modifiers.alt {
+        text.push_str("Alt+");
+    }
+    if shortcut.modifiers.shift {
+        text.push_str("Shift+");
+    }
+    text.push_str(shortcut.key.name());
+    text
+}
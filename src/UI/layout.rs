@@ -1,7 +1,40 @@
 use eframe::egui;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
 use std::path::PathBuf;
-use crate::ui::components::{FileBrowser, CodeEditor, StatusBar, SettingsPanel};
-use crate::AppState;
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::time::{Duration, Instant};
+use crate::ui::command_palette::{CommandPalette, PaletteAction, PathPurpose};
+use crate::ui::commands::Command;
+use crate::ui::commit_view::CommitView;
+use crate::ui::diff_view::DiffView;
+use crate::ui::hex_view::HexView;
+use crate::ui::image_view::ImageView;
+use crate::ui::components::{FileBrowser, FileEvent, CodeEditor, StatusBar, SettingsPanel, SoftWrap};
+use crate::ui::keymap::Keymap;
+use crate::ui::markdown_preview::MarkdownPreview;
+use crate::ui::structured_view::StructuredView;
+use crate::ui::table_view::TableView;
+use crate::ui::styles;
+use crate::bookmarks::Bookmarks;
+use crate::fs_watch::{FsChange, FsWatcher};
+use crate::git::{self, Blame, GitRepository, LineChange};
+use crate::structured::{self, Format};
+use crate::indentation::Indent;
+use crate::line_ending::LineEnding;
+use crate::{diff, editorconfig, file_index, file_types, text_encoding, AppState};
+
+/// 文件变化后延迟刷新 Git 状态的时间（合并连续的文件系统事件）
+const GIT_REFRESH_DELAY: Duration = Duration::from_millis(300);
+
+/// Markdown 预览的显示方式
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum MarkdownMode {
+    Hidden,      // 只显示源文件
+    SideBySide,  // 源文件和预览并排
+    PreviewOnly, // 只显示预览
+}
 
 /// 主布局管理器
 pub struct MainLayout {
@@ -9,97 +42,1042 @@ pub struct MainLayout {
     pub code_editor: CodeEditor,
     pub status_bar: StatusBar,
     pub settings_panel: SettingsPanel,
+    pub command_palette: CommandPalette,
+    pub keymap: Keymap,
+    pub bookmarks: Bookmarks,
+    bookmark_lines: Option<(u64, Vec<u64>)>, // 有书签的文件上次移动书签时的内容哈希和每行的哈希
+    fs_watcher: Option<FsWatcher>,  // 文件系统监视器（首帧创建，需要 egui 上下文）
+    external_change_prompt: bool,   // 有未保存修改时文件被外部修改，等待用户选择
+    git: Option<GitRepository>,     // 工作区所在的 Git 仓库
+    git_checked_root: Option<PathBuf>, // 上次检测仓库时的工作区目录
+    git_refresh_at: Option<Instant>, // 工作区文件变化后等待刷新 Git 状态的时间
+    commit_view: Option<CommitView>, // 打开的提交详情（与比较视图一样显示在代码区）
+    blame_job: Option<(u64, Receiver<Result<Blame, String>>)>, // 后台计算中的 blame 及其输入哈希
+    blame_key: u64,                 // 最近一次开始计算 blame 时的输入哈希
+    line_changes: HashMap<usize, LineChange>, // 当前文件与 HEAD 比较的行变化
+    line_changes_job: Option<(u64, Receiver<HashMap<usize, LineChange>>)>, // 后台比较中的行变化及其输入哈希
+    line_changes_key: u64,          // 最近一次开始比较行变化时的输入哈希
+    diff_view: Option<DiffView>,    // 打开的比较视图（显示在代码区）
+    image_view: Option<ImageView>,  // 打开的图像（显示在代码区）
+    hex_view: Option<HexView>,      // 以十六进制打开的二进制文件（显示在代码区）
+    markdown_preview: MarkdownPreview,
+    markdown_mode: MarkdownMode,
+    is_markdown: bool,              // 当前文件是否为 Markdown
+    markdown_scroll_lines: (f32, f32), // 上一帧源文件和预览顶部对应的行（同步滚动时比较）
+    structured_view: StructuredView,
+    structured_format: Option<Format>, // 当前文件为 JSON/TOML/YAML 时的格式
+    show_structured: bool,          // 以树形视图代替源文件显示
+    table_view: TableView,
+    table_delimiter: Option<u8>,    // 当前文件为 CSV/TSV 时扩展名对应的分隔符
+    show_table: bool,               // 以表格代替源文件显示
 }
 
 impl MainLayout {
     pub fn new(app_state: &AppState) -> Self {
         let mut file_browser = FileBrowser::new(app_state.current_directory.clone());
-        file_browser.directory_items = app_state.directory_items.clone();
+        file_browser.load_directory_content();
 
         Self {
             file_browser,
             code_editor: CodeEditor::new(app_state.code.clone()),
             status_bar: StatusBar::new(app_state.file_path.clone(), app_state.status.clone()),
             settings_panel: SettingsPanel::new(),
+            command_palette: CommandPalette::new(),
+            keymap: Keymap::load(),
+            bookmarks: Bookmarks::load(),
+            bookmark_lines: None,
+            fs_watcher: None,
+            external_change_prompt: false,
+            git: None,
+            git_checked_root: None,
+            git_refresh_at: None,
+            commit_view: None,
+            blame_job: None,
+            blame_key: 0,
+            line_changes: HashMap::new(),
+            line_changes_job: None,
+            line_changes_key: 0,
+            diff_view: None,
+            image_view: None,
+            hex_view: None,
+            markdown_preview: MarkdownPreview::new(),
+            markdown_mode: MarkdownMode::Hidden,
+            is_markdown: false,
+            markdown_scroll_lines: (0.0, 0.0),
+            structured_view: StructuredView::new(),
+            structured_format: None,
+            show_structured: false,
+            table_view: TableView::new(),
+            table_delimiter: None,
+            show_table: true,
         }
     }
 
     /// 渲染主布局
     pub fn render(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame, app_state: &mut AppState) -> Option<PathBuf> {
         // 只在需要时更新组件状态
-        if self.file_browser.current_directory != app_state.current_directory {
-            self.file_browser.current_directory = app_state.current_directory.clone();
-        }
-        if self.file_browser.directory_items.len() != app_state.directory_items.len() {
-            self.file_browser.directory_items = app_state.directory_items.clone();
-        }
-        if self.code_editor.code != app_state.code {
-            self.code_editor.code = app_state.code.clone();
-        }
-        if self.status_bar.file_path != app_state.file_path {
-            self.status_bar.file_path = app_state.file_path.clone();
-            self.status_bar.status = app_state.status.clone();
+        self.sync_from_state(app_state);
+
+        // 命令行请求的文件比较
+        if let Some((left, right)) = app_state.diff_request.take() {
+            self.compare_files(left, right, app_state);
         }
 
+        // 处理全局快捷键和命令面板
+        let mut file_to_load = self.handle_commands(ctx, app_state);
+
+        // 处理磁盘上的变化
+        self.handle_file_system_changes(ctx, app_state);
+        self.render_external_change_prompt(ctx, app_state);
+
+        // 命令和文件变化可能修改了应用状态，渲染前再同步一次
+        self.sync_from_state(app_state);
+        self.update_git_repository();
+        self.update_blame(ctx, app_state);
+        self.update_line_changes(ctx, app_state);
+        self.update_bookmark_lines(app_state);
+
         // 更新窗口标题
-        self.update_window_title(ctx, &app_state.file_path);
+        self.update_window_title(ctx, &app_state.file_path, app_state.is_modified);
 
-        // 渲染主面板
-        egui::CentralPanel::default().show(ctx, |ui| {
-            // 获取总可用高度，不受状态栏影响
-            let total_available_height = ui.available_height();
+        // 组件中按钮触发的命令，渲染结束后统一执行
+        let mut commands: Vec<Command> = Vec::new();
 
-            // 状态栏暂时不渲染以节省空间
-            // if app_state.file_path.is_some() {
-            //     self.status_bar.render(ui);
-            // }
+        // 状态栏固定在窗口底部
+        if app_state.show_status_bar {
+            egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+                self.status_bar.render(ui, &mut commands);
+            });
+        }
 
+        // 渲染主面板
+        let browser_file = egui::CentralPanel::default().show(ctx, |ui| {
             // 计算剩余可用高度给内容区域
             let remaining_height = ui.available_height();
+            // 目录面板需要修改收藏，编辑器使用书签行的副本
+            let bookmarked_lines = self.bookmarks.lines_of(app_state.file_path.as_deref()).clone();
+
+            // 与 HEAD 比较的行变化（在后台计算）
+            let line_changes = &self.line_changes;
+
+            // 图像、十六进制和比较视图优先显示，其次是树形视图、表格和 Markdown 预览
+            let viewer_open = self.diff_view.is_some()
+                || self.commit_view.is_some()
+                || self.image_view.is_some()
+                || self.hex_view.is_some();
+            let markdown_mode = match !viewer_open && self.is_markdown {
+                true => self.markdown_mode,
+                false => MarkdownMode::Hidden,
+            };
+            if markdown_mode != MarkdownMode::Hidden {
+                self.markdown_preview.update(&self.code_editor.code, app_state.file_path.as_deref());
+            }
+            // JSON/TOML/YAML 的解析结果用于树形视图和编辑器标记栏中的错误位置
+            match self.structured_format {
+                Some(format) => {
+                    self.structured_view.update(&self.code_editor.code, format);
+                    self.code_editor.parse_error = self.structured_view.error().cloned();
+                }
+                None => self.code_editor.parse_error = None,
+            }
+            let show_structured = !viewer_open && self.show_structured && self.structured_format.is_some();
+
+            // CSV/TSV 在后台建立索引，原始文本按识别出的分隔符逐列着色
+            match self.table_delimiter {
+                Some(delimiter) => {
+                    self.table_view.update(ui.ctx(), &self.code_editor.code, app_state.file_path.as_deref(), delimiter);
+                    self.code_editor.set_column_delimiter(Some(self.table_view.dialect().delimiter));
+                }
+                None => self.code_editor.set_column_delimiter(None),
+            }
+            let show_table = !viewer_open && self.show_table && self.table_delimiter.is_some();
+            let mut render_code_area = |ui: &mut egui::Ui, commands: &mut Vec<Command>| {
+                if let Some(image_view) = &mut self.image_view {
+                    image_view.render(ui, remaining_height, commands);
+                    return;
+                }
+                if let Some(hex_view) = &mut self.hex_view {
+                    hex_view.render(ui, remaining_height, commands);
+                    return;
+                }
+                if let Some(diff_view) = &mut self.diff_view {
+                    diff_view.render(ui, remaining_height, commands);
+                    return;
+                }
+                if let Some(commit_view) = &mut self.commit_view {
+                    commit_view.render(ui, remaining_height, commands);
+                    return;
+                }
+                if show_structured {
+                    self.structured_view.render(ui, remaining_height, commands);
+                    return;
+                }
+                if show_table {
+                    self.table_view.render(ui, remaining_height, commands);
+                    return;
+                }
+                match markdown_mode {
+                    MarkdownMode::Hidden => {
+                        self.code_editor.render(ui, remaining_height, &bookmarked_lines, line_changes, commands);
+                    }
+                    MarkdownMode::PreviewOnly => self.markdown_preview.render(ui, remaining_height),
+                    MarkdownMode::SideBySide => {
+                        let hover_pos = ui.input(|i| i.pointer.hover_pos());
+                        ui.columns(2, |columns| {
+                            self.code_editor.render(&mut columns[0], remaining_height, &bookmarked_lines, line_changes, commands);
+                            self.markdown_preview.render(&mut columns[1], remaining_height);
+
+                            // 鼠标所在的一侧滚动时带动另一侧
+                            let editor_line = self.code_editor.top_line();
+                            let preview_line = self.markdown_preview.top_source_line();
+                            let (last_editor_line, last_preview_line) = self.markdown_scroll_lines;
+                            let hovered = |column: &egui::Ui| hover_pos.is_some_and(|pos| column.min_rect().contains(pos));
+                            if hovered(&columns[0]) && (editor_line - last_editor_line).abs() > 0.01 {
+                                self.markdown_preview.scroll_to_source_line(editor_line);
+                            } else if hovered(&columns[1]) && (preview_line - last_preview_line).abs() > 0.01 {
+                                self.code_editor.scroll_to_line(preview_line);
+                            }
+                            self.markdown_scroll_lines = (editor_line, preview_line);
+                        });
+                    }
+                }
+            };
+
+            if !app_state.show_file_browser {
+                // 目录面板隐藏时代码区占满宽度
+                render_code_area(ui, &mut commands);
+                return None;
+            }
 
             // 创建水平布局：代码显示区和目录面板
-            let total_width = ui.available_width();
             ui.horizontal(|ui| {
                 // 左侧代码显示区域 - 占75%宽度
                 ui.vertical(|ui| {
                     ui.set_width(ui.available_width() * 0.75);
                     ui.set_min_height(remaining_height);
 
-                    self.code_editor.render(ui, remaining_height);
+                    render_code_area(ui, &mut commands);
                 });
 
                 // 右侧目录面板 - 占25%宽度
                 ui.separator();
 
-                let file_to_load = ui.vertical(|ui| {
+                ui.vertical(|ui| {
                     ui.set_width(ui.available_width());
                     ui.set_min_height(remaining_height);
 
                     if app_state.show_settings {
                         // 显示设置面板 - 不返回文件路径
-                        self.settings_panel.render(ui, remaining_height, &mut app_state.show_settings);
+                        self.settings_panel.render(ui, remaining_height, &mut self.keymap, &mut commands);
                         None
                     } else {
                         // 显示文件浏览器 - 可能返回文件路径
-                        self.file_browser.render(ui, &app_state.file_path, remaining_height, &mut app_state.show_settings)
+                        self.file_browser.render(
+                            ui,
+                            &app_state.file_path,
+                            remaining_height,
+                            &mut self.bookmarks,
+                            self.git.as_ref(),
+                            &mut commands,
+                        )
                     }
-                }).inner;
+                }).inner
             }).inner
         }).inner;
 
-        None // 返回None，因为文件加载逻辑在组件内部处理
+        for command in commands {
+            self.execute_command(command, app_state);
+        }
+
+        // 在表格中双击行号后回到原始文本的对应行
+        if let Some(line) = self.table_view.take_jump_line() {
+            self.code_editor.go_to_line(line);
+        }
+
+        // blame 列中点击的提交在代码区中只读显示
+        if let Some(id) = self.code_editor.take_clicked_commit() {
+            self.open_commit(&id, app_state);
+        }
+
+        // 文件浏览器中的文件操作结果
+        for event in self.file_browser.take_events() {
+            if let Some(path) = self.apply_file_event(event, app_state) {
+                file_to_load = Some(path);
+            }
+        }
+
+        if file_to_load.is_none() {
+            file_to_load = browser_file;
+        }
+        file_to_load
+    }
+
+    /// 将应用状态同步到各组件
+    fn sync_from_state(&mut self, app_state: &AppState) {
+        if self.file_browser.current_directory != app_state.current_directory {
+            self.file_browser.set_root(app_state.current_directory.clone());
+        }
+        if self.code_editor.code != app_state.code {
+            self.code_editor.set_code(app_state.code.clone());
+        }
+        if self.status_bar.file_path != app_state.file_path {
+            self.status_bar.file_path = app_state.file_path.clone();
+            self.code_editor.current_line = 0;
+            self.code_editor.blame = None;
+            self.line_changes.clear();
+            self.bookmark_lines = None;
+
+            // 文件类型决定高亮语言和状态栏显示
+            let file_type = app_state.file_path.as_deref().map(|path| file_types::registry().for_path(path));
+            self.code_editor.set_language(file_type.and_then(|t| t.language.clone()));
+            self.code_editor.reset_tab_width(app_state.file_path.as_deref().and_then(editorconfig::tab_width));
+            self.is_markdown = file_type.and_then(|t| t.language.as_deref()) == Some("markdown");
+            self.structured_format = file_type.and_then(|t| t.language.as_deref()).and_then(Format::from_language);
+            self.table_delimiter = match file_type.and_then(|t| t.language.as_deref()) {
+                Some("csv") => Some(b','),
+                Some("tsv") => Some(b'\t'),
+                _ => None,
+            };
+            self.status_bar.file_type = file_type.map(|t| t.name.clone()).unwrap_or_default();
+        }
+        let viewer_path = self.image_view.as_ref().map(ImageView::path).or(self.hex_view.as_ref().map(HexView::path));
+        if viewer_path != app_state.viewer_file.as_ref().map(|(path, _)| path.as_path()) {
+            self.image_view = None;
+            self.hex_view = None;
+            match app_state.viewer_file.clone() {
+                Some((path, file_types::Viewer::Image)) => self.image_view = Some(ImageView::open(path)),
+                Some((path, _)) => self.hex_view = Some(HexView::open(path)),
+                None => {}
+            }
+            if app_state.viewer_file.is_some() {
+                self.diff_view = None;
+                self.commit_view = None;
+            }
+        }
+        if self.status_bar.status != app_state.status {
+            self.status_bar.status = app_state.status.clone();
+        }
+        self.status_bar.encoding_name = app_state.encoding.name();
+        self.status_bar.is_modified = app_state.is_modified;
+        self.status_bar.line_ending = self.code_editor.line_ending();
+        self.status_bar.indentation = self.code_editor.indentation();
+        self.status_bar.tab_width = self.code_editor.tab_width();
+        self.status_bar.position = self.code_editor.cursor_position();
+    }
+
+    /// 工作区目录变化时重新检测 Git 仓库（仍在同一仓库内时保留）
+    fn update_git_repository(&mut self) {
+        let root = &self.file_browser.current_directory;
+        if self.git_checked_root.as_ref() == Some(root) {
+            return;
+        }
+        self.git_checked_root = Some(root.clone());
+
+        if !self.git.as_ref().is_some_and(|git| root.starts_with(git.workdir())) {
+            self.git = GitRepository::discover(root);
+        }
+        self.status_bar.branch = self.git.as_ref().and_then(|git| git.branch()).map(str::to_string);
+    }
+
+    /// 重新读取 Git 状态
+    fn refresh_git(&mut self) {
+        if let Some(git) = &mut self.git {
+            git.refresh();
+            self.status_bar.branch = git.branch().map(str::to_string);
+            self.blame_key = 0;
+            self.line_changes_key = 0;
+        }
+    }
+
+    /// 显示 blame 时在后台计算，内容、文件或仓库状态变化后重新计算
+    fn update_blame(&mut self, ctx: &egui::Context, app_state: &mut AppState) {
+        let (true, Some(git), Some(path)) = (self.code_editor.show_blame, &self.git, &app_state.file_path) else {
+            self.code_editor.blame = None;
+            self.blame_job = None;
+            self.blame_key = 0;
+            return;
+        };
+
+        let mut hasher = DefaultHasher::new();
+        (path, self.code_editor.code_hash()).hash(&mut hasher);
+        let key = hasher.finish();
+
+        if let Some((job_key, receiver)) = &self.blame_job {
+            match receiver.try_recv() {
+                // 计算期间内容已变化的结果直接丢弃
+                Ok(result) if *job_key == key => match result {
+                    Ok(blame) => self.code_editor.blame = Some(blame),
+                    Err(e) => app_state.status = format!("无法计算 blame: {}", e),
+                },
+                Ok(_) | Err(TryRecvError::Disconnected) => {}
+                Err(TryRecvError::Empty) => return,
+            }
+            self.blame_job = None;
+        }
+
+        if self.blame_key != key {
+            self.blame_key = key;
+            let receiver = git::spawn_blame(
+                ctx.clone(),
+                git.workdir().to_path_buf(),
+                path.clone(),
+                self.code_editor.code.clone(),
+                app_state.encoding,
+            );
+            self.blame_job = Some((key, receiver));
+        }
+    }
+
+    /// 在后台将当前内容与 HEAD 比较，内容、文件、编码或仓库状态变化后重新比较
+    fn update_line_changes(&mut self, ctx: &egui::Context, app_state: &AppState) {
+        let (Some(git), Some(path)) = (&mut self.git, &app_state.file_path) else {
+            self.line_changes.clear();
+            self.line_changes_job = None;
+            self.line_changes_key = 0;
+            return;
+        };
+
+        let mut hasher = DefaultHasher::new();
+        (path, self.code_editor.code_hash(), app_state.encoding.name()).hash(&mut hasher);
+        let key = hasher.finish();
+
+        if let Some((job_key, receiver)) = &self.line_changes_job {
+            match receiver.try_recv() {
+                // 比较期间内容已变化的结果直接丢弃
+                Ok(changes) if *job_key == key => self.line_changes = changes,
+                Ok(_) | Err(TryRecvError::Disconnected) => {}
+                Err(TryRecvError::Empty) => return,
+            }
+            self.line_changes_job = None;
+        }
+
+        if self.line_changes_key != key {
+            self.line_changes_key = key;
+            if !git.in_worktree(path) {
+                self.line_changes.clear();
+                return;
+            }
+            let head = git.head_text(path, app_state.encoding);
+            let receiver = git::spawn_line_changes(ctx.clone(), head, self.code_editor.code.clone());
+            self.line_changes_job = Some((key, receiver));
+        }
+    }
+
+    /// 以 old 为左侧、当前内容为右侧打开比较视图
+    fn open_diff(
+        &mut self,
+        left_title: String,
+        right_title: String,
+        old: &str,
+        path: &std::path::Path,
+        app_state: &mut AppState,
+    ) {
+        let file_diff = diff::diff_texts(old, &app_state.code);
+        self.show_diff(left_title, right_title, file_diff, path, app_state);
+    }
+
+    /// 比较两个文件（左侧为旧版本）
+    fn compare_files(&mut self, left: PathBuf, right: PathBuf, app_state: &mut AppState) {
+        let (old, new) = match (text_encoding::read_text_file(&left), text_encoding::read_text_file(&right)) {
+            (Ok(old), Ok(new)) => (old, new),
+            (Err(e), _) | (_, Err(e)) => {
+                app_state.status = format!("无法比较文件: {}", e);
+                return;
+            }
+        };
+        let file_diff = diff::diff_texts(&old, &new);
+        self.show_diff(file_name_of(&left), file_name_of(&right), file_diff, &right, app_state);
+    }
+
+    fn show_diff(
+        &mut self,
+        left_title: String,
+        right_title: String,
+        file_diff: diff::FileDiff,
+        path: &std::path::Path,
+        app_state: &mut AppState,
+    ) {
+        app_state.status = if file_diff.hunks.is_empty() {
+            "两侧内容相同".to_string()
+        } else {
+            format!("共 {} 处差异（F7 / Shift+F7 跳转）", file_diff.hunks.len())
+        };
+        let language = file_types::registry().for_path(path).language.as_deref();
+        self.diff_view = Some(DiffView::new(left_title, right_title, file_diff, language));
+        self.commit_view = None;
+        app_state.viewer_file = None;
+    }
+
+    /// 读取提交详情并在代码区中打开（代替比较视图）
+    fn open_commit(&mut self, id: &str, app_state: &mut AppState) {
+        let Some(git) = &self.git else {
+            return;
+        };
+        match git::commit_details(git.workdir(), id) {
+            Ok(details) => {
+                self.commit_view = Some(CommitView::new(details));
+                self.diff_view = None;
+                app_state.viewer_file = None;
+            }
+            Err(e) => app_state.status = format!("无法读取提交 {}: {}", id, e.message()),
+        }
+    }
+
+    /// 处理快捷键触发的命令和命令面板的选择，返回需要加载的文件
+    fn handle_commands(&mut self, ctx: &egui::Context, app_state: &mut AppState) -> Option<PathBuf> {
+        // 录制快捷键期间按键交给设置面板处理
+        if !self.settings_panel.is_capturing() {
+            for command in self.keymap.process_input(ctx) {
+                self.execute_command(command, app_state);
+            }
+        }
+        self.render_pending_sequence(ctx);
+
+        let action = self.command_palette.render(ctx, &self.keymap)?;
+        match action {
+            PaletteAction::Run(command) => {
+                self.execute_command(command, app_state);
+                None
+            }
+            PaletteAction::OpenFile(path) => Some(path),
+            PaletteAction::OpenFolder(path) => {
+                self.file_browser.set_root(path);
+                None
+            }
+            PaletteAction::SaveFileAs(path) => {
+                app_state.save_file_as(path);
+                None
+            }
+            PaletteAction::SetDarkMode(dark_mode) => {
+                app_state.dark_mode = dark_mode;
+                styles::apply_theme(ctx, dark_mode);
+                None
+            }
+            PaletteAction::SetEncoding(encoding) => {
+                app_state.reload_with_encoding(encoding);
+                None
+            }
+        }
+    }
+
+    /// 执行命令
+    pub fn execute_command(&mut self, command: Command, app_state: &mut AppState) {
+        match command {
+            Command::ShowPalette => {
+                if self.command_palette.open {
+                    self.command_palette.open = false;
+                } else {
+                    self.command_palette.show_commands();
+                }
+            }
+            Command::OpenFile => {
+                self.command_palette.show_path_input(
+                    PathPurpose::Open,
+                    app_state.current_directory.clone(),
+                    String::new(),
+                );
+            }
+            Command::OpenFolder => {
+                let initial = format!("{}/", app_state.current_directory.display());
+                self.command_palette.show_path_input(
+                    PathPurpose::OpenFolder,
+                    app_state.current_directory.clone(),
+                    initial,
+                );
+            }
+            Command::QuickOpen => {
+                let root = file_index::find_project_root(&app_state.current_directory);
+                self.command_palette.show_quick_open(root, app_state.recent_files.clone());
+            }
+            Command::SaveFile => match app_state.file_path.clone() {
+                Some(path) => app_state.save_file_as(path),
+                None => self.execute_command(Command::SaveFileAs, app_state),
+            },
+            Command::SaveFileAs => {
+                let initial = app_state
+                    .file_path
+                    .as_ref()
+                    .and_then(|path| path.file_name())
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                self.command_palette.show_path_input(
+                    PathPurpose::SaveAs,
+                    app_state.current_directory.clone(),
+                    initial,
+                );
+            }
+            Command::ToggleEditMode => self.code_editor.toggle_mode(),
+            Command::ScrollToTop => self.code_editor.scroll_to_top(),
+            Command::ScrollToBottom => self.code_editor.scroll_to_bottom(),
+            Command::ToggleFollowMode => {
+                self.code_editor.follow_mode = !self.code_editor.follow_mode;
+                if self.code_editor.follow_mode {
+                    if !app_state.is_modified {
+                        app_state.append_from_disk();
+                    }
+                    self.code_editor.scroll_to_bottom();
+                }
+            }
+            Command::ChangeTheme => {
+                let mark = |selected: bool| if selected { " (当前)" } else { "" };
+                self.command_palette.show_picker("选择主题", vec![
+                    (format!("深色{}", mark(app_state.dark_mode)), PaletteAction::SetDarkMode(true)),
+                    (format!("浅色{}", mark(!app_state.dark_mode)), PaletteAction::SetDarkMode(false)),
+                ]);
+            }
+            Command::ChangeEncoding => {
+                let items = text_encoding::SUPPORTED_ENCODINGS
+                    .iter()
+                    .map(|encoding| {
+                        let label = if *encoding == app_state.encoding {
+                            format!("{} (当前)", encoding.name())
+                        } else {
+                            encoding.name().to_string()
+                        };
+                        (label, PaletteAction::SetEncoding(encoding))
+                    })
+                    .collect();
+                self.command_palette.show_picker("以编码重新打开", items);
+            }
+            Command::ToggleFileBrowser => app_state.show_file_browser = !app_state.show_file_browser,
+            Command::ToggleStatusBar => app_state.show_status_bar = !app_state.show_status_bar,
+            Command::ToggleSettings => {
+                app_state.show_settings = !app_state.show_settings;
+                if app_state.show_settings {
+                    app_state.show_file_browser = true;
+                }
+            }
+            Command::OpenKeybindings => {
+                app_state.show_settings = true;
+                app_state.show_file_browser = true;
+            }
+            Command::ResetKeybindings => {
+                self.keymap.reset_all();
+                if let Err(e) = self.keymap.save() {
+                    app_state.status = format!("保存快捷键配置失败: {}", e);
+                }
+            }
+            Command::GoToParentDirectory => self.file_browser.go_to_parent(),
+            Command::NavigateBack => self.file_browser.go_back(),
+            Command::NavigateForward => self.file_browser.go_forward(),
+            Command::EditLocation => {
+                app_state.show_file_browser = true;
+                app_state.show_settings = false;
+                self.file_browser.edit_location();
+            }
+            Command::RefreshDirectory => {
+                self.file_browser.refresh();
+                self.refresh_git();
+            }
+            Command::ToggleHiddenFiles => self.file_browser.toggle_hidden_files(),
+            Command::ToggleBookmark => {
+                let Some(path) = &app_state.file_path else {
+                    app_state.status = "请先保存文件再添加书签".to_string();
+                    return;
+                };
+                let line = self.code_editor.current_line;
+                let added = self.bookmarks.toggle_line(path, line);
+                app_state.status = format!("{}第 {} 行的书签", if added { "已添加" } else { "已移除" }, line + 1);
+                self.save_bookmarks(app_state);
+            }
+            Command::NextBookmark | Command::PreviousBookmark => {
+                let Some(path) = &app_state.file_path else {
+                    return;
+                };
+                let current = self.code_editor.current_line;
+                let target = if command == Command::NextBookmark {
+                    self.bookmarks.next_line(path, current)
+                } else {
+                    self.bookmarks.previous_line(path, current)
+                };
+                match target {
+                    Some(line) => self.code_editor.go_to_line(line),
+                    None => app_state.status = "当前文件没有书签".to_string(),
+                }
+            }
+            Command::ToggleBlame => {
+                let visible = !self.code_editor.show_blame;
+                self.code_editor.set_blame_visible(visible);
+                if visible && self.git.is_none() {
+                    app_state.status = "当前目录不在 Git 仓库中".to_string();
+                }
+            }
+            Command::CompareWithSaved => {
+                let Some(path) = app_state.file_path.clone() else {
+                    app_state.status = "当前内容尚未保存到文件".to_string();
+                    return;
+                };
+                match std::fs::read(&path) {
+                    Ok(bytes) => {
+                        let (saved, _) = text_encoding::decode(&bytes, app_state.encoding);
+                        let name = file_name_of(&path);
+                        self.open_diff(format!("{}（磁盘）", name), format!("{}（当前内容）", name), &saved, &path, app_state);
+                    }
+                    Err(e) => app_state.status = format!("读取失败: {}", e),
+                }
+            }
+            Command::CompareWithHead => {
+                let (Some(git), Some(path)) = (&mut self.git, app_state.file_path.clone()) else {
+                    app_state.status = "当前文件不在 Git 仓库中".to_string();
+                    return;
+                };
+                // 不在 HEAD 中的新文件与空内容比较
+                let head = git.head_text(&path, app_state.encoding).unwrap_or_default();
+                let name = file_name_of(&path);
+                self.open_diff(format!("{}（HEAD）", name), format!("{}（工作副本）", name), &head, &path, app_state);
+            }
+            Command::NextChange | Command::PreviousChange => match &mut self.diff_view {
+                Some(diff_view) if command == Command::NextChange => diff_view.next_hunk(),
+                Some(diff_view) => diff_view.previous_hunk(),
+                None => app_state.status = "没有打开的比较视图".to_string(),
+            },
+            Command::ToggleDiffLayout => {
+                if let Some(diff_view) = &mut self.diff_view {
+                    diff_view.toggle_layout();
+                }
+            }
+            Command::CloseDiff => {
+                self.diff_view = None;
+                self.commit_view = None;
+            }
+            Command::ToggleMarkdownPreview | Command::MarkdownPreviewOnly => {
+                if !self.is_markdown {
+                    app_state.status = "当前文件不是 Markdown".to_string();
+                    return;
+                }
+                let mode = match command {
+                    Command::ToggleMarkdownPreview => MarkdownMode::SideBySide,
+                    _ => MarkdownMode::PreviewOnly,
+                };
+                self.markdown_mode = if self.markdown_mode == mode { MarkdownMode::Hidden } else { mode };
+            }
+            Command::ToggleStructuredView => {
+                if self.structured_format.is_none() {
+                    app_state.status = "当前文件不是 JSON、TOML 或 YAML".to_string();
+                    return;
+                }
+                self.show_structured = !self.show_structured;
+                // 回到源文件时跳到解析错误的位置
+                if let (false, Some(error)) = (self.show_structured, &self.code_editor.parse_error) {
+                    self.code_editor.go_to_line(error.line);
+                }
+            }
+            Command::ToggleFold => self.code_editor.toggle_fold(),
+            Command::FoldAll => self.code_editor.fold_all(),
+            Command::UnfoldAll => self.code_editor.unfold_all(),
+            Command::ToggleMinimap => self.code_editor.show_minimap = !self.code_editor.show_minimap,
+            // 查找快捷键在十六进制查看器打开时作用于查看器
+            Command::EditorFind | Command::EditorFindNext | Command::EditorFindPrevious => match (&mut self.hex_view, command) {
+                (Some(hex_view), Command::EditorFind) => hex_view.focus_search_input(),
+                (Some(hex_view), Command::EditorFindNext) => hex_view.find_next(),
+                (Some(hex_view), _) => hex_view.find_previous(),
+                (None, Command::EditorFind) => self.code_editor.focus_find(),
+                (None, Command::EditorFindNext) => self.code_editor.find_next(false),
+                (None, _) => self.code_editor.find_next(true),
+            },
+            Command::ToggleSoftWrap => {
+                self.code_editor.soft_wrap = match self.code_editor.soft_wrap {
+                    SoftWrap::Off => SoftWrap::Window,
+                    _ => SoftWrap::Off,
+                };
+            }
+            Command::JumpToMatchingBracket => self.code_editor.jump_to_matching_bracket(),
+            Command::ToggleRainbowBrackets => self.code_editor.toggle_rainbow_brackets(),
+            Command::ToggleIndentGuides => self.code_editor.show_indent_guides = !self.code_editor.show_indent_guides,
+            Command::ToggleWhitespace => self.code_editor.show_whitespace = !self.code_editor.show_whitespace,
+            // 修改后的内容由主循环同步到应用状态并标记为已修改
+            Command::ConvertToLf | Command::ConvertToCrlf => {
+                let target = if command == Command::ConvertToLf { LineEnding::Lf } else { LineEnding::CrLf };
+                app_state.status = match self.code_editor.convert_line_endings(target) {
+                    true => format!("已将换行符转换为 {}", target.name()),
+                    false => format!("换行符已经是 {}", target.name()),
+                };
+            }
+            Command::IndentWithSpaces | Command::IndentWithTabs => {
+                let target = match (command, self.code_editor.indentation().0) {
+                    (Command::IndentWithTabs, _) => Indent::Tabs,
+                    (_, Indent::Spaces(width)) => Indent::Spaces(width),
+                    _ => Indent::Spaces(self.code_editor.tab_width()),
+                };
+                app_state.status = match self.code_editor.reindent(target) {
+                    true => format!("已重新缩进为{}", target.name()),
+                    false => "缩进没有变化".to_string(),
+                };
+            }
+            Command::TabWidth2 | Command::TabWidth4 | Command::TabWidth8 => {
+                let tab_width = match command {
+                    Command::TabWidth2 => 2,
+                    Command::TabWidth4 => 4,
+                    _ => 8,
+                };
+                self.code_editor.set_tab_width(tab_width);
+                app_state.status = format!("制表符宽度: {}", tab_width);
+            }
+            Command::ToggleTableView => {
+                if self.table_delimiter.is_none() {
+                    app_state.status = "当前文件不是 CSV 或 TSV".to_string();
+                    return;
+                }
+                self.show_table = !self.show_table;
+            }
+            Command::FormatDocument | Command::MinifyDocument => {
+                let Some(format) = self.structured_format else {
+                    app_state.status = "当前文件不是 JSON、TOML 或 YAML".to_string();
+                    return;
+                };
+                let (action, result) = match command {
+                    Command::FormatDocument => ("格式化", Some(structured::pretty(format, &self.code_editor.code))),
+                    _ => ("压缩", structured::minify(format, &self.code_editor.code)),
+                };
+                match result {
+                    // 修改后的内容由主循环同步到应用状态并标记为已修改
+                    Some(Ok(text)) => {
+                        self.code_editor.set_code(text);
+                        app_state.status = format!("已{} {} 文档（注释不会保留）", action, format.name());
+                    }
+                    Some(Err(error)) => {
+                        app_state.status = format!("无法{}：{}", action, error);
+                        self.code_editor.go_to_line(error.line);
+                    }
+                    None => app_state.status = format!("{} 不支持{}", format.name(), action),
+                }
+            }
+            Command::ZoomIn | Command::ZoomOut | Command::ZoomToFit | Command::ZoomActualSize | Command::TogglePixelGrid => {
+                let Some(image_view) = &mut self.image_view else {
+                    app_state.status = "没有打开的图像".to_string();
+                    return;
+                };
+                match command {
+                    Command::ZoomIn => image_view.zoom_in(),
+                    Command::ZoomOut => image_view.zoom_out(),
+                    Command::ZoomToFit => image_view.zoom_to_fit(),
+                    Command::ZoomActualSize => image_view.zoom_actual_size(),
+                    _ => image_view.show_grid = !image_view.show_grid,
+                }
+            }
+            Command::CloseImage | Command::CloseHexView => app_state.viewer_file = None,
+            Command::HexGoToOffset | Command::HexFind | Command::HexFindNext | Command::HexFindPrevious => {
+                let Some(hex_view) = &mut self.hex_view else {
+                    app_state.status = "没有以十六进制打开的文件".to_string();
+                    return;
+                };
+                match command {
+                    Command::HexGoToOffset => hex_view.focus_offset_input(),
+                    Command::HexFind => hex_view.focus_search_input(),
+                    Command::HexFindNext => hex_view.find_next(),
+                    _ => hex_view.find_previous(),
+                }
+            }
+            Command::ToggleFavourite => {
+                let Some(path) = &app_state.file_path else {
+                    return;
+                };
+                let added = self.bookmarks.toggle_favourite(path);
+                app_state.status = if added { "已添加到收藏" } else { "已从收藏中移除" }.to_string();
+                self.save_bookmarks(app_state);
+            }
+        }
+    }
+
+    /// 内容修改后按增减的行数移动当前文件的书签（与折叠区域相同的方式）
+    fn update_bookmark_lines(&mut self, app_state: &mut AppState) {
+        let Some(path) = &app_state.file_path else {
+            return;
+        };
+        if self.bookmarks.lines_of(Some(path)).is_empty() {
+            self.bookmark_lines = None;
+            return;
+        }
+        let code_hash = self.code_editor.code_hash();
+        if self.bookmark_lines.as_ref().is_some_and(|(hash, _)| *hash == code_hash) {
+            return;
+        }
+
+        let lines: Vec<u64> = self
+            .code_editor
+            .code
+            .lines()
+            .map(|line| {
+                let mut hasher = DefaultHasher::new();
+                line.hash(&mut hasher);
+                hasher.finish()
+            })
+            .collect();
+        let changed = match &self.bookmark_lines {
+            Some((_, old_lines)) => self.bookmarks.remap_lines(path, old_lines, &lines),
+            None => false,
+        };
+        self.bookmark_lines = Some((code_hash, lines));
+        if changed {
+            self.save_bookmarks(app_state);
+        }
+    }
+
+    /// 保存书签，失败时显示在状态栏
+    fn save_bookmarks(&self, app_state: &mut AppState) {
+        if let Err(e) = self.bookmarks.save() {
+            app_state.status = format!("保存书签失败: {}", e);
+        }
+    }
+
+    /// 将文件操作结果同步到应用状态，返回需要打开的新文件
+    fn apply_file_event(&mut self, event: FileEvent, app_state: &mut AppState) -> Option<PathBuf> {
+        match event {
+            FileEvent::Created(path) => return Some(path),
+            FileEvent::Moved { from, to } => {
+                let remap = |path: &mut PathBuf| {
+                    if let Ok(rest) = path.strip_prefix(&from) {
+                        *path = to.join(rest);
+                    }
+                };
+                if let Some(path) = &mut app_state.file_path {
+                    remap(path);
+                }
+                app_state.recent_files.iter_mut().for_each(remap);
+                self.bookmarks.remap(&from, &to);
+                self.save_bookmarks(app_state);
+                app_state.status = format!("已重命名为 {}", to.display());
+            }
+            FileEvent::Deleted(path) => {
+                let open_file_deleted = app_state.file_path.as_ref().is_some_and(|file| file.starts_with(&path));
+                app_state.status = if open_file_deleted {
+                    "打开的文件已移到回收站".to_string()
+                } else {
+                    format!("已移到回收站: {}", path.display())
+                };
+            }
+            FileEvent::Compare { left, right } => self.compare_files(left, right, app_state),
+            FileEvent::Error(message) => app_state.status = message,
+        }
+        None
+    }
+
+    /// 处理文件系统变化：刷新目录列表，重新加载被外部修改的打开文件
+    fn handle_file_system_changes(&mut self, ctx: &egui::Context, app_state: &mut AppState) {
+        let watcher = self.fs_watcher.get_or_insert_with(|| FsWatcher::new(ctx.clone()));
+
+        // 打开文件所在目录也需要监视（以目录方式监视可以捕获原子保存）
+        let mut directories = self.file_browser.watched_directories();
+        if let Some(parent) = app_state.file_path.as_ref().and_then(|path| path.parent()) {
+            directories.insert(parent.to_path_buf());
+        }
+        watcher.set_watched_directories(directories);
+
+        let changes = watcher.poll();
+        if let Some(git) = &self.git {
+            let affects_git = changes.iter().any(|change| match change {
+                FsChange::DirectoryChanged(path) | FsChange::FileModified(path) => git.in_worktree(path),
+            });
+            if affects_git && self.git_refresh_at.is_none() {
+                self.git_refresh_at = Some(Instant::now() + GIT_REFRESH_DELAY);
+            }
+        }
+        if let Some(at) = self.git_refresh_at {
+            let now = Instant::now();
+            if now >= at {
+                self.git_refresh_at = None;
+                self.refresh_git();
+            } else {
+                ctx.request_repaint_after(at - now);
+            }
+        }
+
+        for change in changes {
+            match change {
+                FsChange::DirectoryChanged(dir) => {
+                    self.file_browser.refresh_directory(&dir);
+                    if let Some(path) = &app_state.file_path {
+                        if path.parent() == Some(dir.as_path()) && !path.exists() {
+                            app_state.status = "文件已在磁盘上被删除".to_string();
+                        }
+                    }
+                }
+                FsChange::FileModified(path) => {
+                    if app_state.file_path.as_ref() == Some(&path) {
+                        self.on_open_file_modified(app_state);
+                    }
+                }
+            }
+        }
+    }
+
+    /// 打开的文件被外部修改：无未保存修改时自动重新加载，否则询问用户
+    fn on_open_file_modified(&mut self, app_state: &mut AppState) {
+        if app_state.is_modified {
+            self.external_change_prompt = true;
+        } else if self.code_editor.follow_mode {
+            app_state.append_from_disk();
+            self.code_editor.scroll_to_bottom();
+        } else if app_state.reload_from_disk() {
+            app_state.status = "文件已在外部修改，已自动重新加载".to_string();
+        }
+    }
+
+    /// 外部修改与未保存修改冲突时的提示框
+    fn render_external_change_prompt(&mut self, ctx: &egui::Context, app_state: &mut AppState) {
+        if !self.external_change_prompt {
+            return;
+        }
+
+        let file_name = app_state
+            .file_path
+            .as_ref()
+            .and_then(|path| path.file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        egui::Window::new("文件已在外部修改")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                ui.label(format!("{} 已在磁盘上被修改，但当前内容有未保存的修改。", file_name));
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("重新加载（放弃修改）").clicked() {
+                        app_state.reload_from_disk();
+                        app_state.is_modified = false;
+                        app_state.status = "已重新加载".to_string();
+                        self.external_change_prompt = false;
+                    }
+                    if ui.button("保留当前内容").clicked() {
+                        self.external_change_prompt = false;
+                    }
+                });
+            });
+    }
+
+    /// 多键序列等待第二个按键时在右下角显示提示
+    fn render_pending_sequence(&self, ctx: &egui::Context) {
+        let Some(pending) = self.keymap.pending_text() else {
+            return;
+        };
+        egui::Area::new("pending_key_sequence")
+            .order(egui::Order::Foreground)
+            .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-12.0, -12.0))
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label(format!("已按下 {}，等待下一个按键...", pending));
+                });
+            });
     }
 
     /// 更新窗口标题
-    fn update_window_title(&self, ctx: &egui::Context, file_path: &Option<PathBuf>) {
+    fn update_window_title(&self, ctx: &egui::Context, file_path: &Option<PathBuf>, is_modified: bool) {
+        let modified_mark = if is_modified { "● " } else { "" };
         if let Some(path) = file_path {
             if let Some(file_name) = path.file_name() {
                 if let Some(name_str) = file_name.to_str() {
-                    ctx.send_viewport_cmd(egui::ViewportCommand::Title(format!("{} - 代码查看器", name_str)));
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Title(format!("{}{} - 代码查看器", modified_mark, name_str)));
                 }
             }
         } else {
             ctx.send_viewport_cmd(egui::ViewportCommand::Title("代码查看器".to_string()));
         }
     }
-}
\ No newline at end of file
+}
+
+/// 路径中的文件名（用于标题）
+fn file_name_of(path: &std::path::Path) -> String {
+    path.file_name().map_or_else(|| path.display().to_string(), |name| name.to_string_lossy().into_owned())
+}
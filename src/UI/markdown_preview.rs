@@ -0,0 +1,324 @@
+use eframe::egui;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use crate::markdown::{self, Block, SourceBlock, Span};
+use crate::file_types;
+use crate::ui::syntax_highlighter::SyntaxHighlighter;
+
+/// 标题字号（一级到六级）
+const HEADING_SIZES: [f32; 6] = [26.0, 22.0, 18.0, 16.0, 14.0, 13.0];
+/// 每层列表的缩进
+const LIST_INDENT: f32 = 20.0;
+/// 每层引用的缩进
+const QUOTE_INDENT: f32 = 14.0;
+
+/// Markdown 预览组件（只读）
+pub struct MarkdownPreview {
+    blocks: Vec<SourceBlock>,
+    code_jobs: HashMap<usize, egui::text::LayoutJob>, // 代码块下标 -> 语法高亮后的内容
+    source_hash: u64,
+    line_count: usize,
+    base_dir: Option<PathBuf>,                          // 相对路径图片的基准目录
+    images: HashMap<PathBuf, Option<egui::TextureHandle>>, // 已加载的本地图片（加载失败为 None）
+    block_offsets: Vec<f32>,  // 上一帧每个块相对内容顶部的位置
+    content_height: f32,
+    scroll_offset: f32,       // 上一帧的滚动位置
+    pending_scroll_offset: Option<f32>, // 下一帧需要跳转到的滚动位置
+}
+
+impl MarkdownPreview {
+    pub fn new() -> Self {
+        Self {
+            blocks: Vec::new(),
+            code_jobs: HashMap::new(),
+            source_hash: 0,
+            line_count: 0,
+            base_dir: None,
+            images: HashMap::new(),
+            block_offsets: Vec::new(),
+            content_height: 0.0,
+            scroll_offset: 0.0,
+            pending_scroll_offset: None,
+        }
+    }
+
+    /// 源文本或文件变化时重新解析
+    pub fn update(&mut self, text: &str, path: Option<&Path>) {
+        let base_dir = path.and_then(Path::parent).map(Path::to_path_buf);
+        if base_dir != self.base_dir {
+            self.base_dir = base_dir;
+            self.images.clear();
+        }
+
+        let mut hasher = DefaultHasher::new();
+        text.hash(&mut hasher);
+        let hash = hasher.finish();
+        if hash == self.source_hash && !self.blocks.is_empty() {
+            return;
+        }
+        self.source_hash = hash;
+        self.line_count = text.lines().count();
+        self.blocks = markdown::parse(text);
+        self.block_offsets.clear();
+        self.code_jobs = self
+            .blocks
+            .iter()
+            .enumerate()
+            .filter_map(|(index, source)| match &source.block {
+                Block::Code { language, code } => Some((index, code_job(language.as_deref(), code))),
+                _ => None,
+            })
+            .collect();
+    }
+
+    /// 预览顶部对应的源文件行（按块位置插值）
+    pub fn top_source_line(&self) -> f32 {
+        let count = self.block_offsets.len().min(self.blocks.len());
+        let Some(index) = self.block_offsets[..count].iter().rposition(|&offset| offset <= self.scroll_offset) else {
+            return 0.0;
+        };
+        let (start_offset, start_line) = (self.block_offsets[index], self.blocks[index].line as f32);
+        let (end_offset, end_line) = match index + 1 < count {
+            true => (self.block_offsets[index + 1], self.blocks[index + 1].line as f32),
+            false => (self.content_height, self.line_count as f32),
+        };
+        let fraction = ((self.scroll_offset - start_offset) / (end_offset - start_offset).max(1.0)).clamp(0.0, 1.0);
+        start_line + fraction * (end_line - start_line)
+    }
+
+    /// 滚动到源文件指定行对应的位置
+    pub fn scroll_to_source_line(&mut self, line: f32) {
+        let count = self.block_offsets.len().min(self.blocks.len());
+        let Some(index) = self.blocks[..count].iter().rposition(|block| block.line as f32 <= line) else {
+            self.pending_scroll_offset = Some(0.0);
+            return;
+        };
+        let (start_offset, start_line) = (self.block_offsets[index], self.blocks[index].line as f32);
+        let (end_offset, end_line) = match index + 1 < count {
+            true => (self.block_offsets[index + 1], self.blocks[index + 1].line as f32),
+            false => (self.content_height, self.line_count as f32),
+        };
+        let fraction = ((line - start_line) / (end_line - start_line).max(1.0)).clamp(0.0, 1.0);
+        self.pending_scroll_offset = Some(start_offset + fraction * (end_offset - start_offset));
+    }
+
+    /// 渲染预览
+    pub fn render(&mut self, ui: &mut egui::Ui, available_height: f32) {
+        ui.set_width(ui.available_width());
+        ui.set_min_height(available_height);
+
+        let mut scroll_area = egui::ScrollArea::vertical()
+            .id_source("markdown_preview")
+            .auto_shrink([false, false]);
+        if let Some(offset) = self.pending_scroll_offset.take() {
+            scroll_area = scroll_area.vertical_scroll_offset(offset);
+        }
+
+        // 渲染时需要同时修改图片缓存，先取出块列表
+        let blocks = std::mem::take(&mut self.blocks);
+        let output = scroll_area.show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            if blocks.is_empty() {
+                ui.weak("（空文档）");
+            }
+
+            let content_top = ui.min_rect().top();
+            self.block_offsets.clear();
+            for (index, source) in blocks.iter().enumerate() {
+                self.block_offsets.push(ui.cursor().top() - content_top);
+                let indent = source.quote_depth as f32 * QUOTE_INDENT + source.indent as f32 * LIST_INDENT;
+                let response = ui.horizontal(|ui| {
+                    ui.add_space(indent);
+                    ui.vertical(|ui| self.render_block(ui, index, &source.block));
+                });
+
+                // 引用块左侧的竖线
+                let rect = response.response.rect;
+                for depth in 0..source.quote_depth {
+                    let x = rect.left() + depth as f32 * QUOTE_INDENT + 3.0;
+                    ui.painter().vline(x, rect.y_range(), egui::Stroke::new(3.0, ui.visuals().widgets.noninteractive.bg_stroke.color));
+                }
+                ui.add_space(6.0);
+            }
+        });
+        self.blocks = blocks;
+        self.scroll_offset = output.state.offset.y;
+        self.content_height = output.content_size.y;
+    }
+
+    fn render_block(&mut self, ui: &mut egui::Ui, index: usize, block: &Block) {
+        match block {
+            Block::Heading { level, spans } => {
+                let size = HEADING_SIZES[(*level as usize).clamp(1, 6) - 1];
+                render_spans(ui, spans, Some(size));
+                if *level <= 2 {
+                    ui.separator();
+                }
+            }
+            Block::Paragraph(spans) => render_spans(ui, spans, None),
+            Block::ListItem { marker, checked, spans } => {
+                ui.horizontal_wrapped(|ui| {
+                    match checked {
+                        Some(checked) => {
+                            let mut checked = *checked;
+                            ui.add_enabled(false, egui::Checkbox::new(&mut checked, ""));
+                        }
+                        None => {
+                            ui.label(marker);
+                        }
+                    }
+                    for span in spans {
+                        render_span(ui, span, None);
+                    }
+                });
+            }
+            Block::Code { .. } => {
+                let Some(job) = self.code_jobs.get(&index) else {
+                    return;
+                };
+                egui::Frame::none()
+                    .fill(ui.visuals().extreme_bg_color)
+                    .rounding(4.0)
+                    .inner_margin(8.0)
+                    .show(ui, |ui| {
+                        ui.set_width(ui.available_width());
+                        egui::ScrollArea::horizontal()
+                            .id_source(("markdown_code", index))
+                            .show(ui, |ui| ui.add(egui::Label::new(job.clone()).wrap(false)));
+                    });
+            }
+            Block::Table { header, rows } => {
+                let columns = header.len().max(rows.iter().map(Vec::len).max().unwrap_or(0));
+                egui::Frame::group(ui.style()).show(ui, |ui| {
+                    egui::Grid::new(("markdown_table", index))
+                        .num_columns(columns)
+                        .striped(true)
+                        .spacing([16.0, 4.0])
+                        .show(ui, |ui| {
+                            for cell in header {
+                                ui.horizontal(|ui| {
+                                    for span in cell {
+                                        render_span(ui, &Span { strong: true, ..span.clone() }, None);
+                                    }
+                                });
+                            }
+                            ui.end_row();
+                            for row in rows {
+                                for cell in row {
+                                    ui.horizontal(|ui| {
+                                        for span in cell {
+                                            render_span(ui, span, None);
+                                        }
+                                    });
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+            }
+            Block::Image { url, alt } => self.render_image(ui, url, alt),
+            Block::Rule => {
+                ui.separator();
+            }
+        }
+    }
+
+    /// 本地图片按原始大小显示（超出宽度时缩小），网络图片只显示链接
+    fn render_image(&mut self, ui: &mut egui::Ui, url: &str, alt: &str) {
+        let label = if alt.is_empty() { url } else { alt };
+        if url.contains("://") || url.starts_with("data:") {
+            ui.hyperlink_to(format!("🖼 {}", label), url);
+            return;
+        }
+
+        let path = match &self.base_dir {
+            Some(base_dir) => base_dir.join(url),
+            None => PathBuf::from(url),
+        };
+        let texture = self
+            .images
+            .entry(path.clone())
+            .or_insert_with(|| load_texture(ui.ctx(), &path));
+        match texture {
+            Some(texture) => {
+                let size = texture.size_vec2();
+                let scale = (ui.available_width() / size.x).min(1.0);
+                ui.add(egui::Image::new(egui::load::SizedTexture::new(texture.id(), size * scale)))
+                    .on_hover_text(label);
+            }
+            None => {
+                ui.colored_label(ui.visuals().warn_fg_color, format!("🖼 无法加载图片: {}", label));
+            }
+        }
+    }
+}
+
+/// 一段行内文本（可换行）
+fn render_spans(ui: &mut egui::Ui, spans: &[Span], size: Option<f32>) {
+    ui.horizontal_wrapped(|ui| {
+        ui.spacing_mut().item_spacing.x = 0.0;
+        for span in spans {
+            render_span(ui, span, size);
+        }
+    });
+}
+
+fn render_span(ui: &mut egui::Ui, span: &Span, size: Option<f32>) {
+    let mut text = egui::RichText::new(&span.text);
+    if let Some(size) = size {
+        text = text.size(size).strong();
+    }
+    if span.strong {
+        text = text.strong();
+    }
+    if span.emphasis {
+        text = text.italics();
+    }
+    if span.strikethrough {
+        text = text.strikethrough();
+    }
+    if span.code {
+        text = text.code();
+    }
+    match &span.link {
+        Some(url) => {
+            ui.hyperlink_to(text, url);
+        }
+        None => {
+            ui.label(text);
+        }
+    }
+}
+
+/// 代码块按信息字符串中的语言高亮（`rs` 这类扩展名也通过文件类型注册表识别）
+fn code_job(language: Option<&str>, code: &str) -> egui::text::LayoutJob {
+    let language = language.map(|language| {
+        file_types::registry()
+            .lookup(&format!("code.{}", language))
+            .language
+            .clone()
+            .unwrap_or_else(|| language.to_string())
+    });
+    let highlighter = SyntaxHighlighter::with_language(language.as_deref());
+    let font = egui::FontId::monospace(12.0);
+
+    let mut job = egui::text::LayoutJob::default();
+    for (index, line) in code.lines().enumerate() {
+        if index > 0 {
+            job.append("\n", 0.0, egui::TextFormat::simple(font.clone(), egui::Color32::TRANSPARENT));
+        }
+        for token in highlighter.parse_line_public(line, 0) {
+            job.append(token.text, 0.0, egui::TextFormat::simple(font.clone(), token.color));
+        }
+    }
+    job
+}
+
+fn load_texture(ctx: &egui::Context, path: &Path) -> Option<egui::TextureHandle> {
+    let image = image::open(path).ok()?.to_rgba8();
+    let size = [image.width() as usize, image.height() as usize];
+    let color_image = egui::ColorImage::from_rgba_unmultiplied(size, image.as_raw());
+    Some(ctx.load_texture(path.display().to_string(), color_image, egui::TextureOptions::default()))
+}
@@ -1,4 +1,14 @@
+pub mod command_palette;
+pub mod commands;
+pub mod commit_view;
 pub mod components;
+pub mod diff_view;
+pub mod hex_view;
+pub mod image_view;
+pub mod keymap;
 pub mod layout;
+pub mod markdown_preview;
+pub mod structured_view;
 pub mod styles;
-pub mod syntax_highlighter;
\ No newline at end of file
+pub mod syntax_highlighter;
+pub mod table_view;
\ No newline at end of file
@@ -0,0 +1,298 @@
+use eframe::egui;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use crate::structured::{self, Document, Format, Key, NodeKind, ParseError};
+use crate::ui::commands::Command;
+
+/// 每层的缩进
+const INDENT: f32 = 16.0;
+/// 打开文档时默认展开的层数
+const DEFAULT_EXPAND_DEPTH: usize = 2;
+
+/// 筛选结果
+struct Filter {
+    matches: Vec<bool>, // 节点是否匹配
+    visible: Vec<bool>, // 匹配的节点、其祖先和子树
+    on_path: Vec<bool>, // 匹配节点的祖先（强制展开）
+    count: usize,
+}
+
+/// JSON/TOML/YAML 的树形视图（只读）
+pub struct StructuredView {
+    format: Option<Format>,
+    document: Result<Document, ParseError>,
+    source_hash: u64,
+    expanded: Vec<bool>,
+    filter_text: String,
+    filter: Option<Filter>,
+    filter_error: Option<String>,
+    rows: Vec<usize>,      // 可见行对应的节点
+    rows_dirty: bool,
+    selected: Option<usize>,
+}
+
+impl StructuredView {
+    pub fn new() -> Self {
+        Self {
+            format: None,
+            document: Ok(Document::default()),
+            source_hash: 0,
+            expanded: Vec::new(),
+            filter_text: String::new(),
+            filter: None,
+            filter_error: None,
+            rows: Vec::new(),
+            rows_dirty: true,
+            selected: None,
+        }
+    }
+
+    /// 源文本或格式变化时重新解析（保留展开状态，节点数变化时重置）
+    pub fn update(&mut self, text: &str, format: Format) {
+        let mut hasher = DefaultHasher::new();
+        text.hash(&mut hasher);
+        let hash = hasher.finish();
+        if hash == self.source_hash && self.format == Some(format) {
+            return;
+        }
+        self.source_hash = hash;
+        self.format = Some(format);
+        self.document = structured::parse(format, text);
+        if let Ok(document) = &self.document {
+            if self.expanded.len() != document.nodes.len() {
+                self.expanded = document.nodes.iter().map(|node| node.depth < DEFAULT_EXPAND_DEPTH).collect();
+                self.selected = None;
+            }
+        }
+        self.apply_filter();
+    }
+
+    /// 当前的解析错误
+    pub fn error(&self) -> Option<&ParseError> {
+        self.document.as_ref().err()
+    }
+
+    fn set_all_expanded(&mut self, expanded: bool) {
+        if let Ok(document) = &self.document {
+            // 折叠全部时保留根节点展开
+            self.expanded = document.nodes.iter().map(|node| expanded || node.depth == 0).collect();
+            self.rows_dirty = true;
+        }
+    }
+
+    fn apply_filter(&mut self) {
+        self.rows_dirty = true;
+        self.filter = None;
+        self.filter_error = None;
+        let Ok(document) = &self.document else {
+            return;
+        };
+        if self.filter_text.trim().is_empty() {
+            return;
+        }
+        let matched = match document.query(&self.filter_text) {
+            Ok(matched) => matched,
+            Err(e) => {
+                self.filter_error = Some(e);
+                return;
+            }
+        };
+
+        let count = document.nodes.len();
+        let mut filter = Filter {
+            matches: vec![false; count],
+            visible: vec![false; count],
+            on_path: vec![false; count],
+            count: matched.len(),
+        };
+        for index in matched {
+            filter.matches[index] = true;
+            filter.visible[index..document.nodes[index].subtree_end].fill(true);
+            let mut parent = document.nodes[index].parent;
+            while let Some(p) = parent {
+                if filter.on_path[p] {
+                    break;
+                }
+                filter.on_path[p] = true;
+                filter.visible[p] = true;
+                parent = document.nodes[p].parent;
+            }
+        }
+        self.filter = Some(filter);
+    }
+
+    /// 按展开状态和筛选结果计算可见行
+    fn rebuild_rows(&mut self) {
+        self.rows_dirty = false;
+        self.rows.clear();
+        let Ok(document) = &self.document else {
+            return;
+        };
+        let mut index = 0;
+        while index < document.nodes.len() {
+            let node = &document.nodes[index];
+            if let Some(filter) = &self.filter {
+                if !filter.visible[index] {
+                    index = node.subtree_end;
+                    continue;
+                }
+            }
+            self.rows.push(index);
+            let forced = self.filter.as_ref().is_some_and(|f| f.on_path[index] && !f.matches[index]);
+            index = if self.expanded[index] || forced { index + 1 } else { node.subtree_end };
+        }
+    }
+
+    /// 渲染树形视图
+    pub fn render(&mut self, ui: &mut egui::Ui, available_height: f32, commands: &mut Vec<Command>) {
+        ui.set_width(ui.available_width());
+        ui.set_min_height(available_height);
+
+        self.render_toolbar(ui, commands);
+        ui.separator();
+
+        if let Err(error) = &self.document {
+            ui.colored_label(ui.visuals().error_fg_color, format!("解析失败：{}", error));
+            ui.weak("出错位置已在编辑器的标记栏中标出");
+            if ui.button("在源文件中查看").clicked() {
+                commands.push(Command::ToggleStructuredView);
+            }
+            return;
+        }
+        if self.rows_dirty {
+            self.rebuild_rows();
+        }
+
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace) + ui.spacing().item_spacing.y;
+        let mut toggled = None;
+        egui::ScrollArea::both()
+            .id_source("structured_tree")
+            .auto_shrink([false, false])
+            .show_rows(ui, row_height, self.rows.len(), |ui, range| {
+                let Ok(document) = &self.document else {
+                    return;
+                };
+                for &index in &self.rows[range] {
+                    let node = &document.nodes[index];
+                    let is_match = self.filter.as_ref().is_some_and(|f| f.matches[index]);
+                    let response = ui.horizontal(|ui| {
+                        ui.add_space(node.depth as f32 * INDENT);
+                        if node.kind.is_container() && !node.children.is_empty() {
+                            let open = self.expanded[index] || self.filter.as_ref().is_some_and(|f| f.on_path[index] && !f.matches[index]);
+                            if ui.add(egui::Label::new(if open { "▼" } else { "▶" }).sense(egui::Sense::click())).clicked() {
+                                toggled = Some(index);
+                            }
+                        } else {
+                            ui.add_space(ui.spacing().icon_width);
+                        }
+
+                        let key = match &node.key {
+                            Key::Root => self.format.map_or("$", Format::name).to_string(),
+                            Key::Name(name) => name.clone(),
+                            Key::Index(i) => format!("[{}]", i),
+                        };
+                        let mut key_text = egui::RichText::new(key).monospace().strong();
+                        if is_match {
+                            key_text = key_text.background_color(ui.visuals().selection.bg_fill);
+                        }
+                        let key_response = ui.add(egui::Label::new(key_text).sense(egui::Sense::click()));
+                        if key_response.double_clicked() {
+                            toggled = Some(index);
+                        } else if key_response.clicked() {
+                            self.selected = Some(index);
+                        }
+
+                        match node.kind {
+                            NodeKind::Array => {
+                                ui.weak(format!("[{}]", node.children.len()));
+                            }
+                            NodeKind::Object => {
+                                ui.weak(format!("{{{}}}", node.children.len()));
+                            }
+                            kind => {
+                                let value = match kind {
+                                    NodeKind::String => format!("\"{}\"", node.value),
+                                    _ => node.value.clone(),
+                                };
+                                ui.add(egui::Label::new(egui::RichText::new(value).monospace().color(value_color(kind, ui.visuals()))).truncate(true));
+                            }
+                        }
+                        ui.label(egui::RichText::new(node.kind.name()).small().weak());
+                    });
+
+                    let row = response.response;
+                    if self.selected == Some(index) {
+                        ui.painter().rect_stroke(row.rect, 2.0, ui.visuals().selection.stroke);
+                    }
+                    row.on_hover_text(document.path(index)).context_menu(|ui| {
+                        if ui.button("复制路径").clicked() {
+                            ui.ctx().output_mut(|o| o.copied_text = document.path(index));
+                            ui.close_menu();
+                        }
+                        if !node.kind.is_container() && ui.button("复制值").clicked() {
+                            ui.ctx().output_mut(|o| o.copied_text = node.value.clone());
+                            ui.close_menu();
+                        }
+                    });
+                }
+            });
+
+        if let Some(index) = toggled {
+            self.expanded[index] = !self.expanded[index];
+            self.selected = Some(index);
+            self.rows_dirty = true;
+        }
+    }
+
+    fn render_toolbar(&mut self, ui: &mut egui::Ui, commands: &mut Vec<Command>) {
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new(self.format.map_or("", Format::name)).strong());
+            if let Ok(document) = &self.document {
+                ui.weak(format!("{} 个节点", document.nodes.len()));
+            }
+            if ui.button("展开全部").clicked() {
+                self.set_all_expanded(true);
+            }
+            if ui.button("折叠全部").clicked() {
+                self.set_all_expanded(false);
+            }
+
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.filter_text)
+                    .hint_text("筛选：关键字或 $.items[*].name")
+                    .desired_width(260.0),
+            );
+            if response.changed() {
+                self.apply_filter();
+            }
+            if let Some(error) = &self.filter_error {
+                ui.colored_label(ui.visuals().warn_fg_color, error);
+            } else if let Some(filter) = &self.filter {
+                ui.weak(format!("{} 个匹配", filter.count));
+            }
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.button("✖ 关闭").clicked() {
+                    commands.push(Command::ToggleStructuredView);
+                }
+                if self.format != Some(Format::Toml) && ui.button("压缩").on_hover_text("去掉缩进和换行（不保留注释）").clicked() {
+                    commands.push(Command::MinifyDocument);
+                }
+                if ui.button("格式化").on_hover_text("按标准格式重新缩进（不保留注释）").clicked() {
+                    commands.push(Command::FormatDocument);
+                }
+            });
+        });
+    }
+}
+
+/// 标量值的颜色
+fn value_color(kind: NodeKind, visuals: &egui::Visuals) -> egui::Color32 {
+    match kind {
+        NodeKind::String => egui::Color32::from_rgb(152, 195, 121),
+        NodeKind::Number => egui::Color32::from_rgb(97, 175, 239),
+        NodeKind::Bool => egui::Color32::from_rgb(209, 154, 102),
+        NodeKind::DateTime => egui::Color32::from_rgb(198, 120, 221),
+        _ => visuals.weak_text_color(),
+    }
+}
@@ -1,6 +1,11 @@
 use eframe::egui;
 use egui::{FontId, TextStyle, Color32};
 
+/// Git 新增、修改和删除的标记颜色
+pub const GIT_ADDED_COLOR: Color32 = Color32::from_rgb(87, 171, 90);
+pub const GIT_MODIFIED_COLOR: Color32 = Color32::from_rgb(80, 140, 220);
+pub const GIT_DELETED_COLOR: Color32 = Color32::from_rgb(220, 80, 80);
+
 /// 设置中文字体支持和应用样式
 pub fn setup_chinese_fonts(ctx: &egui::Context) {
     let mut fonts = egui::FontDefinitions::default();
@@ -30,10 +35,21 @@ pub fn setup_chinese_fonts(ctx: &egui::Context) {
     style.text_styles.insert(TextStyle::Button, FontId::new(14.0, egui::FontFamily::Proportional));
     style.text_styles.insert(TextStyle::Small, FontId::new(13.0, egui::FontFamily::Proportional));
 
+    ctx.set_style(style);
+
+    apply_theme(ctx, true);
+}
+
+/// 切换深色/浅色主题
+pub fn apply_theme(ctx: &egui::Context, dark_mode: bool) {
+    let mut visuals = if dark_mode { egui::Visuals::dark() } else { egui::Visuals::light() };
+
     // 设置更明亮的前景色，提高可读性
-    style.visuals.widgets.noninteractive.fg_stroke.color = Color32::from_rgb(240, 240, 240);
-    style.visuals.selection.stroke.color = Color32::from_rgb(100, 150, 255);
-    style.visuals.selection.bg_fill = Color32::from_rgba_premultiplied(100, 150, 255, 50);
+    if dark_mode {
+        visuals.widgets.noninteractive.fg_stroke.color = Color32::from_rgb(240, 240, 240);
+    }
+    visuals.selection.stroke.color = Color32::from_rgb(100, 150, 255);
+    visuals.selection.bg_fill = Color32::from_rgba_premultiplied(100, 150, 255, 50);
 
-    ctx.set_style(style);
+    ctx.set_visuals(visuals);
 }
\ No newline at end of file
@@ -13,15 +13,173 @@ static RUST_KEYWORDS: phf::Set<&'static str> = phf_set! {
     "await", "move", "const", "static", "type", "where", "in",
 };
 
+static PYTHON_KEYWORDS: phf::Set<&'static str> = phf_set! {
+    "def", "class", "return", "if", "elif", "else", "for", "while", "break",
+    "continue", "import", "from", "as", "with", "try", "except", "finally",
+    "raise", "pass", "lambda", "yield", "global", "nonlocal", "in", "is",
+    "not", "and", "or", "None", "True", "False", "async", "await", "del",
+};
+
+static JAVASCRIPT_KEYWORDS: phf::Set<&'static str> = phf_set! {
+    "function", "var", "let", "const", "if", "else", "for", "while", "do",
+    "return", "break", "continue", "switch", "case", "default", "new", "this",
+    "class", "extends", "import", "export", "from", "async", "await", "try",
+    "catch", "finally", "throw", "typeof", "instanceof", "null", "undefined",
+    "true", "false", "interface", "type", "enum", "implements",
+};
+
+static C_KEYWORDS: phf::Set<&'static str> = phf_set! {
+    "int", "char", "float", "double", "void", "long", "short", "unsigned",
+    "signed", "const", "static", "struct", "union", "enum", "typedef", "if",
+    "else", "for", "while", "do", "return", "break", "continue", "switch",
+    "case", "default", "sizeof", "include", "define", "class", "public",
+    "private", "protected", "namespace", "template", "virtual", "auto", "new",
+    "delete", "using", "nullptr", "true", "false",
+};
+
+static JAVA_KEYWORDS: phf::Set<&'static str> = phf_set! {
+    "class", "interface", "extends", "implements", "public", "private",
+    "protected", "static", "final", "void", "int", "long", "boolean", "char",
+    "double", "float", "if", "else", "for", "while", "do", "return", "break",
+    "continue", "switch", "case", "default", "new", "this", "super", "try",
+    "catch", "finally", "throw", "throws", "import", "package", "null",
+    "true", "false",
+};
+
+static GO_KEYWORDS: phf::Set<&'static str> = phf_set! {
+    "func", "var", "const", "type", "struct", "interface", "map", "chan",
+    "package", "import", "if", "else", "for", "range", "return", "break",
+    "continue", "switch", "case", "default", "go", "defer", "select", "nil",
+    "true", "false",
+};
+
+static SHELL_KEYWORDS: phf::Set<&'static str> = phf_set! {
+    "if", "then", "else", "elif", "fi", "for", "while", "do", "done", "case",
+    "esac", "function", "in", "return", "export", "local", "echo",
+};
+
+/// 语言对应的关键字集合和单行注释前缀
+fn language_syntax(language: &str) -> (Option<&'static phf::Set<&'static str>>, Option<&'static str>) {
+    match language {
+        "rust" => (Some(&RUST_KEYWORDS), Some("//")),
+        "python" => (Some(&PYTHON_KEYWORDS), Some("#")),
+        "javascript" | "typescript" => (Some(&JAVASCRIPT_KEYWORDS), Some("//")),
+        "c" | "cpp" => (Some(&C_KEYWORDS), Some("//")),
+        "java" => (Some(&JAVA_KEYWORDS), Some("//")),
+        "go" => (Some(&GO_KEYWORDS), Some("//")),
+        "shell" => (Some(&SHELL_KEYWORDS), Some("#")),
+        "toml" | "yaml" | "ini" | "makefile" | "dockerfile" | "gitignore" => (None, Some("#")),
+        "sql" => (None, Some("--")),
+        _ => (None, None),
+    }
+}
+
+/// 分隔符文本按列轮流使用的颜色
+const COLUMN_COLORS: [egui::Color32; 8] = [
+    egui::Color32::from_rgb(0, 100, 200),
+    egui::Color32::from_rgb(200, 100, 0),
+    egui::Color32::from_rgb(0, 140, 70),
+    egui::Color32::from_rgb(170, 50, 170),
+    egui::Color32::from_rgb(0, 150, 160),
+    egui::Color32::from_rgb(180, 140, 0),
+    egui::Color32::from_rgb(200, 50, 80),
+    egui::Color32::from_rgb(100, 100, 220),
+];
+
+/// 彩虹括号按嵌套深度轮流使用的颜色
+const RAINBOW_COLORS: [egui::Color32; 6] = [
+    egui::Color32::from_rgb(220, 160, 0),
+    egui::Color32::from_rgb(200, 80, 200),
+    egui::Color32::from_rgb(30, 140, 230),
+    egui::Color32::from_rgb(0, 160, 110),
+    egui::Color32::from_rgb(230, 100, 60),
+    egui::Color32::from_rgb(120, 120, 230),
+];
+
 pub struct SyntaxHighlighter {
     cache: HashMap<usize, (u64, Vec<CachedToken>)>,
+    keywords: Option<&'static phf::Set<&'static str>>, // 当前语言的关键字
+    line_comment: Option<&'static str>,                // 当前语言的单行注释前缀
+    delimiter: Option<u8>,                             // CSV/TSV 的分隔符（按列着色）
+    rainbow_brackets: bool,                            // 括号按嵌套深度着色
 }
 
 impl SyntaxHighlighter {
     pub fn new() -> Self {
+        Self::with_language(Some("rust"))
+    }
+
+    /// 按语言标识（来自文件类型注册表）创建；未知语言只做基础着色
+    pub fn with_language(language: Option<&str>) -> Self {
+        let (keywords, line_comment) = language.map_or((None, None), language_syntax);
+        let delimiter = match language {
+            Some("csv") => Some(b','),
+            Some("tsv") => Some(b'\t'),
+            _ => None,
+        };
         Self {
             cache: HashMap::new(),
+            keywords,
+            line_comment,
+            delimiter,
+            rainbow_brackets: false,
+        }
+    }
+
+    /// 当前语言的单行注释前缀
+    pub fn line_comment(&self) -> Option<&'static str> {
+        self.line_comment
+    }
+
+    /// 使用识别出的分隔符代替语言的默认分隔符
+    pub fn set_delimiter(&mut self, delimiter: u8) {
+        self.delimiter = Some(delimiter);
+    }
+
+    /// 括号是否按嵌套深度着色
+    pub fn set_rainbow_brackets(&mut self, enabled: bool) {
+        self.rainbow_brackets = enabled;
+    }
+
+    /// 行内括号的位置和字符（与 parse_line_public 的规则一致：跳过字符串、字符字面量和单行注释）
+    pub fn brackets(&self, line: &str) -> Vec<(usize, char)> {
+        let mut brackets = Vec::new();
+        if self.delimiter.is_some() {
+            return brackets;
         }
+        let mut chars = line.char_indices();
+        while let Some((index, ch)) = chars.next() {
+            if self.line_comment.is_some_and(|prefix| line[index..].starts_with(prefix)) {
+                break;
+            }
+            match ch {
+                '"' | '\'' => {
+                    // 跳到下一个相同的引号
+                    for (_, next) in chars.by_ref() {
+                        if next == ch {
+                            break;
+                        }
+                    }
+                }
+                '(' | ')' | '[' | ']' | '{' | '}' => brackets.push((index, ch)),
+                _ => {}
+            }
+        }
+        brackets
+    }
+
+    /// 每行行首的括号嵌套深度（多余的右括号不会使深度小于 0）
+    pub fn line_depths(&self, text: &str) -> Vec<usize> {
+        let mut depth = 0;
+        text.lines()
+            .map(|line| {
+                let start = depth;
+                for (_, bracket) in self.brackets(line) {
+                    depth = bracket_depth_after(depth, bracket);
+                }
+                start
+            })
+            .collect()
     }
 
     // 计算行的哈希值用于缓存检测
@@ -37,7 +195,7 @@ impl SyntaxHighlighter {
         let mut job = egui::text::LayoutJob::default();
 
         for line in code.lines() {
-            let tokens = self.parse_line_public(line);
+            let tokens = self.parse_line_public(line, 0);
 
             for token in tokens {
                 job.append(
@@ -108,7 +266,7 @@ impl SyntaxHighlighter {
         }
         
         // 缓存未命中或哈希不匹配，重新解析
-        let tokens = self.parse_line_public(line);
+        let tokens = self.parse_line_public(line, 0);
         
         // 转换为缓存Token格式
         let cached_tokens: Vec<CachedToken> = tokens.iter().map(|token| CachedToken {
@@ -134,13 +292,11 @@ impl SyntaxHighlighter {
         self.cache.clear();
     }
 
-    // 并行解析多行（使用rayon进行并行处理）
-    pub fn parse_lines_parallel(&self, lines: &[(usize, &str)]) -> Vec<(usize, Vec<CachedToken>)> {
+    // 并行解析多行（使用rayon进行并行处理），每行附带行首的括号嵌套深度
+    pub fn parse_lines_parallel(&self, lines: &[(usize, &str, usize)]) -> Vec<(usize, Vec<CachedToken>)> {
         lines.par_iter()
-            .map(|&(line_number, line)| {
-                // 创建独立的语法高亮器实例用于并行处理
-                let highlighter = SyntaxHighlighter::new();
-                let tokens = highlighter.parse_line_public(line);
+            .map(|&(line_number, line, bracket_depth)| {
+                let tokens = self.parse_line_public(line, bracket_depth);
                 
                 // 转换为缓存Token格式
                 let cached_tokens: Vec<CachedToken> = tokens.iter().map(|token| CachedToken {
@@ -155,13 +311,31 @@ impl SyntaxHighlighter {
             .collect()
     }
 
-    /// 简化的语法高亮解析（性能优化版本）
-    pub fn parse_line_public<'a>(&self, line: &'a str) -> Vec<Token<'a>> {
+    /// 简化的语法高亮解析（性能优化版本）；bracket_depth 为行首的括号嵌套深度（彩虹括号使用）
+    pub fn parse_line_public<'a>(&self, line: &'a str, bracket_depth: usize) -> Vec<Token<'a>> {
+        if let Some(delimiter) = self.delimiter {
+            return Self::parse_delimited_line(line, delimiter);
+        }
+
         // 预分配token向量，假设平均每行有10个token
         let mut tokens = Vec::with_capacity(10);
         let mut chars = line.char_indices().peekable();
+        let mut depth = bracket_depth;
 
         while let Some((start_idx, ch)) = chars.next() {
+            // 单行注释：该行剩余部分全部作为注释
+            if let Some(prefix) = self.line_comment {
+                if line[start_idx..].starts_with(prefix) {
+                    tokens.push(Token {
+                        text: &line[start_idx..],
+                        start_col: start_idx,
+                        end_col: line.len(),
+                        color: egui::Color32::from_rgb(100, 100, 100), // 灰色注释
+                    });
+                    break;
+                }
+            }
+
             match ch {
                 ch if ch.is_alphabetic() || ch == '_' => {
                     // 标识符或关键字 - 使用切片避免字符串复制
@@ -180,7 +354,7 @@ impl SyntaxHighlighter {
 
                     let word = &line[start..end];
                     // 简化颜色方案：只区分关键字和标识符
-                    let color = if RUST_KEYWORDS.contains(word) {
+                    let color = if self.keywords.is_some_and(|keywords| keywords.contains(word)) {
                         egui::Color32::from_rgb(255, 140, 0) // 橙色关键字
                     } else {
                         egui::Color32::from_rgb(0, 100, 200) // 蓝色标识符
@@ -282,42 +456,6 @@ impl SyntaxHighlighter {
                         color: egui::Color32::from_rgb(128, 0, 128), // 紫色数字
                     });
                 }
-                '/' => {
-                    // 可能是注释
-                    if let Some(&(_, next_ch)) = chars.peek() {
-                        if next_ch == '/' {
-                            // 单行注释 - 使用切片避免字符串复制
-                            let start = start_idx;
-                            let mut end = start_idx + ch.len_utf8();
-                            end += chars.next().unwrap().1.len_utf8(); // 消耗第二个 '/'
-
-                            // 消耗该行剩余所有字符
-                            while let Some(&(next_idx, next_ch)) = chars.peek() {
-                                end = next_idx + next_ch.len_utf8(); // 正确处理UTF-8字符
-                                chars.next();
-                            }
-
-                            let comment = &line[start..end];
-                            tokens.push(Token {
-                                text: comment,
-                                start_col: start,
-                                end_col: end,
-                                color: egui::Color32::from_rgb(100, 100, 100), // 灰色注释
-                            });
-                            continue;
-                        }
-                    }
-
-                    // 普通除号或运算符
-                    // 单个字符需要转换为字符串切片
-                    let char_str = &line[start_idx..start_idx + ch.len_utf8()];
-                    tokens.push(Token {
-                        text: char_str,
-                        start_col: start_idx,
-                        end_col: start_idx + ch.len_utf8(),
-                        color: egui::Color32::from_rgb(200, 100, 0), // 棕色运算符
-                    });
-                }
                 ch if "+-*/%=&|<>!^".contains(ch) => {
                     // 运算符
                     // 单个字符需要转换为字符串切片
@@ -330,14 +468,23 @@ impl SyntaxHighlighter {
                     });
                 }
                 ch if "(){}[];:,".contains(ch) => {
-                    // 标点符号
+                    // 标点符号；彩虹括号时左括号使用进入前的深度、右括号使用退出后的深度着色
                     // 单个字符需要转换为字符串切片
                     let char_str = &line[start_idx..start_idx + ch.len_utf8()];
+                    let color = match ch {
+                        '(' | ')' | '[' | ']' | '{' | '}' if self.rainbow_brackets => {
+                            let after = bracket_depth_after(depth, ch);
+                            let level = depth.min(after);
+                            depth = after;
+                            RAINBOW_COLORS[level % RAINBOW_COLORS.len()]
+                        }
+                        _ => egui::Color32::from_rgb(50, 50, 50), // 深灰色标点
+                    };
                     tokens.push(Token {
                         text: char_str,
                         start_col: start_idx,
                         end_col: start_idx + ch.len_utf8(),
-                        color: egui::Color32::from_rgb(50, 50, 50), // 深灰色标点
+                        color,
                     });
                 }
                 _ => {
@@ -356,6 +503,53 @@ impl SyntaxHighlighter {
 
         tokens
     }
+
+    /// 分隔符文本按列着色，同一列在各行中颜色相同（引号内的分隔符不分列）
+    fn parse_delimited_line(line: &str, delimiter: u8) -> Vec<Token<'_>> {
+        let mut tokens = Vec::new();
+        let mut field_start = 0;
+        let mut column = 0;
+        let mut in_quote = false;
+        for (index, byte) in line.bytes().enumerate() {
+            if byte == b'"' {
+                in_quote = !in_quote;
+            } else if byte == delimiter && !in_quote {
+                if index > field_start {
+                    tokens.push(Token {
+                        text: &line[field_start..index],
+                        start_col: field_start,
+                        end_col: index,
+                        color: COLUMN_COLORS[column % COLUMN_COLORS.len()],
+                    });
+                }
+                tokens.push(Token {
+                    text: &line[index..index + 1],
+                    start_col: index,
+                    end_col: index + 1,
+                    color: egui::Color32::GRAY,
+                });
+                field_start = index + 1;
+                column += 1;
+            }
+        }
+        if line.len() > field_start {
+            tokens.push(Token {
+                text: &line[field_start..],
+                start_col: field_start,
+                end_col: line.len(),
+                color: COLUMN_COLORS[column % COLUMN_COLORS.len()],
+            });
+        }
+        tokens
+    }
+}
+
+/// 经过一个括号后的嵌套深度
+fn bracket_depth_after(depth: usize, bracket: char) -> usize {
+    match bracket {
+        '(' | '[' | '{' => depth + 1,
+        _ => depth.saturating_sub(1),
+    }
 }
 
 pub struct Token<'a> {
@@ -0,0 +1,396 @@
+use eframe::egui;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::sync::Arc;
+use crate::delimited::{self, Dialect, Index, RowQuery};
+use crate::ui::commands::Command;
+
+/// 列宽范围（初始宽度按前几条记录的内容估算）
+const MIN_COLUMN_WIDTH: f32 = 40.0;
+const MAX_AUTO_COLUMN_WIDTH: f32 = 320.0;
+/// 估算列宽时检查的记录数
+const WIDTH_SAMPLE_ROWS: usize = 100;
+/// 估算列宽时每个字符的宽度
+const APPROX_CHAR_WIDTH: f32 = 7.5;
+/// 行号列宽度
+const ROW_NUMBER_WIDTH: f32 = 64.0;
+/// 列右侧拖动调整宽度的区域
+const RESIZE_HANDLE_WIDTH: f32 = 5.0;
+
+/// CSV/TSV 表格视图（只读）
+pub struct TableView {
+    text: Arc<str>,
+    source_hash: u64,
+    path: Option<PathBuf>,
+    default_delimiter: u8,          // 扩展名对应的分隔符
+    dialect: Dialect,
+    dialect_overridden: bool,       // 用户手动选择了分隔符
+    index: Option<Arc<Index>>,      // 建立索引期间为 None
+    index_job: Option<Receiver<Index>>,
+    has_header: bool,               // 首条记录作为列标题
+    column_widths: Vec<f32>,
+    column_filters: Vec<String>,
+    sort: Option<(usize, bool)>,    // (列, 是否降序)
+    query: RowQuery,
+    rows: Option<Vec<usize>>,       // 筛选或排序后的记录，None 表示按原顺序显示全部
+    query_job: Option<(RowQuery, Receiver<Vec<usize>>)>,
+    jump_line: Option<usize>,       // 双击行号后在原始文本中定位的行
+}
+
+impl TableView {
+    pub fn new() -> Self {
+        Self {
+            text: Arc::from(""),
+            source_hash: 0,
+            path: None,
+            default_delimiter: b',',
+            dialect: Dialect { delimiter: b',', quote: b'"' },
+            dialect_overridden: false,
+            index: None,
+            index_job: None,
+            has_header: true,
+            column_widths: Vec::new(),
+            column_filters: Vec::new(),
+            sort: None,
+            query: RowQuery::default(),
+            rows: None,
+            query_job: None,
+            jump_line: None,
+        }
+    }
+
+    /// 当前使用的方言
+    pub fn dialect(&self) -> Dialect {
+        self.dialect
+    }
+
+    /// 取出需要在原始文本中定位的行
+    pub fn take_jump_line(&mut self) -> Option<usize> {
+        self.jump_line.take()
+    }
+
+    /// 源文本变化时在后台重新建立索引；打开其他文件时重置列宽、筛选和排序
+    pub fn update(&mut self, ctx: &egui::Context, text: &str, path: Option<&Path>, default_delimiter: u8) {
+        if self.path.as_deref() != path || self.default_delimiter != default_delimiter {
+            self.path = path.map(Path::to_path_buf);
+            self.default_delimiter = default_delimiter;
+            self.dialect_overridden = false;
+            self.column_widths.clear();
+            self.column_filters.clear();
+            self.sort = None;
+            self.source_hash = 0;
+        }
+
+        let mut hasher = DefaultHasher::new();
+        text.hash(&mut hasher);
+        let hash = hasher.finish();
+        if hash != self.source_hash {
+            self.source_hash = hash;
+            self.text = Arc::from(text);
+            if !self.dialect_overridden {
+                self.dialect = delimited::detect(text, default_delimiter);
+            }
+            self.rebuild_index(ctx);
+        }
+        self.poll_jobs(ctx);
+    }
+
+    fn rebuild_index(&mut self, ctx: &egui::Context) {
+        self.index = None;
+        self.rows = None;
+        self.query_job = None;
+        self.index_job = Some(delimited::spawn_index(ctx.clone(), self.text.clone(), self.dialect));
+    }
+
+    fn poll_jobs(&mut self, ctx: &egui::Context) {
+        if let Some(receiver) = &self.index_job {
+            match receiver.try_recv() {
+                Ok(index) => {
+                    self.index_job = None;
+                    if self.column_widths.len() != index.columns {
+                        self.column_widths = self.estimate_column_widths(&index);
+                    }
+                    self.column_filters.resize(index.columns, String::new());
+                    self.index = Some(Arc::new(index));
+                    self.refresh_query(ctx);
+                }
+                Err(TryRecvError::Disconnected) => self.index_job = None,
+                Err(TryRecvError::Empty) => {}
+            }
+        }
+        if let Some((query, receiver)) = &self.query_job {
+            match receiver.try_recv() {
+                // 计算期间条件已变化的结果直接丢弃
+                Ok(rows) => {
+                    if *query == self.query {
+                        self.rows = Some(rows);
+                    }
+                    self.query_job = None;
+                }
+                Err(TryRecvError::Disconnected) => self.query_job = None,
+                Err(TryRecvError::Empty) => {}
+            }
+        }
+    }
+
+    fn estimate_column_widths(&self, index: &Index) -> Vec<f32> {
+        let mut widths = vec![MIN_COLUMN_WIDTH; index.columns];
+        for row in 0..index.len().min(WIDTH_SAMPLE_ROWS) {
+            for (column, field) in delimited::parse_fields(index.record(&self.text, row), self.dialect).iter().enumerate() {
+                let width = field.chars().count() as f32 * APPROX_CHAR_WIDTH + 16.0;
+                widths[column] = widths[column].max(width.min(MAX_AUTO_COLUMN_WIDTH));
+            }
+        }
+        widths
+    }
+
+    /// 按标题、筛选和排序条件重新计算显示的记录（不筛选也不排序时不需要计算）
+    fn refresh_query(&mut self, ctx: &egui::Context) {
+        self.query = RowQuery {
+            skip_header: self.has_header,
+            filters: self
+                .column_filters
+                .iter()
+                .enumerate()
+                .filter(|(_, filter)| !filter.trim().is_empty())
+                .map(|(column, filter)| (column, filter.trim().to_string()))
+                .collect(),
+            sort: self.sort,
+        };
+        let Some(index) = &self.index else {
+            return;
+        };
+        if self.query.is_identity() {
+            self.rows = None;
+            self.query_job = None;
+        } else {
+            let receiver = delimited::spawn_query(ctx.clone(), self.text.clone(), index.clone(), self.dialect, self.query.clone());
+            self.query_job = Some((self.query.clone(), receiver));
+        }
+    }
+
+    /// 显示的记录数
+    fn row_count(&self, index: &Index) -> usize {
+        match &self.rows {
+            Some(rows) => rows.len(),
+            None => index.len().saturating_sub(usize::from(self.has_header)),
+        }
+    }
+
+    /// 第 n 个显示行对应的记录
+    fn record_at(&self, n: usize) -> usize {
+        match &self.rows {
+            Some(rows) => rows[n],
+            None => n + usize::from(self.has_header),
+        }
+    }
+
+    /// 渲染表格
+    pub fn render(&mut self, ui: &mut egui::Ui, available_height: f32, commands: &mut Vec<Command>) {
+        ui.set_width(ui.available_width());
+        ui.set_min_height(available_height);
+
+        let ctx = ui.ctx().clone();
+        self.render_toolbar(ui, &ctx, commands);
+        ui.separator();
+
+        let Some(index) = self.index.clone() else {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label("正在建立索引…");
+            });
+            return;
+        };
+        if index.is_empty() {
+            ui.weak("（空文件）");
+            return;
+        }
+
+        let first_record = delimited::parse_fields(index.record(&self.text, 0), self.dialect);
+        let header: Vec<String> = (0..index.columns)
+            .map(|column| match self.has_header {
+                true => first_record.get(column).map_or_else(String::new, |field| field.to_string()),
+                false => format!("列 {}", column + 1),
+            })
+            .collect();
+        let row_height = ui.text_style_height(&egui::TextStyle::Body) + 6.0;
+        let row_count = self.row_count(&index);
+
+        egui::ScrollArea::horizontal()
+            .id_source("table_columns")
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                let filters_changed = self.render_header(ui, &ctx, &header, row_height);
+                if filters_changed {
+                    self.refresh_query(&ctx);
+                }
+                ui.separator();
+
+                egui::ScrollArea::vertical()
+                    .id_source("table_rows")
+                    .auto_shrink([false, false])
+                    .show_rows(ui, row_height, row_count, |ui, range| {
+                        for n in range {
+                            let row = self.record_at(n);
+                            let fields = delimited::parse_fields(index.record(&self.text, row), self.dialect);
+                            let background = ui.painter().add(egui::Shape::Noop);
+                            let response = ui.horizontal(|ui| {
+                                ui.spacing_mut().item_spacing.x = 0.0;
+                                let number = ui
+                                    .add_sized(
+                                        [ROW_NUMBER_WIDTH, row_height],
+                                        egui::Label::new(egui::RichText::new(format!("{}", row + 1)).monospace().weak())
+                                            .sense(egui::Sense::click()),
+                                    )
+                                    .on_hover_text("双击在原始文本中定位");
+                                if number.double_clicked() {
+                                    self.jump_line = Some(index.line_of(&self.text, row));
+                                    commands.push(Command::ToggleTableView);
+                                }
+                                for (column, &width) in self.column_widths.iter().enumerate() {
+                                    let field = fields.get(column).map_or("", |field| field.as_ref());
+                                    render_cell(ui, width, row_height, field);
+                                }
+                            });
+                            if n % 2 == 1 {
+                                ui.painter().set(background, egui::Shape::rect_filled(response.response.rect, 0.0, ui.visuals().faint_bg_color));
+                            }
+                        }
+                    });
+            });
+    }
+
+    fn render_toolbar(&mut self, ui: &mut egui::Ui, ctx: &egui::Context, commands: &mut Vec<Command>) {
+        ui.horizontal(|ui| {
+            let mut delimiter = self.dialect.delimiter;
+            egui::ComboBox::from_id_source("table_delimiter")
+                .selected_text(format!("分隔符：{}", delimited::delimiter_name(delimiter)))
+                .show_ui(ui, |ui| {
+                    for &candidate in delimited::DELIMITERS {
+                        ui.selectable_value(&mut delimiter, candidate, delimited::delimiter_name(candidate));
+                    }
+                });
+            if delimiter != self.dialect.delimiter {
+                self.dialect.delimiter = delimiter;
+                self.dialect_overridden = true;
+                self.column_widths.clear();
+                self.column_filters.clear();
+                self.sort = None;
+                self.rebuild_index(ctx);
+            }
+            ui.weak(format!("引号：{}", self.dialect.quote as char));
+
+            if ui.checkbox(&mut self.has_header, "首行为标题").changed() {
+                self.refresh_query(ctx);
+            }
+
+            if let Some(index) = &self.index {
+                let total = index.len().saturating_sub(usize::from(self.has_header));
+                let shown = self.row_count(index);
+                if shown == total {
+                    ui.label(format!("{} 条记录 × {} 列", total, index.columns));
+                } else {
+                    ui.label(format!("{} / {} 条记录 × {} 列", shown, total, index.columns));
+                }
+            }
+            if self.query_job.is_some() {
+                ui.spinner();
+                ui.weak("正在筛选和排序…");
+            }
+            if self.column_filters.iter().any(|filter| !filter.is_empty()) && ui.button("清除筛选").clicked() {
+                self.column_filters.iter_mut().for_each(String::clear);
+                self.refresh_query(ctx);
+            }
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.button("📝 原始文本").clicked() {
+                    commands.push(Command::ToggleTableView);
+                }
+            });
+        });
+    }
+
+    /// 列标题（点击排序，拖动右边缘调整宽度）和每列的筛选框，返回筛选条件是否变化
+    fn render_header(&mut self, ui: &mut egui::Ui, ctx: &egui::Context, header: &[String], row_height: f32) -> bool {
+        let mut sort_clicked = None;
+        ui.horizontal(|ui| {
+            ui.spacing_mut().item_spacing.x = 0.0;
+            ui.add_space(ROW_NUMBER_WIDTH);
+            for (column, title) in header.iter().enumerate() {
+                let arrow = match self.sort {
+                    Some((sorted, false)) if sorted == column => " ▲",
+                    Some((sorted, true)) if sorted == column => " ▼",
+                    _ => "",
+                };
+                let width = self.column_widths[column] - RESIZE_HANDLE_WIDTH;
+                let response = ui
+                    .allocate_ui_with_layout(egui::vec2(width, row_height), egui::Layout::left_to_right(egui::Align::Center), |ui| {
+                        ui.set_min_width(width);
+                        let text = egui::RichText::new(format!("{}{}", title, arrow)).strong();
+                        ui.add(egui::Label::new(text).truncate(true).sense(egui::Sense::click()))
+                    })
+                    .inner
+                    .on_hover_text(format!("{}\n点击排序（升序 → 降序 → 原顺序）", title));
+                if response.clicked() {
+                    sort_clicked = Some(column);
+                }
+
+                let (handle_rect, handle) = ui.allocate_exact_size(egui::vec2(RESIZE_HANDLE_WIDTH, row_height), egui::Sense::drag());
+                let stroke = match handle.hovered() || handle.dragged() {
+                    true => ui.visuals().widgets.hovered.fg_stroke,
+                    false => ui.visuals().widgets.noninteractive.bg_stroke,
+                };
+                ui.painter().vline(handle_rect.center().x, handle_rect.y_range(), stroke);
+                if handle.hovered() || handle.dragged() {
+                    ui.ctx().set_cursor_icon(egui::CursorIcon::ResizeHorizontal);
+                }
+                if handle.dragged() {
+                    self.column_widths[column] = (self.column_widths[column] + handle.drag_delta().x).max(MIN_COLUMN_WIDTH);
+                }
+            }
+        });
+
+        if let Some(column) = sort_clicked {
+            self.sort = match self.sort {
+                Some((sorted, false)) if sorted == column => Some((column, true)),
+                Some((sorted, true)) if sorted == column => None,
+                _ => Some((column, false)),
+            };
+            self.refresh_query(ctx);
+        }
+
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.spacing_mut().item_spacing.x = 0.0;
+            ui.add_space(ROW_NUMBER_WIDTH);
+            for (filter, &width) in self.column_filters.iter_mut().zip(&self.column_widths) {
+                ui.allocate_ui_with_layout(egui::vec2(width, row_height), egui::Layout::left_to_right(egui::Align::Center), |ui| {
+                    ui.set_min_width(width);
+                    // 文本框两侧的内边距不计入 desired_width
+                    let response = ui.add(
+                        egui::TextEdit::singleline(filter)
+                            .hint_text("筛选")
+                            .desired_width(width - RESIZE_HANDLE_WIDTH - 8.0),
+                    );
+                    changed |= response.changed();
+                });
+            }
+        });
+        changed
+    }
+}
+
+/// 固定宽度的单元格，内容过长时截断并在悬停时显示完整内容
+fn render_cell(ui: &mut egui::Ui, width: f32, height: f32, field: &str) {
+    ui.allocate_ui_with_layout(egui::vec2(width, height), egui::Layout::left_to_right(egui::Align::Center), |ui| {
+        ui.set_min_width(width);
+        ui.add_space(4.0);
+        let text = field.replace(['\r', '\n'], "⏎");
+        let response = ui.add(egui::Label::new(text).truncate(true));
+        if response.rect.width() >= width - 8.0 || field.contains('\n') {
+            response.on_hover_text(field);
+        }
+    });
+}
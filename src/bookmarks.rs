@@ -0,0 +1,207 @@
+// 编码：UTF-8
+// 书签：收藏的文件夹和文件，以及每个文件中的行书签
+
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use crate::{config, folding};
+
+/// 书签配置文件名
+const BOOKMARKS_FILE: &str = "bookmarks.txt";
+
+/// 没有书签的文件返回的空集合
+static NO_LINES: BTreeSet<usize> = BTreeSet::new();
+
+/// 收藏和行书签（跨会话保存）
+#[derive(Default)]
+pub struct Bookmarks {
+    pub favourites: Vec<PathBuf>,             // 收藏的文件夹和文件（按添加顺序）
+    lines: HashMap<PathBuf, BTreeSet<usize>>, // 每个文件的书签行（从 0 开始）
+}
+
+impl Bookmarks {
+    /// 读取配置文件
+    ///
+    /// 格式：`[favourites]` 段每行一个路径，`[lines]` 段每行 `路径<Tab>行号,行号`
+    pub fn load() -> Self {
+        let mut bookmarks = Self::default();
+        let Ok(content) = std::fs::read_to_string(config::config_file(BOOKMARKS_FILE)) else {
+            return bookmarks;
+        };
+
+        let mut section = "";
+        for line in content.lines().filter(|line| !line.trim().is_empty()) {
+            match line.trim() {
+                "[favourites]" => section = "favourites",
+                "[lines]" => section = "lines",
+                _ if section == "favourites" => bookmarks.favourites.push(PathBuf::from(line)),
+                _ if section == "lines" => {
+                    let Some((path, numbers)) = line.rsplit_once('\t') else {
+                        continue;
+                    };
+                    let lines: BTreeSet<usize> = numbers.split(',').filter_map(|n| n.trim().parse().ok()).collect();
+                    if !lines.is_empty() {
+                        bookmarks.lines.insert(PathBuf::from(path), lines);
+                    }
+                }
+                _ => {}
+            }
+        }
+        bookmarks
+    }
+
+    /// 保存到配置文件
+    pub fn save(&self) -> std::io::Result<()> {
+        let mut content = String::from("[favourites]\n");
+        for path in &self.favourites {
+            content.push_str(&format!("{}\n", path.display()));
+        }
+
+        content.push_str("\n[lines]\n");
+        let mut files: Vec<_> = self.lines.iter().collect();
+        files.sort_by(|a, b| a.0.cmp(b.0));
+        for (path, lines) in files {
+            let numbers: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+            content.push_str(&format!("{}\t{}\n", path.display(), numbers.join(",")));
+        }
+
+        config::write_config_file(BOOKMARKS_FILE, &content)
+    }
+
+    pub fn is_favourite(&self, path: &Path) -> bool {
+        self.favourites.iter().any(|favourite| favourite == path)
+    }
+
+    /// 添加或移除收藏，返回操作后是否处于收藏状态
+    pub fn toggle_favourite(&mut self, path: &Path) -> bool {
+        if self.is_favourite(path) {
+            self.favourites.retain(|favourite| favourite != path);
+            false
+        } else {
+            self.favourites.push(path.to_path_buf());
+            true
+        }
+    }
+
+    /// 文件中的书签行（未保存的内容没有书签）
+    pub fn lines_of(&self, path: Option<&Path>) -> &BTreeSet<usize> {
+        path.and_then(|path| self.lines.get(path)).unwrap_or(&NO_LINES)
+    }
+
+    /// 添加或移除行书签，返回操作后该行是否有书签
+    pub fn toggle_line(&mut self, path: &Path, line: usize) -> bool {
+        let lines = self.lines.entry(path.to_path_buf()).or_default();
+        let added = lines.insert(line);
+        if !added {
+            lines.remove(&line);
+        }
+        if lines.is_empty() {
+            self.lines.remove(path);
+        }
+        added
+    }
+
+    /// 当前行之后的下一个书签（到末尾后从头开始）
+    pub fn next_line(&self, path: &Path, current: usize) -> Option<usize> {
+        let lines = self.lines_of(Some(path));
+        lines.range(current + 1..).next().or_else(|| lines.iter().next()).copied()
+    }
+
+    /// 当前行之前的上一个书签（到开头后从末尾开始）
+    pub fn previous_line(&self, path: &Path, current: usize) -> Option<usize> {
+        let lines = self.lines_of(Some(path));
+        lines.range(..current).next_back().or_else(|| lines.iter().next_back()).copied()
+    }
+
+    /// 文件内容修改后移动书签行：修改之前的行保持原位，修改之后的行按增减的行数移动；
+    /// 落在修改过的行中的书签留在原位，该处的行被删除时移除。返回书签是否有变化
+    /// old_lines / new_lines 为修改前后每行的哈希
+    pub fn remap_lines(&mut self, path: &Path, old_lines: &[u64], new_lines: &[u64]) -> bool {
+        let Some(lines) = self.lines.get_mut(path) else {
+            return false;
+        };
+        let (prefix, suffix) = folding::unchanged_lines(old_lines, new_lines);
+        let (old_tail, new_tail) = (old_lines.len() - suffix, new_lines.len() - suffix);
+        let remapped: BTreeSet<usize> = lines
+            .iter()
+            .filter_map(|&line| match line {
+                line if line < prefix => Some(line),
+                line if line >= old_tail => Some(new_tail + (line - old_tail)),
+                line => Some(line).filter(|&line| line < new_tail),
+            })
+            .collect();
+        if remapped == *lines {
+            return false;
+        }
+        if remapped.is_empty() {
+            self.lines.remove(path);
+        } else {
+            *lines = remapped;
+        }
+        true
+    }
+
+    /// 文件或文件夹重命名后更新书签中的路径
+    pub fn remap(&mut self, from: &Path, to: &Path) {
+        let remap = |path: &Path| path.strip_prefix(from).ok().map(|rest| to.join(rest));
+
+        for favourite in &mut self.favourites {
+            if let Some(new_path) = remap(favourite) {
+                *favourite = new_path;
+            }
+        }
+        self.lines = std::mem::take(&mut self.lines)
+            .into_iter()
+            .map(|(path, lines)| (remap(&path).unwrap_or(path), lines))
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bookmarks(lines: &[usize]) -> Bookmarks {
+        let mut bookmarks = Bookmarks::default();
+        for &line in lines {
+            bookmarks.toggle_line(Path::new("a.rs"), line);
+        }
+        bookmarks
+    }
+
+    fn lines_of(bookmarks: &Bookmarks) -> Vec<usize> {
+        bookmarks.lines_of(Some(Path::new("a.rs"))).iter().copied().collect()
+    }
+
+    #[test]
+    fn remap_lines_follows_inserted_and_deleted_lines() {
+        let path = Path::new("a.rs");
+        let mut marks = bookmarks(&[1, 3]);
+        // 在第 2 行之前插入两行
+        assert!(marks.remap_lines(path, &[0, 1, 2, 3], &[0, 1, 8, 9, 2, 3]));
+        assert_eq!(lines_of(&marks), [1, 5]);
+
+        // 删除书签之前的一行
+        assert!(marks.remap_lines(path, &[0, 1, 8, 9, 2, 3], &[1, 8, 9, 2, 3]));
+        assert_eq!(lines_of(&marks), [0, 4]);
+
+        // 书签之后的修改不影响书签
+        assert!(!marks.remap_lines(path, &[1, 8, 9, 2, 3], &[1, 8, 9, 2, 3, 4]));
+        assert_eq!(lines_of(&marks), [0, 4]);
+    }
+
+    #[test]
+    fn remap_lines_keeps_edited_lines_and_drops_deleted_ones() {
+        let path = Path::new("a.rs");
+        let mut marks = bookmarks(&[1, 2]);
+        // 修改书签所在的行
+        assert!(!marks.remap_lines(path, &[0, 1, 2], &[0, 7, 2]));
+        assert_eq!(lines_of(&marks), [1, 2]);
+
+        // 删除书签所在的行
+        assert!(marks.remap_lines(path, &[0, 7, 2], &[0, 2]));
+        assert_eq!(lines_of(&marks), [1]);
+
+        assert!(marks.remap_lines(path, &[0, 2], &[0]));
+        assert!(lines_of(&marks).is_empty());
+    }
+}
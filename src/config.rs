@@ -0,0 +1,29 @@
+// 编码：UTF-8
+// 用户配置目录：保存快捷键等用户自定义设置
+
+use std::path::PathBuf;
+
+/// 配置目录（Windows 下为 %APPDATA%\CodeNotebook，其他平台为 ~/.config/code_notebook）
+pub fn config_dir() -> PathBuf {
+    if let Some(app_data) = std::env::var_os("APPDATA") {
+        return PathBuf::from(app_data).join("CodeNotebook");
+    }
+    if let Some(xdg) = std::env::var_os("XDG_CONFIG_HOME") {
+        return PathBuf::from(xdg).join("code_notebook");
+    }
+    if let Some(home) = std::env::var_os("HOME") {
+        return PathBuf::from(home).join(".config").join("code_notebook");
+    }
+    PathBuf::from(".code_notebook")
+}
+
+/// 配置目录下的文件路径
+pub fn config_file(name: &str) -> PathBuf {
+    config_dir().join(name)
+}
+
+/// 写入配置文件（目录不存在时自动创建）
+pub fn write_config_file(name: &str, content: &str) -> std::io::Result<()> {
+    std::fs::create_dir_all(config_dir())?;
+    std::fs::write(config_file(name), content)
+}
@@ -0,0 +1,358 @@
+// 编码：UTF-8
+// 分隔符文本（CSV/TSV）：方言识别、记录索引、字段解析，以及后台筛选和排序
+
+use eframe::egui;
+use rayon::prelude::*;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::Arc;
+
+/// 识别方言时检查的行数
+const DETECT_LINES: usize = 50;
+/// 候选分隔符
+pub const DELIMITERS: &[u8] = b",\t;|";
+/// 候选引号
+const QUOTES: &[u8] = b"\"'";
+
+/// 分隔符和引号
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Dialect {
+    pub delimiter: u8,
+    pub quote: u8,
+}
+
+/// 分隔符的显示名称
+pub fn delimiter_name(delimiter: u8) -> &'static str {
+    match delimiter {
+        b',' => "逗号",
+        b'\t' => "制表符",
+        b';' => "分号",
+        b'|' => "竖线",
+        _ => "其他",
+    }
+}
+
+/// 按前几行识别方言：各行字段数一致的行最多的分隔符优先，其次是扩展名对应的默认分隔符
+pub fn detect(text: &str, default_delimiter: u8) -> Dialect {
+    let lines: Vec<&str> = text.lines().filter(|line| !line.trim().is_empty()).take(DETECT_LINES).collect();
+    let delimiter = DELIMITERS
+        .iter()
+        .copied()
+        .filter_map(|delimiter| {
+            let counts: Vec<usize> = lines.iter().map(|line| count_delimiters(line, delimiter, b'"')).collect();
+            let first = *counts.first().filter(|&&count| count > 0)?;
+            let consistent = counts.iter().filter(|&&count| count == first).count();
+            Some((consistent, delimiter == default_delimiter, first, delimiter))
+        })
+        .max()
+        .map_or(default_delimiter, |(.., delimiter)| delimiter);
+
+    // 引号：首尾都是该字符的字段最多者，默认为双引号
+    let quote = QUOTES
+        .iter()
+        .copied()
+        .max_by_key(|&quote| {
+            let quoted = lines
+                .iter()
+                .flat_map(|line| line.split(delimiter as char))
+                .filter(|field| {
+                    let field = field.trim().as_bytes();
+                    field.len() >= 2 && field[0] == quote && field[field.len() - 1] == quote
+                })
+                .count();
+            (quoted, quote == b'"')
+        })
+        .unwrap_or(b'"');
+    Dialect { delimiter, quote }
+}
+
+/// 一行中引号外的分隔符个数
+fn count_delimiters(line: &str, delimiter: u8, quote: u8) -> usize {
+    let mut in_quote = false;
+    line.bytes()
+        .filter(|&b| {
+            if b == quote {
+                in_quote = !in_quote;
+            }
+            !in_quote && b == delimiter
+        })
+        .count()
+}
+
+/// 记录索引：每条记录的起始位置（引号内的换行不分隔记录）
+pub struct Index {
+    starts: Vec<usize>, // 最后一项为文本长度
+    pub columns: usize, // 最多的字段数
+}
+
+impl Index {
+    /// 扫描全文建立索引（大文件在后台线程中调用）
+    pub fn build(text: &str, dialect: Dialect) -> Self {
+        let mut starts = vec![0];
+        let mut in_quote = false;
+        let mut fields = 1;
+        let mut columns = 0;
+        for (i, &b) in text.as_bytes().iter().enumerate() {
+            if b == dialect.quote {
+                in_quote = !in_quote;
+            } else if !in_quote && b == dialect.delimiter {
+                fields += 1;
+            } else if !in_quote && b == b'\n' {
+                columns = columns.max(fields);
+                fields = 1;
+                starts.push(i + 1);
+            }
+        }
+        if starts.last() != Some(&text.len()) {
+            columns = columns.max(fields);
+            starts.push(text.len());
+        }
+        Self { starts, columns }
+    }
+
+    /// 记录数
+    pub fn len(&self) -> usize {
+        self.starts.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 记录的原始文本（不含换行）
+    pub fn record<'a>(&self, text: &'a str, row: usize) -> &'a str {
+        text.get(self.starts[row]..self.starts[row + 1]).unwrap_or("").trim_end_matches(['\r', '\n'])
+    }
+
+    /// 记录所在的行（从 0 开始）
+    pub fn line_of(&self, text: &str, row: usize) -> usize {
+        text.as_bytes()[..self.starts[row]].iter().filter(|&&b| b == b'\n').count()
+    }
+}
+
+/// 拆分一条记录的字段（去掉引号，连续两个引号表示引号本身）
+pub fn parse_fields(record: &str, dialect: Dialect) -> Vec<Cow<'_, str>> {
+    let (delimiter, quote) = (dialect.delimiter as char, dialect.quote as char);
+    let mut fields = Vec::new();
+    let mut rest = record;
+    loop {
+        let Some(quoted) = rest.strip_prefix(quote) else {
+            match rest.find(delimiter) {
+                Some(end) => {
+                    fields.push(Cow::Borrowed(&rest[..end]));
+                    rest = &rest[end + 1..];
+                    continue;
+                }
+                None => {
+                    fields.push(Cow::Borrowed(rest));
+                    return fields;
+                }
+            }
+        };
+
+        let mut value = String::new();
+        let mut remaining = quoted;
+        loop {
+            match remaining.find(quote) {
+                Some(end) if remaining[end + 1..].starts_with(quote) => {
+                    value.push_str(&remaining[..=end]);
+                    remaining = &remaining[end + 2..];
+                }
+                Some(end) => {
+                    value.push_str(&remaining[..end]);
+                    remaining = &remaining[end + 1..];
+                    break;
+                }
+                None => {
+                    value.push_str(remaining);
+                    remaining = "";
+                    break;
+                }
+            }
+        }
+        // 右引号之后到分隔符之间的内容按原样保留
+        match remaining.find(delimiter) {
+            Some(end) => {
+                value.push_str(&remaining[..end]);
+                fields.push(Cow::Owned(value));
+                rest = &remaining[end + 1..];
+            }
+            None => {
+                value.push_str(remaining);
+                fields.push(Cow::Owned(value));
+                return fields;
+            }
+        }
+    }
+}
+
+/// 显示哪些记录以及按什么顺序
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RowQuery {
+    pub skip_header: bool,
+    pub filters: Vec<(usize, String)>, // (列, 包含的文本)，不区分大小写
+    pub sort: Option<(usize, bool)>,   // (列, 是否降序)
+}
+
+impl RowQuery {
+    /// 不筛选也不排序时直接按记录顺序显示，不需要计算
+    pub fn is_identity(&self) -> bool {
+        self.filters.is_empty() && self.sort.is_none()
+    }
+}
+
+/// 按条件计算显示的记录
+pub fn query_rows(text: &str, index: &Index, dialect: Dialect, query: &RowQuery) -> Vec<usize> {
+    let filters: Vec<(usize, String)> = query.filters.iter().map(|(column, text)| (*column, text.to_lowercase())).collect();
+    let mut rows: Vec<usize> = (usize::from(query.skip_header)..index.len())
+        .into_par_iter()
+        .filter(|&row| {
+            filters.is_empty() || {
+                let fields = parse_fields(index.record(text, row), dialect);
+                filters.iter().all(|(column, needle)| fields.get(*column).is_some_and(|field| field.to_lowercase().contains(needle)))
+            }
+        })
+        .collect();
+
+    if let Some((column, descending)) = query.sort {
+        let mut keys: Vec<(SortKey, usize)> = rows
+            .par_iter()
+            .map(|&row| (SortKey::new(parse_fields(index.record(text, row), dialect).get(column)), row))
+            .collect();
+        // 降序时空值仍排在最后
+        keys.par_sort_by(|(a, _), (b, _)| match (a, b) {
+            (SortKey::Empty, _) | (_, SortKey::Empty) => a.compare(b),
+            _ if descending => a.compare(b).reverse(),
+            _ => a.compare(b),
+        });
+        rows = keys.into_iter().map(|(_, row)| row).collect();
+    }
+    rows
+}
+
+/// 排序键：数字按数值比较并排在文本之前，空值排在最后
+enum SortKey {
+    Number(f64),
+    Text(String),
+    Empty,
+}
+
+impl SortKey {
+    fn new(field: Option<&Cow<'_, str>>) -> Self {
+        let field = field.map_or("", |field| field.trim());
+        if field.is_empty() {
+            SortKey::Empty
+        } else if let Ok(number) = field.parse::<f64>() {
+            SortKey::Number(number)
+        } else {
+            SortKey::Text(field.to_lowercase())
+        }
+    }
+
+    fn compare(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (SortKey::Number(a), SortKey::Number(b)) => a.total_cmp(b),
+            (SortKey::Text(a), SortKey::Text(b)) => a.cmp(b),
+            (SortKey::Number(_), _) | (SortKey::Text(_), SortKey::Empty) => Ordering::Less,
+            (SortKey::Empty, SortKey::Empty) => Ordering::Equal,
+            _ => Ordering::Greater,
+        }
+    }
+}
+
+/// 在后台线程中建立索引，完成后请求重绘
+pub fn spawn_index(ctx: egui::Context, text: Arc<str>, dialect: Dialect) -> Receiver<Index> {
+    let (sender, receiver) = channel();
+    std::thread::spawn(move || {
+        let _ = sender.send(Index::build(&text, dialect));
+        ctx.request_repaint();
+    });
+    receiver
+}
+
+/// 在后台线程中筛选和排序，完成后请求重绘
+pub fn spawn_query(ctx: egui::Context, text: Arc<str>, index: Arc<Index>, dialect: Dialect, query: RowQuery) -> Receiver<Vec<usize>> {
+    let (sender, receiver) = channel();
+    std::thread::spawn(move || {
+        let _ = sender.send(query_rows(&text, &index, dialect, &query));
+        ctx.request_repaint();
+    });
+    receiver
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CSV: Dialect = Dialect { delimiter: b',', quote: b'"' };
+
+    fn fields(record: &str) -> Vec<String> {
+        parse_fields(record, CSV).into_iter().map(Cow::into_owned).collect()
+    }
+
+    #[test]
+    fn parse_fields_handles_quotes() {
+        assert_eq!(fields("a,b,c"), ["a", "b", "c"]);
+        assert_eq!(fields("a,,"), ["a", "", ""]);
+        assert_eq!(fields(""), [""]);
+        assert_eq!(fields(r#""a,b",c"#), ["a,b", "c"]);
+        assert_eq!(fields(r#""say ""hi""",x"#), [r#"say "hi""#, "x"]);
+        assert_eq!(fields(r#""""#), [""]);
+        assert_eq!(fields("\"line1\nline2\",z"), ["line1\nline2", "z"]);
+        // 右引号之后的内容原样保留，缺少右引号时取到末尾
+        assert_eq!(fields(r#""a"b,c"#), ["ab", "c"]);
+        assert_eq!(fields(r#""open,end"#), ["open,end"]);
+    }
+
+    #[test]
+    fn parse_fields_uses_dialect() {
+        let dialect = Dialect { delimiter: b'\t', quote: b'\'' };
+        let fields: Vec<String> = parse_fields("'a\tb'\t'it''s'", dialect).into_iter().map(Cow::into_owned).collect();
+        assert_eq!(fields, ["a\tb", "it's"]);
+    }
+
+    #[test]
+    fn index_keeps_quoted_newlines_in_one_record() {
+        let text = "id,note\r\n1,\"two\nlines\"\r\n2,x,extra";
+        let index = Index::build(text, CSV);
+        assert_eq!(index.len(), 3);
+        assert_eq!(index.columns, 3);
+        assert_eq!(index.record(text, 0), "id,note");
+        assert_eq!(index.record(text, 1), "1,\"two\nlines\"");
+        assert_eq!(index.record(text, 2), "2,x,extra");
+        assert_eq!(index.line_of(text, 2), 3);
+    }
+
+    #[test]
+    fn index_with_trailing_newline() {
+        let text = "a,b\nc,d\n";
+        let index = Index::build(text, CSV);
+        assert_eq!(index.len(), 2);
+        assert_eq!(index.record(text, 1), "c,d");
+        assert!(Index::build("", CSV).is_empty());
+    }
+
+    fn query(text: &str, query: RowQuery) -> Vec<usize> {
+        query_rows(text, &Index::build(text, CSV), CSV, &query)
+    }
+
+    #[test]
+    fn sort_puts_numbers_first_and_empty_values_last() {
+        let text = "name,value\na,10\nb,\nc,9\nd,x\ne,-1";
+        let ascending = RowQuery { skip_header: true, sort: Some((1, false)), ..Default::default() };
+        assert_eq!(query(text, ascending), [5, 3, 1, 4, 2]);
+        let descending = RowQuery { skip_header: true, sort: Some((1, true)), ..Default::default() };
+        assert_eq!(query(text, descending), [4, 1, 3, 5, 2]);
+    }
+
+    #[test]
+    fn filters_are_case_insensitive_and_combined() {
+        let text = "name,city\nAlice,Paris\nbob,paris\nALICE,Rome\nCarol";
+        let filters = vec![(0, "alice".to_string()), (1, "PAR".to_string())];
+        assert_eq!(query(text, RowQuery { skip_header: true, filters, sort: None }), [1]);
+        // 缺少该列的记录不匹配
+        let filters = vec![(1, String::new())];
+        assert_eq!(query(text, RowQuery { skip_header: true, filters, sort: None }), [1, 2, 3]);
+    }
+}
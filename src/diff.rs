@@ -0,0 +1,197 @@
+// 编码：UTF-8
+// 文本比较：按行对齐两侧内容，并标出修改行中变化的片段
+
+use std::borrow::Cow;
+use std::ops::Range;
+use std::time::Duration;
+use similar::{ChangeTag, DiffTag, TextDiff};
+
+/// 比较超过该时间后退化为较粗的结果，避免大文件卡住界面
+const DIFF_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 一侧的一行
+pub struct DiffLine {
+    pub number: usize,             // 行号（从 0 开始）
+    pub text: String,              // 不含换行符
+    pub emphasis: Vec<Range<usize>>, // 行内变化的字节范围
+}
+
+/// 对齐后一行的类型
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RowKind {
+    Equal,    // 两侧相同
+    Deleted,  // 只在左侧
+    Inserted, // 只在右侧
+    Changed,  // 两侧都有但内容不同
+}
+
+/// 并排显示的一行
+pub struct DiffRow {
+    pub kind: RowKind,
+    pub left: Option<DiffLine>,
+    pub right: Option<DiffLine>,
+}
+
+/// 两段文本的比较结果
+pub struct FileDiff {
+    pub rows: Vec<DiffRow>,
+    pub hunks: Vec<usize>, // 每个变化块第一行在 rows 中的下标
+    pub added: usize,      // 新增的行数
+    pub removed: usize,    // 删除的行数
+}
+
+/// 按行比较，修改的行再按字符找出变化的片段
+pub fn diff_texts(old: &str, new: &str) -> FileDiff {
+    // 末尾是否有换行不影响最后一行的比较
+    let (old, new) = (with_final_newline(old), with_final_newline(new));
+    let diff = TextDiff::configure().timeout(DIFF_TIMEOUT).diff_lines(old.as_ref(), new.as_ref());
+    let mut result = FileDiff {
+        rows: Vec::new(),
+        hunks: Vec::new(),
+        added: 0,
+        removed: 0,
+    };
+
+    for op in diff.ops() {
+        if op.tag() == DiffTag::Equal {
+            for (old_index, new_index) in op.old_range().zip(op.new_range()) {
+                let line = |text: &str, number| DiffLine {
+                    number,
+                    text: trim_line_ending(text).to_string(),
+                    emphasis: Vec::new(),
+                };
+                result.rows.push(DiffRow {
+                    kind: RowKind::Equal,
+                    left: Some(line(diff.old_slices()[old_index], old_index)),
+                    right: Some(line(diff.new_slices()[new_index], new_index)),
+                });
+            }
+            continue;
+        }
+
+        // 相邻的变化操作属于同一个变化块
+        if !result.rows.last().is_some_and(|row| row.kind != RowKind::Equal) {
+            result.hunks.push(result.rows.len());
+        }
+
+        let mut deleted = Vec::new();
+        let mut inserted = Vec::new();
+        for change in diff.iter_inline_changes(op) {
+            let mut text = String::new();
+            let mut emphasis = Vec::new();
+            for (emphasized, value) in change.iter_strings_lossy() {
+                let start = text.len();
+                text.push_str(&value);
+                if emphasized {
+                    emphasis.push(start..text.len());
+                }
+            }
+            let len = trim_line_ending(&text).len();
+            text.truncate(len);
+            emphasis.retain_mut(|range| {
+                range.end = range.end.min(len);
+                range.start < range.end
+            });
+
+            match (change.tag(), change.old_index(), change.new_index()) {
+                (ChangeTag::Delete, Some(number), _) => deleted.push(DiffLine { number, text, emphasis }),
+                (ChangeTag::Insert, _, Some(number)) => inserted.push(DiffLine { number, text, emphasis }),
+                _ => {}
+            }
+        }
+
+        result.removed += deleted.len();
+        result.added += inserted.len();
+        let count = deleted.len().max(inserted.len());
+        let mut deleted = deleted.into_iter();
+        let mut inserted = inserted.into_iter();
+        for _ in 0..count {
+            let (left, right) = (deleted.next(), inserted.next());
+            let kind = match (&left, &right) {
+                (Some(_), Some(_)) => RowKind::Changed,
+                (Some(_), None) => RowKind::Deleted,
+                _ => RowKind::Inserted,
+            };
+            result.rows.push(DiffRow { kind, left, right });
+        }
+    }
+    result
+}
+
+fn trim_line_ending(line: &str) -> &str {
+    line.strip_suffix('\n').map_or(line, |line| line.strip_suffix('\r').unwrap_or(line))
+}
+
+fn with_final_newline(text: &str) -> Cow<'_, str> {
+    if text.is_empty() || text.ends_with('\n') {
+        Cow::Borrowed(text)
+    } else {
+        Cow::Owned(format!("{}\n", text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(diff: &FileDiff) -> Vec<RowKind> {
+        diff.rows.iter().map(|row| row.kind).collect()
+    }
+
+    fn emphasized(line: &DiffLine) -> Vec<&str> {
+        line.emphasis.iter().map(|range| &line.text[range.clone()]).collect()
+    }
+
+    #[test]
+    fn identical_texts_have_no_hunks() {
+        let diff = diff_texts("a\nb\n", "a\nb\n");
+        assert_eq!(kinds(&diff), [RowKind::Equal, RowKind::Equal]);
+        assert!(diff.hunks.is_empty());
+        assert_eq!((diff.added, diff.removed), (0, 0));
+        assert!(diff_texts("", "").rows.is_empty());
+    }
+
+    #[test]
+    fn adjacent_changes_form_one_hunk() {
+        let diff = diff_texts("a\nb\nc\nd\ne\n", "a\nB\nx\nc\nd\n");
+        use RowKind::*;
+        assert_eq!(kinds(&diff), [Equal, Changed, Inserted, Equal, Equal, Deleted]);
+        assert_eq!(diff.hunks, [1, 5]);
+        assert_eq!((diff.added, diff.removed), (2, 2));
+
+        let inserted = diff.rows[2].right.as_ref().unwrap();
+        assert_eq!((inserted.number, inserted.text.as_str()), (2, "x"));
+        assert!(diff.rows[2].left.is_none());
+        let deleted = diff.rows[5].left.as_ref().unwrap();
+        assert_eq!((deleted.number, deleted.text.as_str()), (4, "e"));
+    }
+
+    #[test]
+    fn missing_final_newline_does_not_change_the_last_line() {
+        let diff = diff_texts("a\nb", "a\nb\n");
+        assert_eq!(kinds(&diff), [RowKind::Equal, RowKind::Equal]);
+        assert_eq!(with_final_newline("a"), "a\n");
+        assert_eq!(with_final_newline("a\n"), "a\n");
+        assert_eq!(with_final_newline(""), "");
+    }
+
+    #[test]
+    fn changed_lines_emphasize_changed_words() {
+        let diff = diff_texts("let x = 1;\n", "let y = 1;\n");
+        assert_eq!(kinds(&diff), [RowKind::Changed]);
+        let row = &diff.rows[0];
+        assert_eq!(emphasized(row.left.as_ref().unwrap()), ["x"]);
+        assert_eq!(emphasized(row.right.as_ref().unwrap()), ["y"]);
+    }
+
+    #[test]
+    fn emphasis_is_trimmed_to_the_line_text() {
+        // 只有换行符不同时，去掉换行符后没有需要标出的片段
+        let diff = diff_texts("a\r\nb\n", "a\nb\n");
+        assert_eq!(kinds(&diff), [RowKind::Changed, RowKind::Equal]);
+        for line in [diff.rows[0].left.as_ref().unwrap(), diff.rows[0].right.as_ref().unwrap()] {
+            assert_eq!(line.text, "a");
+            assert!(line.emphasis.is_empty());
+        }
+    }
+}
@@ -0,0 +1,421 @@
+// 编码：UTF-8
+// 目录列表：读取目录内容，支持自然排序、隐藏文件和 .gitignore 过滤
+
+use std::cmp::Ordering;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use std::time::{SystemTime, UNIX_EPOCH};
+use eframe::egui;
+
+/// 超过此大小的文件不统计行数
+const MAX_LINE_COUNT_SIZE: u64 = 64 * 1024 * 1024;
+
+/// 目录中的一个条目
+#[derive(Clone, Default)]
+pub struct DirectoryItem {
+    pub name: String,   // 显示名称（非 UTF-8 名称有损显示，路径仍保持原样）
+    pub path: PathBuf,
+    pub is_directory: bool,
+    pub size: u64,                    // 文件大小（目录为 0）
+    pub modified: Option<SystemTime>, // 最后修改时间
+    pub permissions: String,          // 权限（Unix 为 rwxr-xr-x 形式）
+    pub symlink_target: Option<PathBuf>, // 符号链接指向的路径
+    pub error: Option<String>,        // 无法读取时的错误信息
+}
+
+/// 排序方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortKey {
+    Name,
+    Extension,
+    Size,
+    Modified,
+}
+
+impl SortKey {
+    pub const ALL: &'static [SortKey] = &[SortKey::Name, SortKey::Extension, SortKey::Size, SortKey::Modified];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            SortKey::Name => "名称",
+            SortKey::Extension => "扩展名",
+            SortKey::Size => "大小",
+            SortKey::Modified => "修改时间",
+        }
+    }
+}
+
+/// 目录列表选项
+#[derive(Clone, Debug, PartialEq)]
+pub struct ListingOptions {
+    pub sort_key: SortKey,
+    pub descending: bool,        // 倒序排列
+    pub show_hidden: bool,       // 显示隐藏文件（以 . 开头或带隐藏属性）
+    pub respect_gitignore: bool, // 隐藏被 .gitignore 忽略的条目
+}
+
+impl Default for ListingOptions {
+    fn default() -> Self {
+        Self {
+            sort_key: SortKey::Name,
+            descending: false,
+            show_hidden: false,
+            respect_gitignore: true,
+        }
+    }
+}
+
+/// 读取目录内容：目录在前，文件在后，各自按选项排序
+pub fn read_directory(dir: &Path, options: &ListingOptions) -> Vec<DirectoryItem> {
+    let walker = ignore::WalkBuilder::new(dir)
+        .max_depth(Some(1))
+        .hidden(!options.show_hidden)
+        .git_ignore(options.respect_gitignore)
+        .git_global(options.respect_gitignore)
+        .git_exclude(options.respect_gitignore)
+        .ignore(options.respect_gitignore)
+        .parents(options.respect_gitignore)
+        .require_git(false)
+        .build();
+
+    let (mut directories, mut files): (Vec<DirectoryItem>, Vec<DirectoryItem>) = walker
+        .filter_map(|result| match result {
+            Ok(entry) if entry.depth() == 1 => Some(read_item(entry.into_path())),
+            Ok(_) => None,
+            // 无法读取的条目也列出并标记，而不是直接丢弃
+            Err(err) => Some(error_item(dir, &err)),
+        })
+        .partition(|item| item.is_directory);
+
+    sort_items(&mut directories, options);
+    sort_items(&mut files, options);
+    directories.extend(files);
+    directories
+}
+
+/// 读取单个条目的元数据
+fn read_item(path: PathBuf) -> DirectoryItem {
+    let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    let symlink_target = std::fs::symlink_metadata(&path)
+        .is_ok_and(|m| m.file_type().is_symlink())
+        .then(|| std::fs::read_link(&path).ok())
+        .flatten();
+
+    // 符号链接使用目标的元数据
+    match std::fs::metadata(&path) {
+        Ok(metadata) => {
+            let is_directory = metadata.is_dir();
+            DirectoryItem {
+                name,
+                is_directory,
+                size: if is_directory { 0 } else { metadata.len() },
+                modified: metadata.modified().ok(),
+                permissions: format_permissions(&metadata.permissions()),
+                symlink_target,
+                error: None,
+                path,
+            }
+        }
+        Err(e) => {
+            let error = if symlink_target.is_some() { "链接目标不存在".to_string() } else { e.to_string() };
+            DirectoryItem { name, path, symlink_target, error: Some(error), ..Default::default() }
+        }
+    }
+}
+
+/// 遍历出错的条目；无法确定具体条目时（如目录本身不可读）以目录路径标记
+fn error_item(dir: &Path, err: &ignore::Error) -> DirectoryItem {
+    let path = error_path(err).map_or_else(|| dir.to_path_buf(), Path::to_path_buf);
+    let name = if path == dir {
+        "无法读取目录内容".to_string()
+    } else {
+        path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default()
+    };
+    DirectoryItem { name, path, error: Some(err.to_string()), ..Default::default() }
+}
+
+fn error_path(err: &ignore::Error) -> Option<&Path> {
+    match err {
+        ignore::Error::WithPath { path, .. } => Some(path),
+        ignore::Error::WithDepth { err, .. } | ignore::Error::WithLineNumber { err, .. } => error_path(err),
+        _ => None,
+    }
+}
+
+/// 权限显示文本
+#[cfg(unix)]
+fn format_permissions(permissions: &std::fs::Permissions) -> String {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = permissions.mode();
+    ["r", "w", "x"]
+        .iter()
+        .cycle()
+        .take(9)
+        .enumerate()
+        .map(|(bit, flag)| if mode & (0o400 >> bit) != 0 { *flag } else { "-" })
+        .collect()
+}
+
+#[cfg(not(unix))]
+fn format_permissions(permissions: &std::fs::Permissions) -> String {
+    if permissions.readonly() { "只读" } else { "读写" }.to_string()
+}
+
+/// 统计文件行数（超过大小上限或读取失败时返回 None）
+pub fn count_lines(path: &Path) -> Option<usize> {
+    let file = std::fs::File::open(path).ok()?;
+    if file.metadata().ok()?.len() > MAX_LINE_COUNT_SIZE {
+        return None;
+    }
+
+    let mut reader = std::io::BufReader::new(file);
+    let mut buffer = [0u8; 64 * 1024];
+    let mut lines = 0;
+    let mut last_byte = b'\n';
+    loop {
+        let read = reader.read(&mut buffer).ok()?;
+        if read == 0 {
+            break;
+        }
+        lines += buffer[..read].iter().filter(|&&b| b == b'\n').count();
+        last_byte = buffer[read - 1];
+    }
+    // 最后一行没有换行符时也计入
+    if last_byte != b'\n' {
+        lines += 1;
+    }
+    Some(lines)
+}
+
+/// 在后台线程中统计文件行数，完成后请求重绘
+pub fn spawn_count_lines(ctx: egui::Context, path: PathBuf) -> Receiver<Option<usize>> {
+    let (sender, receiver) = channel();
+    std::thread::spawn(move || {
+        let _ = sender.send(count_lines(&path));
+        ctx.request_repaint();
+    });
+    receiver
+}
+
+/// 文件大小显示文本
+pub fn format_size(size: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = size as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", size)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// 修改时间的简短显示：一个月内显示相对时间，更早显示日期
+pub fn format_modified(modified: SystemTime) -> String {
+    let Ok(elapsed) = SystemTime::now().duration_since(modified) else {
+        return format_timestamp(modified);
+    };
+    match elapsed.as_secs() {
+        0..=59 => "刚刚".to_string(),
+        secs @ 60..=3599 => format!("{} 分钟前", secs / 60),
+        secs @ 3600..=86_399 => format!("{} 小时前", secs / 3600),
+        secs @ 86_400..=2_591_999 => format!("{} 天前", secs / 86_400),
+        _ => format_timestamp(modified)[..10].to_string(),
+    }
+}
+
+/// 完整时间 YYYY-MM-DD HH:MM:SS（UTC）
+pub fn format_timestamp(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+
+    // 由天数推算公历日期（Howard Hinnant 的 civil_from_days 算法）
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year, month, day, rem / 3600, rem % 3600 / 60, rem % 60
+    )
+}
+
+fn sort_items(items: &mut [DirectoryItem], options: &ListingOptions) {
+    items.sort_by(|a, b| {
+        let ordering = match options.sort_key {
+            SortKey::Name => Ordering::Equal,
+            SortKey::Extension => extension_of(&a.name).cmp(&extension_of(&b.name)),
+            SortKey::Size => a.size.cmp(&b.size),
+            SortKey::Modified => a.modified.cmp(&b.modified),
+        }
+        .then_with(|| natural_cmp(&a.name, &b.name));
+
+        if options.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+}
+
+/// 小写扩展名（无扩展名或隐藏文件名返回空串）
+fn extension_of(name: &str) -> String {
+    match name.rfind('.') {
+        Some(dot) if dot > 0 => name[dot + 1..].to_lowercase(),
+        _ => String::new(),
+    }
+}
+
+/// 自然排序：忽略大小写，连续数字按数值比较（file2 排在 file10 之前）
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a_chars = a.chars().peekable();
+    let mut b_chars = b.chars().peekable();
+
+    loop {
+        match (a_chars.peek().copied(), b_chars.peek().copied()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let a_number = take_number(&mut a_chars);
+                let b_number = take_number(&mut b_chars);
+                // 去掉前导零后先比较位数再逐位比较，避免大数溢出
+                let a_digits = a_number.trim_start_matches('0');
+                let b_digits = b_number.trim_start_matches('0');
+                let ordering = a_digits
+                    .len()
+                    .cmp(&b_digits.len())
+                    .then_with(|| a_digits.cmp(b_digits))
+                    .then_with(|| a_number.len().cmp(&b_number.len()));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            (Some(x), Some(y)) => {
+                let ordering = x.to_lowercase().cmp(y.to_lowercase());
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                a_chars.next();
+                b_chars.next();
+            }
+        }
+    }
+}
+
+fn take_number(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    let mut number = String::new();
+    while let Some(c) = chars.next_if(|c| c.is_ascii_digit()) {
+        number.push(c);
+    }
+    number
+}
+
+/// 文件名是否匹配筛选条件：含 * 或 ? 时按通配符匹配，否则按子串匹配（均忽略大小写）
+pub fn matches_filter(name: &str, filter: &str) -> bool {
+    let filter = filter.trim();
+    if filter.is_empty() {
+        return true;
+    }
+
+    let name = name.to_lowercase();
+    let filter = filter.to_lowercase();
+    if filter.contains(['*', '?']) {
+        matches_glob(&name, &filter)
+    } else {
+        name.contains(&filter)
+    }
+}
+
+/// 通配符匹配（区分大小写，调用方负责统一大小写）
+pub fn matches_glob(name: &str, pattern: &str) -> bool {
+    let name: Vec<char> = name.chars().collect();
+    let pattern: Vec<char> = pattern.chars().collect();
+    wildcard_match(&pattern, &name)
+}
+
+/// 通配符匹配：* 匹配任意个字符，? 匹配单个字符
+fn wildcard_match(pattern: &[char], text: &[char]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None; // 最近的 * 位置及其已匹配到的文本位置
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(names: &[&str]) -> Vec<String> {
+        let mut names: Vec<String> = names.iter().map(|name| name.to_string()).collect();
+        names.sort_by(|a, b| natural_cmp(a, b));
+        names
+    }
+
+    #[test]
+    fn natural_cmp_orders_numbers_by_value() {
+        assert_eq!(sorted(&["file10", "file2", "file1"]), ["file1", "file2", "file10"]);
+        assert_eq!(sorted(&["v1.10", "v1.9", "v1.2"]), ["v1.2", "v1.9", "v1.10"]);
+        // 数值相同时前导零少的在前，超过 u64 的数字也能比较
+        assert_eq!(sorted(&["a007", "a7", "a07"]), ["a7", "a07", "a007"]);
+        assert_eq!(natural_cmp("x99999999999999999999999", "x100000000000000000000000"), Ordering::Less);
+    }
+
+    #[test]
+    fn natural_cmp_ignores_case_and_puts_prefixes_first() {
+        assert_eq!(sorted(&["b", "A", "a", "B"]), ["A", "a", "B", "b"]);
+        assert_eq!(natural_cmp("main", "main.rs"), Ordering::Less);
+        assert_eq!(natural_cmp("Readme", "readme"), "Readme".cmp("readme"));
+        assert_eq!(natural_cmp("", ""), Ordering::Equal);
+    }
+
+    #[test]
+    fn wildcard_patterns() {
+        assert!(matches_glob("main.rs", "*.rs"));
+        assert!(matches_glob("main.rs", "m??n.*"));
+        assert!(matches_glob("main.rs", "*"));
+        assert!(matches_glob("", "*"));
+        assert!(matches_glob("a.b.rs", "*.*.rs"));
+        assert!(matches_glob("abcbd", "*b*d"));
+        assert!(!matches_glob("main.rs", "*.toml"));
+        assert!(!matches_glob("main.rs", "main"));
+        assert!(!matches_glob("main.rs", "?"));
+        assert!(!matches_glob("Main.rs", "main*"));
+    }
+
+    #[test]
+    fn filter_matches_substrings_or_wildcards_ignoring_case() {
+        assert!(matches_filter("Cargo.toml", ""));
+        assert!(matches_filter("Cargo.toml", "  "));
+        assert!(matches_filter("Cargo.toml", "CARGO"));
+        assert!(matches_filter("Cargo.toml", "*.TOML"));
+        assert!(!matches_filter("Cargo.toml", "*.rs"));
+        assert!(!matches_filter("Cargo.toml", "lock"));
+    }
+}
@@ -0,0 +1,152 @@
+// 编码：UTF-8
+// .editorconfig：从文件所在目录向上查找配置，读取对该文件生效的制表符宽度
+
+use crate::directory_listing::matches_glob;
+use std::path::Path;
+
+/// 对文件生效的制表符宽度：tab_width，未设置时取数字形式的 indent_size
+/// 查找到 root = true 的配置为止；离文件越近的配置、同一配置中越靠后的节优先
+pub fn tab_width(path: &Path) -> Option<usize> {
+    let mut configs = Vec::new();
+    for dir in path.ancestors().skip(1) {
+        let Ok(text) = std::fs::read_to_string(dir.join(".editorconfig")) else {
+            continue;
+        };
+        let root = is_root(&text);
+        configs.push((dir, text));
+        if root {
+            break;
+        }
+    }
+
+    let mut tab_width = None;
+    let mut indent_size = None;
+    for (dir, text) in configs.iter().rev() {
+        let Ok(relative) = path.strip_prefix(dir) else {
+            continue;
+        };
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        let mut matched = false;
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with(['#', ';']) {
+                continue;
+            }
+            if let Some(section) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+                matched = section_matches(section, &relative);
+            } else if let (true, Some((key, value))) = (matched, line.split_once('=')) {
+                // 非数字的值（如 unset）清除之前的设置
+                let (value, width) = (value.trim(), value.trim().parse().ok().filter(|&width| width > 0));
+                match key.trim().to_ascii_lowercase().as_str() {
+                    "tab_width" => tab_width = width,
+                    // indent_size = tab：用制表符缩进，宽度取 tab_width
+                    "indent_size" if value.eq_ignore_ascii_case("tab") => indent_size = None,
+                    "indent_size" => indent_size = width,
+                    _ => {}
+                }
+            }
+        }
+    }
+    tab_width.or(indent_size)
+}
+
+/// 第一个节之前是否有 root = true
+fn is_root(text: &str) -> bool {
+    text.lines()
+        .map(str::trim)
+        .take_while(|line| !line.starts_with('['))
+        .filter_map(|line| line.split_once('='))
+        .any(|(key, value)| key.trim().eq_ignore_ascii_case("root") && value.trim().eq_ignore_ascii_case("true"))
+}
+
+/// 节名是否匹配文件：不含 / 的通配符匹配文件名，否则匹配相对于配置所在目录的路径
+fn section_matches(section: &str, relative: &str) -> bool {
+    let file_name = relative.rsplit('/').next().unwrap_or(relative);
+    expand_braces(section).iter().any(|pattern| match pattern.contains('/') {
+        true => matches_glob(relative, pattern.trim_start_matches('/')),
+        false => matches_glob(file_name, pattern),
+    })
+}
+
+/// 展开 {a,b} 形式的多选（支持嵌套和多组，如 {a,{b,c}}/{d,e}.rs）；没有配对的括号按字面匹配
+fn expand_braces(pattern: &str) -> Vec<String> {
+    let Some(open) = pattern.find('{') else {
+        return vec![pattern.to_string()];
+    };
+
+    // 找到与第一个 { 配对的 }，记录其中最外层的逗号
+    let mut depth = 0;
+    let mut commas = Vec::new();
+    let mut close = None;
+    for (index, c) in pattern.char_indices().skip_while(|&(index, _)| index < open) {
+        match c {
+            '{' => depth += 1,
+            '}' if depth == 1 => {
+                close = Some(index);
+                break;
+            }
+            '}' => depth -= 1,
+            ',' if depth == 1 => commas.push(index),
+            _ => {}
+        }
+    }
+    let Some(close) = close else {
+        return vec![pattern.to_string()];
+    };
+
+    let (prefix, suffix) = (&pattern[..open], &pattern[close + 1..]);
+    let bounds: Vec<usize> = std::iter::once(open).chain(commas).chain(std::iter::once(close)).collect();
+    bounds
+        .windows(2)
+        .flat_map(|pair| expand_braces(&format!("{}{}{}", prefix, &pattern[pair[0] + 1..pair[1]], suffix)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expand_braces_handles_nested_and_multiple_groups() {
+        assert_eq!(expand_braces("*.rs"), ["*.rs"]);
+        assert_eq!(expand_braces("*.{js,ts}"), ["*.js", "*.ts"]);
+        assert_eq!(expand_braces("{a,b}/{c,d}.rs"), ["a/c.rs", "a/d.rs", "b/c.rs", "b/d.rs"]);
+        assert_eq!(expand_braces("*.{a,{b,c}x}"), ["*.a", "*.bx", "*.cx"]);
+        // 没有配对的括号按字面保留
+        assert_eq!(expand_braces("*.{js"), ["*.{js"]);
+        assert_eq!(expand_braces("}{a,b}"), ["}a", "}b"]);
+    }
+
+    #[test]
+    fn section_matches_file_names_and_paths() {
+        assert!(section_matches("*", "src/main.rs"));
+        assert!(section_matches("*.{rs,toml}", "Cargo.toml"));
+        assert!(section_matches("src/*.rs", "src/main.rs"));
+        assert!(section_matches("/src/*.rs", "src/main.rs"));
+        assert!(!section_matches("src/*.rs", "tests/main.rs"));
+        assert!(section_matches("{src,tests}/{a,b}.rs", "tests/b.rs"));
+        assert!(!section_matches("*.md", "src/main.rs"));
+    }
+
+    #[test]
+    fn is_root_only_reads_the_preamble() {
+        assert!(is_root("root = true\n[*]\n"));
+        assert!(is_root("# comment\nROOT=TRUE\n"));
+        assert!(!is_root("[*]\nroot = true\n"));
+        assert!(!is_root("root = false\n"));
+    }
+
+    #[test]
+    fn tab_width_uses_nearest_config_and_indent_size_tab() {
+        let root = std::env::temp_dir().join(format!("code_notebook_editorconfig_{}", std::process::id()));
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join(".editorconfig"), "root = true\n[*]\nindent_size = 2\n[*.go]\nindent_size = tab\ntab_width = 8\n").unwrap();
+        assert_eq!(tab_width(&root.join("src").join("main.rs")), Some(2));
+        assert_eq!(tab_width(&root.join("main.go")), Some(8));
+
+        // 子目录中的配置优先；indent_size = tab 且没有 tab_width 时不设置宽度
+        std::fs::write(root.join("src").join(".editorconfig"), "[*.rs]\nindent_size = tab\n").unwrap();
+        assert_eq!(tab_width(&root.join("src").join("main.rs")), None);
+        assert_eq!(tab_width(&root.join("src").join("notes.txt")), Some(2));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,181 @@
+// 编码：UTF-8
+// 文件索引：后台遍历工作目录（遵循 .gitignore），供快速打开使用
+
+use rayon::prelude::*;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use crate::fuzzy::fuzzy_match;
+
+/// 索引文件数上限，避免在超大目录中占用过多内存
+const MAX_INDEXED_FILES: usize = 200_000;
+
+/// 每收集多少个文件向共享列表提交一次
+const BATCH_SIZE: usize = 1_000;
+
+/// 最近打开的文件获得的额外得分
+const RECENT_BONUS: i32 = 60;
+
+/// 匹配落在文件名上时的额外得分
+const FILE_NAME_BONUS: i32 = 30;
+
+/// 后台构建的文件索引
+pub struct FileIndex {
+    root: PathBuf,
+    files: Arc<Mutex<Vec<String>>>, // 相对于根目录的路径，统一使用 '/' 分隔
+    finished: Arc<AtomicBool>,
+    cancelled: Arc<AtomicBool>,
+    created_at: Instant,
+}
+
+/// 快速打开的排序结果
+pub struct RankedFile {
+    pub relative_path: String,
+    pub positions: Vec<usize>, // 命中字符在相对路径中的字符下标
+    pub score: i32,
+}
+
+impl FileIndex {
+    /// 在后台线程中开始索引指定目录
+    pub fn build(root: PathBuf) -> Self {
+        let files = Arc::new(Mutex::new(Vec::new()));
+        let finished = Arc::new(AtomicBool::new(false));
+        let cancelled = Arc::new(AtomicBool::new(false));
+
+        let thread_root = root.clone();
+        let thread_files = Arc::clone(&files);
+        let thread_finished = Arc::clone(&finished);
+        let thread_cancelled = Arc::clone(&cancelled);
+        std::thread::spawn(move || {
+            Self::walk(&thread_root, &thread_files, &thread_cancelled);
+            thread_finished.store(true, Ordering::Release);
+        });
+
+        Self {
+            root,
+            files,
+            finished,
+            cancelled,
+            created_at: Instant::now(),
+        }
+    }
+
+    /// 遍历目录，按批次提交结果
+    fn walk(root: &Path, files: &Mutex<Vec<String>>, cancelled: &AtomicBool) {
+        let mut batch = Vec::with_capacity(BATCH_SIZE);
+        let mut total = 0;
+
+        for entry in ignore::WalkBuilder::new(root).build().flatten() {
+            if cancelled.load(Ordering::Relaxed) || total >= MAX_INDEXED_FILES {
+                break;
+            }
+            if !entry.file_type().is_some_and(|t| t.is_file()) {
+                continue;
+            }
+            let Ok(relative) = entry.path().strip_prefix(root) else {
+                continue;
+            };
+
+            batch.push(relative.to_string_lossy().replace('\\', "/"));
+            total += 1;
+            if batch.len() >= BATCH_SIZE {
+                files.lock().unwrap().append(&mut batch);
+            }
+        }
+        files.lock().unwrap().append(&mut batch);
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// 索引是否已完成
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::Acquire)
+    }
+
+    /// 已索引的文件数
+    pub fn len(&self) -> usize {
+        self.files.lock().unwrap().len()
+    }
+
+    /// 索引创建至今的秒数
+    pub fn age_secs(&self) -> u64 {
+        self.created_at.elapsed().as_secs()
+    }
+
+    /// 按模糊匹配得分、文件名命中和最近使用排序，返回前 `limit` 项
+    pub fn rank(&self, query: &str, recent_files: &[PathBuf], limit: usize) -> Vec<RankedFile> {
+        let recent: Vec<String> = recent_files
+            .iter()
+            .filter_map(|path| path.strip_prefix(&self.root).ok())
+            .map(|relative| relative.to_string_lossy().replace('\\', "/"))
+            .collect();
+        let recent_bonus = |relative: &str| {
+            recent
+                .iter()
+                .position(|r| r == relative)
+                .map_or(0, |index| RECENT_BONUS - (index as i32 * 2).min(RECENT_BONUS))
+        };
+
+        // 无输入时只列出最近打开、仍然存在的文件（不必扫描整个索引）
+        if query.trim().is_empty() {
+            return recent
+                .iter()
+                .filter(|r| self.root.join(r).is_file())
+                .take(limit)
+                .map(|r| RankedFile { relative_path: r.clone(), positions: Vec::new(), score: 0 })
+                .collect();
+        }
+
+        let files = self.files.lock().unwrap();
+
+        let mut ranked: Vec<RankedFile> = files
+            .par_iter()
+            .filter_map(|relative| {
+                let path_match = fuzzy_match(query, relative)?;
+                let mut score = path_match.score;
+                let mut positions = path_match.positions;
+
+                // 匹配完全落在文件名内时优先，并使用文件名中的命中位置
+                let name_start = relative.rfind('/').map_or(0, |pos| pos + 1);
+                if let Some(name_match) = fuzzy_match(query, &relative[name_start..]) {
+                    let offset = relative[..name_start].chars().count();
+                    score = score.max(name_match.score) + FILE_NAME_BONUS;
+                    positions = name_match.positions.iter().map(|p| p + offset).collect();
+                }
+
+                // 层级越深略微降低得分
+                score -= relative.matches('/').count() as i32;
+                score += recent_bonus(relative);
+
+                Some(RankedFile { relative_path: relative.clone(), positions, score })
+            })
+            .collect();
+
+        ranked.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then_with(|| a.relative_path.len().cmp(&b.relative_path.len()))
+        });
+        ranked.truncate(limit);
+        ranked
+    }
+}
+
+impl Drop for FileIndex {
+    fn drop(&mut self) {
+        // 索引被替换时停止后台遍历
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+/// 向上查找包含 .git 的目录作为项目根目录，找不到时使用起始目录
+pub fn find_project_root(start: &Path) -> PathBuf {
+    start
+        .ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .unwrap_or(start)
+        .to_path_buf()
+}
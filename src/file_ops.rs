@@ -0,0 +1,92 @@
+// 编码：UTF-8
+// 文件操作：新建、重命名、复制、删除到回收站、在系统文件管理器中显示
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// 新建空文件（已存在时报错，不覆盖）
+pub fn create_file(path: &Path) -> io::Result<()> {
+    std::fs::OpenOptions::new().write(true).create_new(true).open(path)?;
+    Ok(())
+}
+
+/// 新建文件夹
+pub fn create_directory(path: &Path) -> io::Result<()> {
+    std::fs::create_dir(path)
+}
+
+/// 重命名文件或文件夹（目标已存在时报错）
+pub fn rename(from: &Path, to: &Path) -> io::Result<()> {
+    if to.exists() {
+        return Err(io::Error::new(io::ErrorKind::AlreadyExists, "目标已存在"));
+    }
+    std::fs::rename(from, to)
+}
+
+/// 在同一目录下创建副本，返回副本路径
+pub fn duplicate(path: &Path) -> io::Result<PathBuf> {
+    let target = duplicate_name(path);
+    if path.is_dir() {
+        copy_directory(path, &target)?;
+    } else {
+        std::fs::copy(path, &target)?;
+    }
+    Ok(target)
+}
+
+/// 副本名称：name - 副本.ext，重名时追加序号
+fn duplicate_name(path: &Path) -> PathBuf {
+    let parent = path.parent().unwrap_or(Path::new(""));
+    let is_file = path.is_file();
+    let stem = if is_file { path.file_stem() } else { path.file_name() }
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let extension = match path.extension() {
+        Some(ext) if is_file => format!(".{}", ext.to_string_lossy()),
+        _ => String::new(),
+    };
+
+    let mut candidate = parent.join(format!("{} - 副本{}", stem, extension));
+    let mut index = 2;
+    while candidate.exists() {
+        candidate = parent.join(format!("{} - 副本 ({}){}", stem, index, extension));
+        index += 1;
+    }
+    candidate
+}
+
+/// 递归复制文件夹
+fn copy_directory(from: &Path, to: &Path) -> io::Result<()> {
+    std::fs::create_dir(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_directory(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+/// 移到系统回收站
+pub fn move_to_trash(path: &Path) -> io::Result<()> {
+    trash::delete(path).map_err(|e| io::Error::other(e.to_string()))
+}
+
+/// 在系统文件管理器中显示（支持时选中该条目）
+pub fn reveal_in_file_manager(path: &Path) -> io::Result<()> {
+    #[cfg(target_os = "windows")]
+    let result = std::process::Command::new("explorer")
+        .arg(format!("/select,{}", path.display()))
+        .spawn();
+    #[cfg(target_os = "macos")]
+    let result = std::process::Command::new("open").arg("-R").arg(path).spawn();
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let result = std::process::Command::new("xdg-open")
+        .arg(path.parent().unwrap_or(path))
+        .spawn();
+
+    result.map(|_| ())
+}
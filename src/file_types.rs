@@ -0,0 +1,248 @@
+// 编码：UTF-8
+// 文件类型注册表：按文件名、通配符和扩展名关联图标、语言和查看方式
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::OnceLock;
+use crate::config;
+use crate::directory_listing::matches_glob;
+
+/// 用户自定义文件类型的配置文件名
+const FILE_TYPES_FILE: &str = "file_types.conf";
+
+/// 文件的查看方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Viewer {
+    Text,   // 按文本打开
+    Binary, // 二进制文件，不按文本解码
+    Image,  // 在图像查看器中打开
+}
+
+/// 一种文件类型
+#[derive(Clone, Debug)]
+pub struct FileType {
+    pub name: String,             // 显示名称（状态栏、悬停提示）
+    pub icon: String,             // 目录树中的图标
+    pub language: Option<String>, // 语法高亮使用的语言标识
+    pub viewer: Viewer,
+}
+
+/// 内置关联：(模式, 图标, 名称, 语言, 查看方式)
+/// 模式为 `*.ext` 时按扩展名匹配，含其他通配符时按通配符匹配，否则按完整文件名匹配
+const DEFAULT_ASSOCIATIONS: &[(&str, &str, &str, Option<&str>, Viewer)] = &[
+    // 完整文件名
+    ("Cargo.toml", "📦", "Cargo", Some("toml"), Viewer::Text),
+    ("Cargo.lock", "🔒", "Cargo 锁文件", Some("toml"), Viewer::Text),
+    ("Makefile", "🔧", "Makefile", Some("makefile"), Viewer::Text),
+    ("GNUmakefile", "🔧", "Makefile", Some("makefile"), Viewer::Text),
+    ("Dockerfile", "🐳", "Dockerfile", Some("dockerfile"), Viewer::Text),
+    ("docker-compose.yml", "🐳", "Docker Compose", Some("yaml"), Viewer::Text),
+    (".gitignore", "⚙️", "Git 忽略规则", Some("gitignore"), Viewer::Text),
+    (".gitattributes", "⚙️", "Git 属性", Some("gitignore"), Viewer::Text),
+    (".gitmodules", "⚙️", "Git 子模块", Some("gitignore"), Viewer::Text),
+    (".editorconfig", "⚙️", "EditorConfig", Some("gitignore"), Viewer::Text),
+    ("README.md", "📖", "Markdown", Some("markdown"), Viewer::Text),
+    ("LICENSE", "📜", "许可证", None, Viewer::Text),
+    // 通配符
+    ("Dockerfile.*", "🐳", "Dockerfile", Some("dockerfile"), Viewer::Text),
+    (".env*", "🔑", "环境变量", Some("shell"), Viewer::Text),
+    (".*rc", "⚙️", "配置", Some("shell"), Viewer::Text),
+    // 扩展名
+    ("*.rs", "🦀", "Rust", Some("rust"), Viewer::Text),
+    ("*.py", "🐍", "Python", Some("python"), Viewer::Text),
+    ("*.pyw", "🐍", "Python", Some("python"), Viewer::Text),
+    ("*.js", "🟨", "JavaScript", Some("javascript"), Viewer::Text),
+    ("*.mjs", "🟨", "JavaScript", Some("javascript"), Viewer::Text),
+    ("*.cjs", "🟨", "JavaScript", Some("javascript"), Viewer::Text),
+    ("*.jsx", "🟨", "JavaScript React", Some("javascript"), Viewer::Text),
+    ("*.ts", "🟦", "TypeScript", Some("typescript"), Viewer::Text),
+    ("*.tsx", "🟦", "TypeScript React", Some("typescript"), Viewer::Text),
+    ("*.d.ts", "🟦", "TypeScript 声明", Some("typescript"), Viewer::Text),
+    ("*.c", "📘", "C", Some("c"), Viewer::Text),
+    ("*.h", "📘", "C 头文件", Some("c"), Viewer::Text),
+    ("*.cpp", "📗", "C++", Some("cpp"), Viewer::Text),
+    ("*.cc", "📗", "C++", Some("cpp"), Viewer::Text),
+    ("*.cxx", "📗", "C++", Some("cpp"), Viewer::Text),
+    ("*.hpp", "📗", "C++ 头文件", Some("cpp"), Viewer::Text),
+    ("*.hh", "📗", "C++ 头文件", Some("cpp"), Viewer::Text),
+    ("*.java", "☕", "Java", Some("java"), Viewer::Text),
+    ("*.go", "🐹", "Go", Some("go"), Viewer::Text),
+    ("*.sh", "🐚", "Shell", Some("shell"), Viewer::Text),
+    ("*.bash", "🐚", "Shell", Some("shell"), Viewer::Text),
+    ("*.zsh", "🐚", "Shell", Some("shell"), Viewer::Text),
+    ("*.html", "🌐", "HTML", Some("html"), Viewer::Text),
+    ("*.htm", "🌐", "HTML", Some("html"), Viewer::Text),
+    ("*.css", "🎨", "CSS", Some("css"), Viewer::Text),
+    ("*.scss", "🎨", "SCSS", Some("css"), Viewer::Text),
+    ("*.less", "🎨", "Less", Some("css"), Viewer::Text),
+    ("*.json", "📄", "JSON", Some("json"), Viewer::Text),
+    ("*.xml", "📄", "XML", Some("xml"), Viewer::Text),
+    ("*.toml", "⚙️", "TOML", Some("toml"), Viewer::Text),
+    ("*.yaml", "⚙️", "YAML", Some("yaml"), Viewer::Text),
+    ("*.yml", "⚙️", "YAML", Some("yaml"), Viewer::Text),
+    ("*.ini", "⚙️", "INI", Some("ini"), Viewer::Text),
+    ("*.cfg", "⚙️", "配置", Some("ini"), Viewer::Text),
+    ("*.conf", "⚙️", "配置", Some("ini"), Viewer::Text),
+    ("*.sql", "🗃", "SQL", Some("sql"), Viewer::Text),
+    ("*.md", "📝", "Markdown", Some("markdown"), Viewer::Text),
+    ("*.markdown", "📝", "Markdown", Some("markdown"), Viewer::Text),
+    ("*.txt", "📄", "纯文本", None, Viewer::Text),
+    ("*.log", "📜", "日志", None, Viewer::Text),
+    ("*.csv", "📊", "CSV", Some("csv"), Viewer::Text),
+    ("*.tsv", "📊", "TSV", Some("tsv"), Viewer::Text),
+    ("*.png", "🖼", "PNG 图像", None, Viewer::Image),
+    ("*.jpg", "🖼", "JPEG 图像", None, Viewer::Image),
+    ("*.jpeg", "🖼", "JPEG 图像", None, Viewer::Image),
+    ("*.gif", "🖼", "GIF 图像", None, Viewer::Image),
+    ("*.bmp", "🖼", "BMP 图像", None, Viewer::Image),
+    ("*.ico", "🖼", "图标", None, Viewer::Image),
+    ("*.webp", "🖼", "WebP 图像", None, Viewer::Image),
+    ("*.zip", "📦", "ZIP 压缩包", None, Viewer::Binary),
+    ("*.7z", "📦", "7z 压缩包", None, Viewer::Binary),
+    ("*.rar", "📦", "RAR 压缩包", None, Viewer::Binary),
+    ("*.gz", "📦", "gzip 压缩包", None, Viewer::Binary),
+    ("*.tar", "📦", "tar 归档", None, Viewer::Binary),
+    ("*.exe", "💾", "可执行文件", None, Viewer::Binary),
+    ("*.dll", "💾", "动态链接库", None, Viewer::Binary),
+    ("*.so", "💾", "动态链接库", None, Viewer::Binary),
+    ("*.dylib", "💾", "动态链接库", None, Viewer::Binary),
+    ("*.o", "💾", "目标文件", None, Viewer::Binary),
+    ("*.obj", "💾", "目标文件", None, Viewer::Binary),
+    ("*.class", "💾", "Java 字节码", None, Viewer::Binary),
+    ("*.wasm", "💾", "WebAssembly", None, Viewer::Binary),
+    ("*.pdf", "📕", "PDF 文档", None, Viewer::Binary),
+];
+
+/// 文件类型注册表
+pub struct FileTypeRegistry {
+    file_names: HashMap<String, FileType>, // 完整文件名（小写）
+    globs: Vec<(String, FileType)>,        // 通配符（小写，按优先级排列）
+    extensions: HashMap<String, FileType>, // 扩展名（小写，不含点，可含多段如 d.ts）
+    default: FileType,
+}
+
+/// 全局注册表（首次使用时加载内置关联和用户配置）
+pub fn registry() -> &'static FileTypeRegistry {
+    static REGISTRY: OnceLock<FileTypeRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(FileTypeRegistry::load)
+}
+
+impl FileTypeRegistry {
+    /// 内置关联
+    pub fn with_defaults() -> Self {
+        let mut registry = Self {
+            file_names: HashMap::new(),
+            globs: Vec::new(),
+            extensions: HashMap::new(),
+            default: FileType {
+                name: "纯文本".to_string(),
+                icon: "📄".to_string(),
+                language: None,
+                viewer: Viewer::Text,
+            },
+        };
+
+        for &(pattern, icon, name, language, viewer) in DEFAULT_ASSOCIATIONS {
+            let file_type = FileType {
+                name: name.to_string(),
+                icon: icon.to_string(),
+                language: language.map(str::to_string),
+                viewer,
+            };
+            registry.register(pattern, file_type, false);
+        }
+        registry
+    }
+
+    /// 内置关联加上用户配置（用户配置优先）
+    ///
+    /// 配置格式：每行 `模式 = 图标 [语言] [binary|image]`，例如 `*.tpl = 🌐 html`
+    pub fn load() -> Self {
+        let mut registry = Self::with_defaults();
+        let Ok(content) = std::fs::read_to_string(config::config_file(FILE_TYPES_FILE)) else {
+            return registry;
+        };
+
+        for (line_number, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((pattern, value)) = line.split_once('=') else {
+                eprintln!("{} 第 {} 行格式错误: {}", FILE_TYPES_FILE, line_number + 1, line);
+                continue;
+            };
+
+            let mut parts = value.split_whitespace();
+            let Some(icon) = parts.next() else {
+                eprintln!("{} 第 {} 行缺少图标: {}", FILE_TYPES_FILE, line_number + 1, line);
+                continue;
+            };
+            let mut language = None;
+            let mut viewer = Viewer::Text;
+            for part in parts {
+                if part.eq_ignore_ascii_case("binary") {
+                    viewer = Viewer::Binary;
+                } else if part.eq_ignore_ascii_case("image") {
+                    viewer = Viewer::Image;
+                } else {
+                    language = Some(part.to_lowercase());
+                }
+            }
+
+            let file_type = FileType {
+                name: language.clone().unwrap_or_else(|| pattern.trim().to_string()),
+                icon: icon.to_string(),
+                language,
+                viewer,
+            };
+            registry.register(pattern.trim(), file_type, true);
+        }
+        registry
+    }
+
+    /// 注册一条关联；`override_existing` 为 true 时通配符优先于已有的通配符
+    fn register(&mut self, pattern: &str, file_type: FileType, override_existing: bool) {
+        let pattern = pattern.to_lowercase();
+        match pattern.strip_prefix("*.") {
+            Some(extension) if !extension.contains(['*', '?']) => {
+                self.extensions.insert(extension.to_string(), file_type);
+            }
+            _ if pattern.contains(['*', '?']) => {
+                if override_existing {
+                    self.globs.insert(0, (pattern, file_type));
+                } else {
+                    self.globs.push((pattern, file_type));
+                }
+            }
+            _ => {
+                self.file_names.insert(pattern, file_type);
+            }
+        }
+    }
+
+    /// 按文件名查找类型：完整文件名 > 通配符 > 扩展名（多段扩展名优先）
+    pub fn lookup(&self, file_name: &str) -> &FileType {
+        let name = file_name.to_lowercase();
+        if let Some(file_type) = self.file_names.get(&name) {
+            return file_type;
+        }
+        if let Some((_, file_type)) = self.globs.iter().find(|(glob, _)| matches_glob(&name, glob)) {
+            return file_type;
+        }
+
+        // 从第一个点开始依次尝试，使 a.d.ts 先匹配 d.ts 再匹配 ts；以点开头的文件名不视为扩展名
+        name.match_indices('.')
+            .filter(|&(index, _)| index > 0)
+            .find_map(|(index, _)| self.extensions.get(&name[index + 1..]))
+            .unwrap_or(&self.default)
+    }
+
+    /// 按路径查找类型
+    pub fn for_path(&self, path: &Path) -> &FileType {
+        match path.file_name() {
+            Some(name) => self.lookup(&name.to_string_lossy()),
+            None => &self.default,
+        }
+    }
+}
@@ -0,0 +1,313 @@
+// 编码：UTF-8
+// 代码折叠：按括号嵌套、缩进和 region 标记计算可折叠区域，以及折叠后可见行与源文件行的换算
+
+use std::collections::BTreeSet;
+use crate::indentation;
+
+/// 可折叠区域：折叠后只显示首行，隐藏 start + 1 ..= end
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FoldRegion {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// 区域的来源，同一行有多个区域时按此优先（大者优先）
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Source {
+    Indentation,
+    Bracket,
+    Marker,
+}
+
+/// region 标记（去掉行首空白后匹配）
+const REGION_START_MARKERS: &[&str] = &["#region", "// region", "//region", "// #region", "#pragma region", "<!-- #region"];
+const REGION_END_MARKERS: &[&str] = &["#endregion", "// endregion", "//endregion", "// #endregion", "#pragma endregion", "<!-- #endregion"];
+
+/// 计算可折叠区域（按首行排序，每行最多一个）；缩进中的制表符按 tab_width 列计算
+pub fn regions(text: &str, line_comment: Option<&str>, tab_width: usize) -> Vec<FoldRegion> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut found: Vec<(usize, Source, usize)> = Vec::new();
+
+    // region 标记：折叠到结束标记所在行
+    let mut markers = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_start();
+        if REGION_END_MARKERS.iter().any(|marker| trimmed.starts_with(marker)) {
+            if let Some(start) = markers.pop() {
+                found.push((start, Source::Marker, index));
+            }
+        } else if REGION_START_MARKERS.iter().any(|marker| trimmed.starts_with(marker)) {
+            markers.push(index);
+        }
+    }
+
+    // 括号：跨行的括号对，右括号在行首时该行保持可见
+    let mut stack: Vec<(u8, usize)> = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        let bytes = line.as_bytes();
+        let mut in_string = false;
+        let mut i = 0;
+        while i < bytes.len() {
+            let byte = bytes[i];
+            if in_string {
+                match byte {
+                    b'\\' => i += 1,
+                    b'"' => in_string = false,
+                    _ => {}
+                }
+            } else if line_comment.is_some_and(|prefix| bytes[i..].starts_with(prefix.as_bytes())) {
+                break;
+            } else {
+                match byte {
+                    b'"' => in_string = true,
+                    b'{' | b'[' | b'(' => stack.push((byte, index)),
+                    b'}' | b']' | b')' => {
+                        let open = match byte {
+                            b'}' => b'{',
+                            b']' => b'[',
+                            _ => b'(',
+                        };
+                        if let Some(position) = stack.iter().rposition(|&(b, _)| b == open) {
+                            let start = stack[position].1;
+                            stack.truncate(position);
+                            let end = match line.trim_start().as_bytes().first() == Some(&byte) {
+                                true => index.saturating_sub(1),
+                                false => index,
+                            };
+                            if end > start {
+                                found.push((start, Source::Bracket, end));
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            i += 1;
+        }
+    }
+
+    // 缩进：后续缩进更深的行（中间的空行不打断，末尾的空行不计入）
+    let indents: Vec<Option<usize>> = lines
+        .iter()
+        .map(|line| (!line.trim().is_empty()).then(|| indentation::leading_columns(line, tab_width)))
+        .collect();
+    for (start, indent) in indents.iter().enumerate() {
+        let Some(indent) = indent else {
+            continue;
+        };
+        let mut end = start;
+        for (index, other) in indents.iter().enumerate().skip(start + 1) {
+            match other {
+                None => continue,
+                Some(other) if other > indent => end = index,
+                Some(_) => break,
+            }
+        }
+        if end > start {
+            found.push((start, Source::Indentation, end));
+        }
+    }
+
+    found.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)).then(b.2.cmp(&a.2)));
+    found.dedup_by_key(|region| region.0);
+    found.into_iter().map(|(start, _, end)| FoldRegion { start, end }).collect()
+}
+
+/// 编辑前后开头相同的行数和末尾相同的行数（两段不重叠），其间为修改过的行
+pub fn unchanged_lines<T: PartialEq>(old: &[T], new: &[T]) -> (usize, usize) {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let limit = old.len().min(new.len()) - prefix;
+    let suffix = old.iter().rev().zip(new.iter().rev()).take(limit).take_while(|(a, b)| a == b).count();
+    (prefix, suffix)
+}
+
+/// 代码修改后仍然折叠的区域首行：修改之前的区域保持原位，修改之后的区域按增减的行数移动；
+/// 首行或末行落在修改过的行中、或移动后范围不同的区域取消折叠
+/// old_lines / new_lines 为修改前后每行的哈希
+pub fn remap_folded(
+    folded: &BTreeSet<usize>,
+    old_regions: &[FoldRegion],
+    new_regions: &[FoldRegion],
+    old_lines: &[u64],
+    new_lines: &[u64],
+) -> BTreeSet<usize> {
+    let (prefix, suffix) = unchanged_lines(old_lines, new_lines);
+    let old_tail = old_lines.len() - suffix;
+    let map = |line: usize| match line {
+        line if line < prefix => Some(line),
+        line if line >= old_tail => Some(new_lines.len() - suffix + (line - old_tail)),
+        _ => None,
+    };
+    let region_at = |regions: &[FoldRegion], start: usize| {
+        regions.binary_search_by_key(&start, |region| region.start).ok().map(|index| regions[index])
+    };
+    folded
+        .iter()
+        .filter_map(|&start| {
+            let old = region_at(old_regions, start)?;
+            let (start, end) = (map(old.start)?, map(old.end)?);
+            region_at(new_regions, start).filter(|region| region.end == end).map(|_| start)
+        })
+        .collect()
+}
+
+/// 折叠后隐藏的行区间（首尾都包含，按起始行排序；折叠在已隐藏区域内的区域不再单独计算）
+pub fn hidden_ranges(regions: &[FoldRegion], folded: &BTreeSet<usize>) -> Vec<(usize, usize)> {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for region in regions.iter().filter(|region| folded.contains(&region.start)) {
+        if ranges.last().is_some_and(|&(_, end)| region.start <= end) {
+            continue;
+        }
+        ranges.push((region.start + 1, region.end));
+    }
+    ranges
+}
+
+/// 折叠后的可见行数
+pub fn visible_count(total_lines: usize, hidden: &[(usize, usize)]) -> usize {
+    total_lines - hidden.iter().map(|&(start, end)| end - start + 1).sum::<usize>()
+}
+
+/// 第 row 个可见行对应的源文件行
+pub fn row_to_line(row: usize, hidden: &[(usize, usize)]) -> usize {
+    let mut line = row;
+    for &(start, end) in hidden {
+        if start > line {
+            break;
+        }
+        line += end - start + 1;
+    }
+    line
+}
+
+/// 源文件行对应的可见行（隐藏的行对应其所在折叠的首行）
+pub fn line_to_row(line: usize, hidden: &[(usize, usize)]) -> usize {
+    let mut hidden_before = 0;
+    for &(start, end) in hidden {
+        if end < line {
+            hidden_before += end - start + 1;
+        } else if start <= line {
+            return start - 1 - hidden_before;
+        } else {
+            break;
+        }
+    }
+    line - hidden_before
+}
+
+/// 下一个可见行（跳过以 line 为首行的折叠）
+pub fn next_visible_line(line: usize, hidden: &[(usize, usize)]) -> usize {
+    match hidden.binary_search_by_key(&(line + 1), |&(start, _)| start) {
+        Ok(index) => hidden[index].1 + 1,
+        Err(_) => line + 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    fn region(start: usize, end: usize) -> FoldRegion {
+        FoldRegion { start, end }
+    }
+
+    fn line_hashes(text: &str) -> Vec<u64> {
+        text.lines()
+            .map(|line| {
+                let mut hasher = DefaultHasher::new();
+                line.hash(&mut hasher);
+                hasher.finish()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn regions_from_brackets_and_indentation() {
+        let text = "fn a() {\n    x;\n}\nif y:\n\tz\n\tw\nend";
+        assert_eq!(regions(text, Some("//"), 4), [region(0, 1), region(3, 5)]);
+        // 括号在注释和字符串中不计入
+        let text = "a // {\nb \"{\"\nc";
+        assert!(regions(text, Some("//"), 4).is_empty());
+    }
+
+    #[test]
+    fn indentation_regions_use_tab_width() {
+        // 制表符宽 2 列时，两个空格与一个制表符同级
+        let text = "a\n  b\n\tc";
+        assert_eq!(regions(text, None, 2), [region(0, 2)]);
+        assert_eq!(regions(text, None, 4), [region(0, 2), region(1, 2)]);
+    }
+
+    #[test]
+    fn nested_folds_inside_hidden_ranges_are_skipped() {
+        let regions = [region(0, 5), region(2, 4), region(7, 9)];
+        let folded = BTreeSet::from([0, 2, 7]);
+        assert_eq!(hidden_ranges(&regions, &folded), [(1, 5), (8, 9)]);
+        assert!(hidden_ranges(&regions, &BTreeSet::new()).is_empty());
+    }
+
+    #[test]
+    fn rows_and_lines_round_trip() {
+        let hidden = [(1, 5), (8, 9)];
+        let total = 12;
+        let visible = visible_count(total, &hidden);
+        assert_eq!(visible, 5);
+
+        let lines: Vec<usize> = (0..visible).map(|row| row_to_line(row, &hidden)).collect();
+        assert_eq!(lines, [0, 6, 7, 10, 11]);
+        for (row, &line) in lines.iter().enumerate() {
+            assert_eq!(line_to_row(line, &hidden), row);
+        }
+        // 隐藏的行对应其所在折叠的首行
+        assert_eq!(line_to_row(3, &hidden), 0);
+        assert_eq!(line_to_row(9, &hidden), 2);
+
+        let mut line = 0;
+        let mut walked = vec![line];
+        while line < total {
+            line = next_visible_line(line, &hidden);
+            walked.push(line);
+        }
+        assert_eq!(walked, [0, 6, 7, 10, 11, 12]);
+    }
+
+    #[test]
+    fn round_trip_without_hidden_ranges() {
+        for line in 0..5 {
+            assert_eq!(row_to_line(line, &[]), line);
+            assert_eq!(line_to_row(line, &[]), line);
+            assert_eq!(next_visible_line(line, &[]), line + 1);
+        }
+    }
+
+    #[test]
+    fn unchanged_lines_do_not_overlap() {
+        assert_eq!(unchanged_lines(&[1, 2, 3], &[1, 2, 3]), (3, 0));
+        assert_eq!(unchanged_lines(&[1, 2, 3], &[0, 1, 2, 3]), (0, 3));
+        assert_eq!(unchanged_lines(&[1, 1, 1], &[1, 1]), (2, 0));
+        assert_eq!(unchanged_lines(&[1, 2, 3], &[1, 9, 3]), (1, 1));
+    }
+
+    #[test]
+    fn folds_move_with_inserted_and_deleted_lines() {
+        let old_text = "fn a() {\n    x;\n}\nfn b() {\n    y;\n    w;\n}";
+        let old_regions = regions(old_text, Some("//"), 4);
+        assert_eq!(old_regions, [region(0, 1), region(3, 5)]);
+        let folded = BTreeSet::from([3]);
+        let remap = |new_text: &str| {
+            remap_folded(&folded, &old_regions, &regions(new_text, Some("//"), 4), &line_hashes(old_text), &line_hashes(new_text))
+        };
+
+        // 上方插入一行：折叠随之下移，而不是落到新的第 3 行所在的区域
+        assert_eq!(remap("// c\nfn a() {\n    x;\n}\nfn b() {\n    y;\n    w;\n}"), BTreeSet::from([4]));
+        // 上方删除区域
+        assert_eq!(remap("fn b() {\n    y;\n    w;\n}"), BTreeSet::from([0]));
+        // 区域中间的行被修改但范围不变时保持折叠
+        assert_eq!(remap("fn a() {\n    x;\n}\nfn b() {\n    z;\n    w;\n}"), BTreeSet::from([3]));
+        // 首行被修改、或范围变化时取消折叠
+        assert!(remap("fn a() {\n    x;\n}\nfn c() {\n    y;\n    w;\n}").is_empty());
+        assert!(remap("fn a() {\n    x;\n}\nfn b() {\n    y;\n    w;\n    z;\n}").is_empty());
+    }
+}
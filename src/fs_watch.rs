@@ -0,0 +1,91 @@
+// 编码：UTF-8
+// 文件系统监视：目录内容变化时刷新列表，打开的文件被外部修改时重新加载
+
+use eframe::egui;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Receiver};
+
+/// 监视到的变化
+#[derive(Debug, PartialEq)]
+pub enum FsChange {
+    FileModified(PathBuf),     // 文件内容被修改
+    DirectoryChanged(PathBuf), // 目录中有条目被创建、删除或重命名
+}
+
+/// 文件系统监视器（所有目录均非递归监视）
+pub struct FsWatcher {
+    watcher: Option<RecommendedWatcher>,
+    events: Receiver<notify::Result<notify::Event>>,
+    watched: HashSet<PathBuf>,
+}
+
+impl FsWatcher {
+    /// 创建监视器，收到事件时请求重绘以便及时处理
+    pub fn new(ctx: egui::Context) -> Self {
+        let (sender, events) = channel();
+        let watcher = notify::recommended_watcher(move |event| {
+            let _ = sender.send(event);
+            ctx.request_repaint();
+        });
+
+        if let Err(e) = &watcher {
+            eprintln!("无法创建文件监视器: {}", e);
+        }
+
+        Self {
+            watcher: watcher.ok(),
+            events,
+            watched: HashSet::new(),
+        }
+    }
+
+    /// 更新监视的目录集合：新增的开始监视，不再需要的取消监视
+    pub fn set_watched_directories(&mut self, directories: HashSet<PathBuf>) {
+        let Some(watcher) = &mut self.watcher else {
+            return;
+        };
+
+        for removed in self.watched.difference(&directories) {
+            let _ = watcher.unwatch(removed);
+        }
+        for added in directories.difference(&self.watched) {
+            let _ = watcher.watch(added, RecursiveMode::NonRecursive);
+        }
+        self.watched = directories;
+    }
+
+    /// 取出自上次调用以来的所有变化（已去重）
+    pub fn poll(&mut self) -> Vec<FsChange> {
+        let mut changes = Vec::new();
+
+        for event in self.events.try_iter().flatten() {
+            for path in &event.paths {
+                let change = match event.kind {
+                    EventKind::Modify(notify::event::ModifyKind::Name(_))
+                    | EventKind::Create(_)
+                    | EventKind::Remove(_) => path.parent().map(|dir| FsChange::DirectoryChanged(dir.to_path_buf())),
+                    EventKind::Modify(_) => Some(FsChange::FileModified(path.clone())),
+                    _ => None,
+                };
+
+                // 通过重命名覆盖（原子保存）的文件也视为内容修改
+                if matches!(event.kind, EventKind::Modify(notify::event::ModifyKind::Name(_)) | EventKind::Create(_)) {
+                    let modified = FsChange::FileModified(path.clone());
+                    if !changes.contains(&modified) {
+                        changes.push(modified);
+                    }
+                }
+
+                if let Some(change) = change {
+                    if !changes.contains(&change) {
+                        changes.push(change);
+                    }
+                }
+            }
+        }
+
+        changes
+    }
+}
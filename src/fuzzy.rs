@@ -0,0 +1,136 @@
+// 编码：UTF-8
+// 模糊匹配：用于命令面板等需要按缩写查找条目的场景
+
+/// 模糊匹配结果
+#[derive(Clone, Debug)]
+pub struct FuzzyMatch {
+    pub score: i32,            // 匹配得分，越高越相关
+    pub positions: Vec<usize>, // 命中字符在候选串中的字符下标
+}
+
+// 评分参数
+const SCORE_MATCH: i32 = 16;
+const BONUS_CONSECUTIVE: i32 = 15;
+const BONUS_WORD_START: i32 = 10;
+const BONUS_FIRST_CHAR: i32 = 8;
+const PENALTY_GAP: i32 = 1;
+
+/// 对候选串进行模糊匹配（大小写不敏感），模式中的每个字符需按顺序出现
+pub fn fuzzy_match(pattern: &str, candidate: &str) -> Option<FuzzyMatch> {
+    let pattern: Vec<char> = pattern
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    if pattern.is_empty() {
+        return Some(FuzzyMatch { score: 0, positions: Vec::new() });
+    }
+
+    let original: Vec<char> = candidate.chars().collect();
+    let lowered: Vec<char> = original
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+
+    // 从每个可能的起点做一次贪心匹配，取得分最高者
+    let mut best: Option<FuzzyMatch> = None;
+    for start in 0..lowered.len() {
+        if lowered[start] != pattern[0] {
+            continue;
+        }
+        if let Some(m) = match_from(&pattern, &original, &lowered, start) {
+            let is_better = match &best {
+                Some(b) => m.score > b.score,
+                None => true,
+            };
+            if is_better {
+                best = Some(m);
+            }
+        }
+    }
+    best
+}
+
+/// 从指定位置开始贪心匹配并计算得分
+fn match_from(pattern: &[char], original: &[char], lowered: &[char], start: usize) -> Option<FuzzyMatch> {
+    let mut positions = Vec::with_capacity(pattern.len());
+    let mut score = 0;
+    let mut idx = start;
+
+    for &pc in pattern {
+        while idx < lowered.len() && lowered[idx] != pc {
+            idx += 1;
+        }
+        if idx >= lowered.len() {
+            return None;
+        }
+
+        score += SCORE_MATCH;
+        if is_word_start(original, idx) {
+            score += BONUS_WORD_START;
+        }
+        if idx == 0 {
+            score += BONUS_FIRST_CHAR;
+        }
+        match positions.last() {
+            Some(&last) if last + 1 == idx => score += BONUS_CONSECUTIVE,
+            Some(&last) => score -= PENALTY_GAP * (idx - last - 1) as i32,
+            None => score -= PENALTY_GAP * idx.min(10) as i32,
+        }
+
+        positions.push(idx);
+        idx += 1;
+    }
+
+    Some(FuzzyMatch { score, positions })
+}
+
+/// 判断字符是否位于单词开头（分隔符之后或驼峰大写处）
+fn is_word_start(chars: &[char], idx: usize) -> bool {
+    if idx == 0 {
+        return true;
+    }
+    let prev = chars[idx - 1];
+    let cur = chars[idx];
+    matches!(prev, ' ' | '_' | '-' | '.' | '/' | '\\' | ':')
+        || (prev.is_lowercase() && cur.is_uppercase())
+        || (!prev.is_alphanumeric() && cur.is_alphanumeric())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_characters_in_order_ignoring_case() {
+        assert_eq!(fuzzy_match("mr", "main.rs").unwrap().positions, [0, 5]);
+        assert_eq!(fuzzy_match("MAIN", "main.rs").unwrap().positions, [0, 1, 2, 3]);
+        assert!(fuzzy_match("rm", "main.rs").is_none());
+        assert!(fuzzy_match("mainx", "main.rs").is_none());
+        // 空白被忽略，空模式匹配任何候选
+        assert_eq!(fuzzy_match("m r", "main.rs").unwrap().positions, [0, 5]);
+        assert_eq!(fuzzy_match("", "main.rs").unwrap().score, 0);
+    }
+
+    #[test]
+    fn prefers_word_starts_and_consecutive_runs() {
+        let score = |pattern, candidate| fuzzy_match(pattern, candidate).unwrap().score;
+        assert!(score("fb", "file_browser") > score("fb", "fableb"));
+        assert!(score("ui", "ui/mod.rs") > score("ui", "build.rs"));
+        assert!(score("lay", "layout.rs") > score("lay", "l_a_y.rs"));
+    }
+
+    #[test]
+    fn picks_the_best_starting_point() {
+        // 第二个 "lay" 位于单词开头且连续，优于从第一个 l 开始的匹配
+        assert_eq!(fuzzy_match("lay", "ls/layout").unwrap().positions, [3, 4, 5]);
+        assert_eq!(fuzzy_match("cv", "commit_view").unwrap().positions, [0, 7]);
+    }
+
+    #[test]
+    fn camel_case_humps_are_word_starts() {
+        assert!(is_word_start(&['f', 'B'], 1));
+        assert!(!is_word_start(&['f', 'b'], 1));
+        assert!(is_word_start(&['.', 'r'], 1));
+    }
+}
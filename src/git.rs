@@ -0,0 +1,364 @@
+// 编码：UTF-8
+// Git 集成：仓库检测、文件状态、当前分支和与 HEAD 比较的行变化
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use eframe::egui;
+use encoding_rs::Encoding;
+use git2::{DiffFormat, DiffOptions, ErrorCode, Oid, Patch, Repository, Status, StatusOptions};
+use crate::text_encoding;
+
+/// 文件在工作区中的 Git 状态
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FileStatus {
+    Ignored,    // 被 .gitignore 忽略
+    Untracked,  // 未跟踪
+    Added,      // 已暂存的新文件
+    Modified,   // 已修改（含重命名、删除和类型变化）
+    Conflicted, // 合并冲突
+}
+
+impl FileStatus {
+    /// 目录树中显示的状态字母
+    pub fn letter(&self) -> &'static str {
+        match self {
+            FileStatus::Ignored => "!",
+            FileStatus::Untracked => "U",
+            FileStatus::Added => "A",
+            FileStatus::Modified => "M",
+            FileStatus::Conflicted => "C",
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            FileStatus::Ignored => "已忽略",
+            FileStatus::Untracked => "未跟踪",
+            FileStatus::Added => "已添加",
+            FileStatus::Modified => "已修改",
+            FileStatus::Conflicted => "冲突",
+        }
+    }
+
+    fn from_flags(flags: Status) -> Option<Self> {
+        if flags.is_conflicted() {
+            Some(FileStatus::Conflicted)
+        } else if flags.is_ignored() {
+            Some(FileStatus::Ignored)
+        } else if flags.is_wt_new() {
+            Some(FileStatus::Untracked)
+        } else if flags.is_index_new() {
+            Some(FileStatus::Added)
+        } else if flags.intersects(
+            Status::INDEX_MODIFIED
+                | Status::INDEX_DELETED
+                | Status::INDEX_RENAMED
+                | Status::INDEX_TYPECHANGE
+                | Status::WT_MODIFIED
+                | Status::WT_DELETED
+                | Status::WT_RENAMED
+                | Status::WT_TYPECHANGE,
+        ) {
+            Some(FileStatus::Modified)
+        } else {
+            None
+        }
+    }
+}
+
+/// 行相对 HEAD 的变化
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineChange {
+    Added,    // 新增的行
+    Modified, // 修改的行
+    Deleted,  // 该行之前有被删除的行
+}
+
+/// 打开的 Git 仓库
+pub struct GitRepository {
+    repo: Repository,
+    workdir: PathBuf,
+    statuses: HashMap<PathBuf, FileStatus>,    // 有变化的条目（绝对路径）
+    directories: HashMap<PathBuf, FileStatus>, // 包含变化的目录及其中最重要的状态
+    branch: Option<String>,
+    head_blobs: HashMap<PathBuf, Option<Vec<u8>>>, // HEAD 中的文件内容缓存（不在 HEAD 中为 None）
+}
+
+impl GitRepository {
+    /// 查找包含指定目录的仓库（裸仓库不支持）
+    pub fn discover(dir: &Path) -> Option<Self> {
+        let repo = Repository::discover(dir).ok()?;
+        let workdir = repo.workdir()?.to_path_buf();
+        // 与目录树中的路径保持一致（去掉 discover 返回的末尾分隔符）
+        let workdir = workdir.components().collect::<PathBuf>();
+        let mut repository = Self {
+            repo,
+            workdir,
+            statuses: HashMap::new(),
+            directories: HashMap::new(),
+            branch: None,
+            head_blobs: HashMap::new(),
+        };
+        repository.refresh();
+        Some(repository)
+    }
+
+    pub fn workdir(&self) -> &Path {
+        &self.workdir
+    }
+
+    /// 当前分支名（未提交的新仓库也能显示分支名，分离 HEAD 时显示提交号）
+    pub fn branch(&self) -> Option<&str> {
+        self.branch.as_deref()
+    }
+
+    /// 重新读取文件状态和分支
+    pub fn refresh(&mut self) {
+        self.branch = self.read_branch();
+        self.head_blobs.clear();
+        self.statuses.clear();
+        self.directories.clear();
+
+        let mut options = StatusOptions::new();
+        options
+            .include_untracked(true)
+            .include_ignored(true)
+            .recurse_untracked_dirs(false)
+            .recurse_ignored_dirs(false);
+        let Ok(entries) = self.repo.statuses(Some(&mut options)) else {
+            return;
+        };
+
+        for entry in entries.iter() {
+            let (Some(relative), Some(status)) = (entry.path(), FileStatus::from_flags(entry.status())) else {
+                continue;
+            };
+            let path = self.workdir.join(relative.trim_end_matches('/'));
+
+            // 上级目录显示其中最重要的状态（忽略的条目不影响上级）
+            if status != FileStatus::Ignored {
+                for ancestor in path.ancestors().skip(1).take_while(|dir| dir.starts_with(&self.workdir)) {
+                    let current = self.directories.entry(ancestor.to_path_buf()).or_insert(status);
+                    *current = (*current).max(status);
+                }
+            }
+            self.statuses.insert(path, status);
+        }
+    }
+
+    fn read_branch(&self) -> Option<String> {
+        let head = self.repo.find_reference("HEAD").ok()?;
+        if let Some(target) = head.symbolic_target() {
+            return Some(target.strip_prefix("refs/heads/").unwrap_or(target).to_string());
+        }
+        let oid = head.target()?.to_string();
+        Some(format!("分离于 {}", &oid[..7]))
+    }
+
+    /// 路径是否位于工作区内且未被忽略（其变化会影响仓库状态，文件需要与 HEAD 比较行变化）
+    pub fn in_worktree(&self, path: &Path) -> bool {
+        path.starts_with(&self.workdir) && self.status_of(path) != Some(FileStatus::Ignored)
+    }
+
+    /// 文件或目录的状态；未跟踪或被忽略的目录中的条目继承目录的状态
+    pub fn status_of(&self, path: &Path) -> Option<FileStatus> {
+        if let Some(status) = self.statuses.get(path).or_else(|| self.directories.get(path)) {
+            return Some(*status);
+        }
+        path.ancestors()
+            .skip(1)
+            .take_while(|dir| dir.starts_with(&self.workdir))
+            .find_map(|dir| self.statuses.get(dir))
+            .filter(|status| matches!(status, FileStatus::Ignored | FileStatus::Untracked))
+            .copied()
+    }
+
+    /// HEAD 中的文件内容，按指定编码解码；文件不在 HEAD 中时返回 None
+    pub fn head_text(&mut self, path: &Path, encoding: &'static Encoding) -> Option<String> {
+        self.head_blob(path).map(|bytes| text_encoding::decode(bytes, encoding).0)
+    }
+
+    /// HEAD 中的文件内容（带缓存）
+    fn head_blob(&mut self, path: &Path) -> Option<&[u8]> {
+        if !self.head_blobs.contains_key(path) {
+            let blob = self.read_head_blob(path);
+            self.head_blobs.insert(path.to_path_buf(), blob);
+        }
+        self.head_blobs.get(path)?.as_deref()
+    }
+
+    fn read_head_blob(&self, path: &Path) -> Option<Vec<u8>> {
+        let relative = path.strip_prefix(&self.workdir).ok()?;
+        let tree = self.repo.head().ok()?.peel_to_tree().ok()?;
+        let entry = tree.get_path(relative).ok()?;
+        let blob = entry.to_object(&self.repo).ok()?.peel_to_blob().ok()?;
+        Some(blob.content().to_vec())
+    }
+}
+
+/// blame 中的一次提交
+#[derive(Clone, Debug)]
+pub struct BlameCommit {
+    pub id: String,      // 完整提交号
+    pub author: String,
+    pub time: SystemTime, // 作者时间
+    pub message: String, // 完整提交说明
+}
+
+impl BlameCommit {
+    fn from_commit(commit: &git2::Commit) -> Self {
+        let author = commit.author();
+        Self {
+            id: commit.id().to_string(),
+            author: author.name().unwrap_or("未知").to_string(),
+            time: UNIX_EPOCH + Duration::from_secs(author.when().seconds().max(0) as u64),
+            message: commit.message().unwrap_or_default().trim_end().to_string(),
+        }
+    }
+
+    pub fn short_id(&self) -> &str {
+        &self.id[..self.id.len().min(7)]
+    }
+}
+
+/// 文件每一行最后修改它的提交
+#[derive(Default)]
+pub struct Blame {
+    pub commits: Vec<BlameCommit>,
+    pub lines: Vec<Option<usize>>, // 每行对应 commits 中的下标，未提交的行为 None
+}
+
+impl Blame {
+    pub fn commit_of(&self, line: usize) -> Option<&BlameCommit> {
+        self.lines.get(line).copied().flatten().map(|index| &self.commits[index])
+    }
+}
+
+/// 在后台线程中计算 blame（以当前内容为准，未保存的修改显示为未提交），完成后请求重绘
+pub fn spawn_blame(ctx: egui::Context, workdir: PathBuf, path: PathBuf, text: String, encoding: &'static Encoding) -> Receiver<Result<Blame, String>> {
+    let (sender, receiver) = channel();
+    std::thread::spawn(move || {
+        let result = blame(&workdir, &path, &text, encoding).map_err(|e| e.message().to_string());
+        let _ = sender.send(result);
+        ctx.request_repaint();
+    });
+    receiver
+}
+
+fn blame(workdir: &Path, path: &Path, text: &str, encoding: &'static Encoding) -> Result<Blame, git2::Error> {
+    let repo = Repository::open(workdir)?;
+    let relative = path.strip_prefix(workdir).map_err(|_| git2::Error::from_str("文件不在仓库中"))?;
+    let line_count = text.lines().count();
+    let mut result = Blame {
+        commits: Vec::new(),
+        lines: vec![None; line_count],
+    };
+
+    // 尚未提交过的文件全部视为未提交
+    let file_blame = match repo.blame_file(relative, None) {
+        Ok(file_blame) => file_blame,
+        Err(e) if e.code() == ErrorCode::NotFound => return Ok(result),
+        Err(e) => return Err(e),
+    };
+    let buffer_blame = file_blame.blame_buffer(&text_encoding::encode(text, encoding))?;
+
+    let mut commit_indices: HashMap<Oid, usize> = HashMap::new();
+    for hunk in buffer_blame.iter() {
+        let oid = hunk.final_commit_id();
+        let index = if oid.is_zero() {
+            None
+        } else if let Some(&index) = commit_indices.get(&oid) {
+            Some(index)
+        } else {
+            result.commits.push(BlameCommit::from_commit(&repo.find_commit(oid)?));
+            commit_indices.insert(oid, result.commits.len() - 1);
+            Some(result.commits.len() - 1)
+        };
+
+        let start = hunk.final_start_line().saturating_sub(1);
+        let end = (start + hunk.lines_in_hunk()).min(line_count);
+        for line in start..end {
+            result.lines[line] = index;
+        }
+    }
+    Ok(result)
+}
+
+/// 在后台线程中将文件内容与 HEAD 中的内容比较，完成后请求重绘
+/// head 为 None（文件不在 HEAD 中）时全部行视为新增；结果的键为从 0 开始的行号
+pub fn spawn_line_changes(ctx: egui::Context, head: Option<String>, text: String) -> Receiver<HashMap<usize, LineChange>> {
+    let (sender, receiver) = channel();
+    std::thread::spawn(move || {
+        let _ = sender.send(line_changes(head.as_deref(), &text));
+        ctx.request_repaint();
+    });
+    receiver
+}
+
+fn line_changes(head: Option<&str>, text: &str) -> HashMap<usize, LineChange> {
+    let mut changes = HashMap::new();
+    let line_count = text.lines().count();
+    let Some(old_text) = head else {
+        (0..line_count).for_each(|line| {
+            changes.insert(line, LineChange::Added);
+        });
+        return changes;
+    };
+
+    let mut options = DiffOptions::new();
+    options.context_lines(0);
+    let Ok(patch) = Patch::from_buffers(old_text.as_bytes(), None, text.as_bytes(), None, Some(&mut options)) else {
+        return changes;
+    };
+
+    for index in 0..patch.num_hunks() {
+        let Ok((hunk, _)) = patch.hunk(index) else {
+            continue;
+        };
+        let (new_start, new_lines) = (hunk.new_start() as usize, hunk.new_lines() as usize);
+        if new_lines == 0 {
+            // 纯删除：new_start 是删除位置之前的行（从 1 开始），标记在下一行；删除在末尾时标记最后一行
+            changes.insert(new_start.min(line_count.saturating_sub(1)), LineChange::Deleted);
+            continue;
+        }
+        let kind = if hunk.old_lines() == 0 { LineChange::Added } else { LineChange::Modified };
+        for line in new_start - 1..new_start - 1 + new_lines {
+            changes.insert(line, kind);
+        }
+    }
+    changes
+}
+
+/// 一次提交的说明和与第一个父提交相比的补丁
+pub struct CommitDetails {
+    pub commit: BlameCommit,
+    pub patch: String,
+}
+
+/// 读取提交的补丁（首个提交与空树比较）
+pub fn commit_details(workdir: &Path, id: &str) -> Result<CommitDetails, git2::Error> {
+    let repo = Repository::open(workdir)?;
+    let commit = repo.find_commit(Oid::from_str(id)?)?;
+    let tree = commit.tree()?;
+    let parent_tree = match commit.parent(0) {
+        Ok(parent) => Some(parent.tree()?),
+        Err(_) => None,
+    };
+    let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+
+    let mut patch = String::new();
+    diff.print(DiffFormat::Patch, |_, _, line| {
+        if matches!(line.origin(), '+' | '-' | ' ') {
+            patch.push(line.origin());
+        }
+        patch.push_str(&String::from_utf8_lossy(line.content()));
+        true
+    })?;
+
+    Ok(CommitDetails {
+        commit: BlameCommit::from_commit(&commit),
+        patch,
+    })
+}
@@ -0,0 +1,207 @@
+// 编码：UTF-8
+// 缩进：识别文件使用的缩进方式，计算每行的缩进宽度（缩进参考线使用）
+
+/// 没有其他依据时的制表符宽度
+pub const DEFAULT_TAB_WIDTH: usize = 4;
+
+/// 文件的缩进方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Indent {
+    Spaces(usize), // 每级的空格数
+    Tabs,
+}
+
+impl Indent {
+    /// 状态栏中显示的名称
+    pub fn name(self) -> String {
+        match self {
+            Indent::Spaces(width) => format!("空格: {}", width),
+            Indent::Tabs => "制表符".to_string(),
+        }
+    }
+
+    /// 每级缩进的列数
+    pub fn columns(self, tab_width: usize) -> usize {
+        match self {
+            Indent::Spaces(width) => width,
+            Indent::Tabs => tab_width,
+        }
+    }
+}
+
+/// 按各行行首空白识别缩进方式：以制表符开头的行多于以空格开头的行时为制表符，
+/// 否则取相邻行缩进增量中最常见的值（2 到 8），无法判断时为 4 个空格
+pub fn detect(text: &str) -> Indent {
+    let mut tab_lines = 0;
+    let mut space_lines = 0;
+    let mut deltas = [0usize; 9];
+    let mut previous = 0;
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        if line.starts_with('\t') {
+            tab_lines += 1;
+            continue;
+        }
+        let spaces = line.len() - line.trim_start_matches(' ').len();
+        if spaces > 0 {
+            space_lines += 1;
+        }
+        if spaces > previous && spaces - previous < deltas.len() {
+            deltas[spaces - previous] += 1;
+        }
+        previous = spaces;
+    }
+    if tab_lines > space_lines {
+        return Indent::Tabs;
+    }
+    (2..deltas.len())
+        .filter(|&width| deltas[width] > 0)
+        .max_by_key(|&width| (deltas[width], width == 4))
+        .map_or(Indent::Spaces(4), Indent::Spaces)
+}
+
+/// 没有 .editorconfig 设置时按缩进推测制表符宽度：空格缩进的文件取每级的空格数
+pub fn guess_tab_width(indent: Indent) -> usize {
+    match indent {
+        Indent::Spaces(width @ (2 | 4 | 8)) => width,
+        _ => DEFAULT_TAB_WIDTH,
+    }
+}
+
+/// 行首空白占用的列数
+pub fn leading_columns(line: &str, tab_width: usize) -> usize {
+    let mut columns = 0;
+    for ch in line.chars() {
+        match ch {
+            ' ' => columns += 1,
+            '\t' => columns += tab_width - columns % tab_width,
+            _ => break,
+        }
+    }
+    columns
+}
+
+/// 每行的缩进列数；空行取上下相邻非空行中较小的值，参考线才不会在空行处断开
+pub fn line_indents(text: &str, tab_width: usize) -> Vec<usize> {
+    let indents: Vec<Option<usize>> = text
+        .lines()
+        .map(|line| (!line.trim().is_empty()).then(|| leading_columns(line, tab_width)))
+        .collect();
+
+    // 每行之后（含本行）第一个非空行的缩进
+    let mut next = vec![0; indents.len()];
+    let mut following = 0;
+    for (index, indent) in indents.iter().enumerate().rev() {
+        following = indent.unwrap_or(following);
+        next[index] = following;
+    }
+
+    let mut previous = 0;
+    indents
+        .iter()
+        .zip(next)
+        .map(|(indent, next)| match indent {
+            Some(indent) => {
+                previous = *indent;
+                *indent
+            }
+            None => previous.min(next),
+        })
+        .collect()
+}
+
+/// 行首空白是否与文件的缩进方式不一致：空格缩进的文件中出现制表符，
+/// 或制表符缩进的文件中用空格缩进了一级以上（制表符之后用于对齐的空格不算）
+pub fn is_inconsistent(leading: &str, indent: Indent, tab_width: usize) -> bool {
+    match indent {
+        Indent::Spaces(_) => leading.contains('\t'),
+        Indent::Tabs => leading.contains(" \t") || leading.starts_with(&" ".repeat(tab_width)),
+    }
+}
+
+/// 文件中是否有与缩进方式不一致的行
+pub fn has_inconsistent(text: &str, indent: Indent, tab_width: usize) -> bool {
+    text.lines().any(|line| {
+        let leading = &line[..line.len() - line.trim_start_matches([' ', '\t']).len()];
+        is_inconsistent(leading, indent, tab_width)
+    })
+}
+
+/// 按新的缩进方式重写每行的行首空白：每级 from 缩进换成一级 to 缩进，不足一级的部分保留为空格
+pub fn reindent(text: &str, from: Indent, to: Indent, tab_width: usize) -> String {
+    let unit = match to {
+        Indent::Spaces(width) => " ".repeat(width),
+        Indent::Tabs => "\t".to_string(),
+    };
+    let step = from.columns(tab_width).max(1);
+    let mut result = String::with_capacity(text.len());
+    for line in text.split_inclusive('\n') {
+        let content = line.trim_start_matches([' ', '\t']);
+        let columns = leading_columns(line, tab_width);
+        result.push_str(&unit.repeat(columns / step));
+        result.push_str(&" ".repeat(columns % step));
+        result.push_str(content);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_spaces_tabs_and_width() {
+        assert_eq!(detect("fn a() {\n  b();\n  if c {\n    d();\n  }\n}\n"), Indent::Spaces(2));
+        assert_eq!(detect("a:\n    b:\n        c\n"), Indent::Spaces(4));
+        assert_eq!(detect("a\n\tb\n\t\tc\n  d\n"), Indent::Tabs);
+        // 没有缩进的文件默认 4 个空格；相同次数时优先 4
+        assert_eq!(detect("a\nb\n"), Indent::Spaces(4));
+        assert_eq!(detect("a\n  b\nc\n    d\n"), Indent::Spaces(4));
+        // 空白行不参与判断
+        assert_eq!(detect("a\n\t\n   \n  b\n"), Indent::Spaces(2));
+    }
+
+    #[test]
+    fn guess_tab_width_from_indent() {
+        assert_eq!(guess_tab_width(Indent::Spaces(2)), 2);
+        assert_eq!(guess_tab_width(Indent::Spaces(8)), 8);
+        assert_eq!(guess_tab_width(Indent::Spaces(3)), DEFAULT_TAB_WIDTH);
+        assert_eq!(guess_tab_width(Indent::Tabs), DEFAULT_TAB_WIDTH);
+    }
+
+    #[test]
+    fn leading_columns_expand_tabs_to_tab_stops() {
+        assert_eq!(leading_columns("x", 4), 0);
+        assert_eq!(leading_columns("    x", 4), 4);
+        assert_eq!(leading_columns("\tx", 4), 4);
+        assert_eq!(leading_columns("  \tx", 4), 4);
+        assert_eq!(leading_columns("\t  x", 8), 10);
+    }
+
+    #[test]
+    fn line_indents_bridge_blank_lines() {
+        assert_eq!(line_indents("a\n    b\n\n    c\nd\n", 4), [0, 4, 4, 4, 0]);
+        assert_eq!(line_indents("a\n        b\n\n    c\n", 4), [0, 8, 4, 4]);
+        assert_eq!(line_indents("\n  a\n\n", 4), [0, 2, 0]);
+    }
+
+    #[test]
+    fn inconsistent_indentation() {
+        assert!(is_inconsistent("  \t", Indent::Spaces(2), 4));
+        assert!(!is_inconsistent("    ", Indent::Spaces(2), 4));
+        assert!(is_inconsistent("    ", Indent::Tabs, 4));
+        assert!(is_inconsistent(" \t", Indent::Tabs, 4));
+        // 制表符之后用于对齐的空格不算
+        assert!(!is_inconsistent("\t  ", Indent::Tabs, 4));
+        assert!(has_inconsistent("a\n\tb\n    c\n", Indent::Tabs, 4));
+        assert!(!has_inconsistent("a\n\tb\n\t  c\n", Indent::Tabs, 4));
+    }
+
+    #[test]
+    fn reindent_converts_whole_levels() {
+        assert_eq!(reindent("a\n    b\n        c\n", Indent::Spaces(4), Indent::Tabs, 4), "a\n\tb\n\t\tc\n");
+        assert_eq!(reindent("a\n\tb\n\t\tc", Indent::Tabs, Indent::Spaces(2), 4), "a\n  b\n    c");
+        // 不足一级的部分保留为空格
+        assert_eq!(reindent("      b\n", Indent::Spaces(4), Indent::Tabs, 4), "\t  b\n");
+        assert_eq!(reindent("a\r\n  b\r\n", Indent::Spaces(2), Indent::Spaces(4), 4), "a\r\n    b\r\n");
+    }
+}
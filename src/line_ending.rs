@@ -0,0 +1,65 @@
+// 编码：UTF-8
+// 换行符：识别文件使用的换行符（LF / CRLF / 混合）并统一转换
+
+/// 文件的换行符
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineEnding {
+    Lf,
+    CrLf,
+    Mixed,
+}
+
+impl LineEnding {
+    /// 状态栏中显示的名称
+    pub fn name(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "LF",
+            LineEnding::CrLf => "CRLF",
+            LineEnding::Mixed => "混合换行符",
+        }
+    }
+}
+
+/// 识别换行符（没有换行的文件视为 LF）
+pub fn detect(text: &str) -> LineEnding {
+    let lf = text.bytes().filter(|&b| b == b'\n').count();
+    let crlf = text.matches("\r\n").count();
+    match crlf {
+        0 => LineEnding::Lf,
+        _ if crlf == lf => LineEnding::CrLf,
+        _ => LineEnding::Mixed,
+    }
+}
+
+/// 将所有换行符统一为 LF 或 CRLF
+pub fn convert(text: &str, target: LineEnding) -> String {
+    let normalized = text.replace("\r\n", "\n");
+    match target {
+        LineEnding::CrLf => normalized.replace('\n', "\r\n"),
+        _ => normalized,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_line_endings() {
+        assert_eq!(detect(""), LineEnding::Lf);
+        assert_eq!(detect("one line"), LineEnding::Lf);
+        assert_eq!(detect("a\nb\n"), LineEnding::Lf);
+        assert_eq!(detect("a\r\nb\r\n"), LineEnding::CrLf);
+        assert_eq!(detect("a\r\nb\n"), LineEnding::Mixed);
+        // 单独的 \r 不算换行
+        assert_eq!(detect("a\rb\n"), LineEnding::Lf);
+    }
+
+    #[test]
+    fn convert_normalizes_mixed_endings() {
+        assert_eq!(convert("a\r\nb\nc", LineEnding::Lf), "a\nb\nc");
+        assert_eq!(convert("a\r\nb\nc", LineEnding::CrLf), "a\r\nb\r\nc");
+        assert_eq!(convert("a\r\n", LineEnding::CrLf), "a\r\n");
+        assert_eq!(detect(&convert("a\nb\r\n", LineEnding::CrLf)), LineEnding::CrLf);
+    }
+}
@@ -2,18 +2,38 @@
 // 作者：code_notebook项目组Seraphiel
 
 use eframe::egui;
+use encoding_rs::Encoding;
 use std::path::PathBuf;
 
+mod bookmarks;
+mod config;
+mod delimited;
+mod diff;
+mod directory_listing;
+mod editorconfig;
+mod file_index;
+mod file_ops;
+mod file_types;
+mod folding;
+mod fs_watch;
+mod fuzzy;
+mod git;
+mod indentation;
+mod line_ending;
+mod markdown;
+mod structured;
+mod text_encoding;
 mod ui;
+mod wrap;
 use ui::layout::MainLayout;
 use ui::styles;
+use directory_listing::DirectoryItem;
 
-#[derive(Clone)]
-pub struct DirectoryItem {
-    pub name: String,
-    pub path: PathBuf,
-    pub is_directory: bool,
-}
+/// 最近打开文件列表的配置文件名
+const RECENT_FILES_FILE: &str = "recent_files.txt";
+
+/// 最多记录的最近打开文件数
+const MAX_RECENT_FILES: usize = 50;
 
 /// 应用状态
 pub struct AppState {
@@ -23,22 +43,40 @@ pub struct AppState {
     pub current_directory: PathBuf, // 当前显示的目录
     pub directory_items: Vec<DirectoryItem>, // 目录内容列表
     pub show_settings: bool,   // 是否显示设置面板
+    pub show_file_browser: bool, // 是否显示目录面板
+    pub show_status_bar: bool, // 是否显示状态栏
+    pub dark_mode: bool,       // 是否使用深色主题
+    pub encoding: &'static Encoding, // 当前文件编码
+    pub is_modified: bool,     // 内容是否有未保存的修改
+    pub loaded_len: u64,       // 已读取的文件字节数（跟随模式下增量读取）
+    append_decoder: Option<encoding_rs::Decoder>, // 跟随模式的解码器（保留跨两次读取被截断的字符）
+    pub recent_files: Vec<PathBuf>, // 最近打开的文件（最近的在前）
+    pub diff_request: Option<(PathBuf, PathBuf)>, // 等待打开的文件比较（命令行 --diff）
+    pub viewer_file: Option<(PathBuf, file_types::Viewer)>, // 在图像或十六进制查看器中打开的文件
+    main_layout: Option<MainLayout>, // 主布局（跨帧保留组件状态）
 }
 
 impl Default for AppState {
     fn default() -> Self {
-        let mut state = Self {
+        Self {
             code: "将代码文件拖拽到窗口即可查看".to_string(),
             file_path: None,
             status: String::new(),
             current_directory: std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
             directory_items: Vec::new(),
             show_settings: false,
-        };
-
-        // 加载初始目录内容
-        state.load_directory_content();
-        state
+            show_file_browser: true,
+            show_status_bar: false,
+            dark_mode: true,
+            encoding: encoding_rs::UTF_8,
+            is_modified: false,
+            loaded_len: 0,
+            append_decoder: None,
+            recent_files: Self::load_recent_files(),
+            diff_request: None,
+            viewer_file: None,
+            main_layout: None,
+        }
     }
 }
 
@@ -47,23 +85,27 @@ impl eframe::App for AppState {
         // 处理拖拽文件
         self.handle_dropped_files(ctx);
 
-        // 创建布局并渲染（优化：减少状态复制）
-        let mut main_layout = MainLayout::new(self);
+        // 取出布局并渲染（布局跨帧保留，组件缓存和模式不会每帧重置）
+        let mut main_layout = self.main_layout.take().unwrap_or_else(|| MainLayout::new(self));
 
         // 渲染UI并获取可能的文件加载请求
-        if let Some(file_path) = main_layout.render(ctx, frame, self) {
-            // 文件加载后直接更新状态，避免下一帧的额外开销
-            self.load_file(file_path);
+        let file_to_load = main_layout.render(ctx, frame, self);
+
+        // 同步用户编辑的代码（需在加载新文件之前，避免覆盖新内容）
+        if main_layout.code_editor.code != self.code {
+            self.code = main_layout.code_editor.code.clone();
+            self.is_modified = true;
         }
 
         // 同步目录状态（重要：用于目录导航）
         self.current_directory = main_layout.file_browser.current_directory.clone();
         self.directory_items = main_layout.file_browser.directory_items.clone();
 
-        // 同步用户编辑的代码（只在无文件时，避免覆盖文件内容）
-        if self.file_path.is_none() && main_layout.code_editor.code != self.code {
-            self.code = main_layout.code_editor.code.clone();
+        if let Some(file_path) = file_to_load {
+            self.load_file(file_path);
         }
+
+        self.main_layout = Some(main_layout);
     }
 }
 
@@ -75,73 +117,181 @@ impl AppState {
             if let Some(path) = df.path {
                 self.load_file(path);
             } else if let Some(bytes) = df.bytes {
-                // 直接处理字节内容
-                self.code = String::from_utf8_lossy(&bytes).into_owned();
+                // 直接处理字节内容（没有路径，二进制内容无法在查看器中打开）
+                if text_encoding::is_binary(&bytes) {
+                    self.status = "拖入的是二进制数据，无法以文本方式查看".to_string();
+                    continue;
+                }
+                let (content, encoding) = text_encoding::decode(&bytes, text_encoding::detect_encoding(&bytes));
+                self.code = content;
+                self.encoding = encoding;
                 self.status = "已加载临时数据".to_string();
                 self.file_path = None;
+                self.viewer_file = None;
             }
         }
     }
 
-    /// 加载文件
+    /// 加载文件（自动识别编码）
     fn load_file(&mut self, path: PathBuf) {
-        match std::fs::read_to_string(&path) {
-            Ok(content) => {
+        let file_type = file_types::registry().for_path(&path);
+        match file_type.viewer {
+            file_types::Viewer::Binary | file_types::Viewer::Image => {
+                // 在查看器中打开，不替换文本内容
+                self.status = format!("已打开{}", file_type.name);
+                self.viewer_file = Some((path.clone(), file_type.viewer));
+                self.remember_recent_file(&path);
+                return;
+            }
+            file_types::Viewer::Text => {}
+        }
+
+        match std::fs::read(&path) {
+            Ok(bytes) if text_encoding::is_binary(&bytes) => {
+                // 未知类型的二进制内容按十六进制显示
+                self.status = "二进制内容，已以十六进制打开".to_string();
+                self.viewer_file = Some((path.clone(), file_types::Viewer::Binary));
+                self.remember_recent_file(&path);
+            }
+            Ok(bytes) => {
+                let encoding = text_encoding::detect_encoding(&bytes);
+                let (content, encoding) = text_encoding::decode(&bytes, encoding);
                 self.code = content;
+                self.encoding = encoding;
+                self.is_modified = false;
+                self.loaded_len = bytes.len() as u64;
+                self.append_decoder = None;
                 self.status = "已加载".to_string();
                 self.file_path = Some(path.clone());
+                self.viewer_file = None;
+                self.remember_recent_file(&path);
 
-                // 设置当前目录为文件所在目录
-                if let Some(parent_dir) = path.parent() {
-                    self.current_directory = parent_dir.to_path_buf();
-                    self.load_directory_content();
+                // 文件不在当前工作区内时，以文件所在目录作为新的工作区
+                if !path.starts_with(&self.current_directory) {
+                    if let Some(parent_dir) = path.parent() {
+                        self.current_directory = parent_dir.to_path_buf();
+                    }
                 }
             }
             Err(e) => {
                 self.code = format!("读取失败: {}", e);
                 self.status = "错误".to_string();
                 self.file_path = None;
-                self.directory_items.clear();
             }
         }
     }
 
-    /// 加载当前目录的内容
-    fn load_directory_content(&mut self) {
-        self.directory_items.clear();
-
-        if let Ok(entries) = std::fs::read_dir(&self.current_directory) {
-            let mut directories = Vec::new();
-            let mut files = Vec::new();
-
-            for entry in entries.flatten() {
-                let path = entry.path();
-                let is_directory = path.is_dir();
-
-                if let Some(file_name) = path.file_name() {
-                    if let Some(name_str) = file_name.to_str() {
-                        let item = DirectoryItem {
-                            name: name_str.to_string(),
-                            path: path.clone(),
-                            is_directory,
-                        };
-
-                        if is_directory {
-                            directories.push(item);
-                        } else {
-                            files.push(item);
-                        }
-                    }
-                }
+    /// 读取最近打开的文件列表
+    fn load_recent_files() -> Vec<PathBuf> {
+        std::fs::read_to_string(config::config_file(RECENT_FILES_FILE))
+            .map(|content| content.lines().filter(|line| !line.is_empty()).map(PathBuf::from).collect())
+            .unwrap_or_default()
+    }
+
+    /// 记录最近打开的文件并保存
+    fn remember_recent_file(&mut self, path: &std::path::Path) {
+        self.recent_files.retain(|recent| recent != path);
+        self.recent_files.insert(0, path.to_path_buf());
+        self.recent_files.truncate(MAX_RECENT_FILES);
+
+        let content: Vec<String> = self.recent_files.iter().map(|p| p.display().to_string()).collect();
+        let _ = config::write_config_file(RECENT_FILES_FILE, &content.join("\n"));
+    }
+
+    /// 以指定编码重新读取当前文件
+    fn reload_with_encoding(&mut self, encoding: &'static Encoding) {
+        let Some(path) = self.file_path.clone() else {
+            self.encoding = encoding;
+            return;
+        };
+
+        match std::fs::read(&path) {
+            Ok(bytes) => {
+                let (content, encoding) = text_encoding::decode(&bytes, encoding);
+                self.code = content;
+                self.encoding = encoding;
+                self.is_modified = false;
+                self.loaded_len = bytes.len() as u64;
+                self.append_decoder = None;
+                self.status = format!("已按 {} 重新加载", encoding.name());
+            }
+            Err(e) => {
+                self.status = format!("重新加载失败: {}", e);
             }
+        }
+    }
+
+    /// 文件被外部修改后按当前编码重新读取，返回内容是否有变化
+    fn reload_from_disk(&mut self) -> bool {
+        let Some(path) = self.file_path.clone() else {
+            return false;
+        };
+        let Ok(bytes) = std::fs::read(&path) else {
+            return false;
+        };
+
+        self.loaded_len = bytes.len() as u64;
+        self.append_decoder = None;
+        let (content, _) = text_encoding::decode(&bytes, self.encoding);
+        if content == self.code {
+            return false;
+        }
+        self.code = content;
+        self.is_modified = false;
+        true
+    }
+
+    /// 跟随模式：只读取文件新增的部分；文件变短（被截断或轮转）时整体重新读取
+    fn append_from_disk(&mut self) {
+        let Some(path) = self.file_path.clone() else {
+            return;
+        };
+        let Ok(metadata) = std::fs::metadata(&path) else {
+            return;
+        };
 
-            // 排序：目录在前，文件在后，都按字母顺序排序
-            directories.sort_by(|a, b| a.name.cmp(&b.name));
-            files.sort_by(|a, b| a.name.cmp(&b.name));
+        let len = metadata.len();
+        if len < self.loaded_len {
+            self.reload_from_disk();
+            return;
+        }
+        if len == self.loaded_len {
+            return;
+        }
 
-            // 合并列表
-            self.directory_items.extend(directories);
-            self.directory_items.extend(files);
+        use std::io::{Read, Seek, SeekFrom};
+        let mut appended = Vec::new();
+        let read = std::fs::File::open(&path).and_then(|mut file| {
+            file.seek(SeekFrom::Start(self.loaded_len))?;
+            file.read_to_end(&mut appended)
+        });
+        if read.is_ok() {
+            self.loaded_len += appended.len() as u64;
+            // 末尾不完整的字符留在解码器中，与下次读取的字节一起解码
+            let encoding = self.encoding;
+            let decoder = self.append_decoder.get_or_insert_with(|| encoding.new_decoder_without_bom_handling());
+            let capacity = decoder.max_utf8_buffer_length(appended.len()).unwrap_or(appended.len() * 3);
+            let mut text = String::with_capacity(capacity);
+            let _ = decoder.decode_to_string(&appended, &mut text, false);
+            self.code.push_str(&text);
+        }
+    }
+
+    /// 保存到指定路径（使用当前编码）
+    fn save_file_as(&mut self, path: PathBuf) {
+        let bytes = text_encoding::encode(&self.code, self.encoding);
+        let len = bytes.len() as u64;
+        match std::fs::write(&path, bytes) {
+            Ok(()) => {
+                self.is_modified = false;
+                self.loaded_len = len;
+                self.append_decoder = None;
+                self.status = "已保存".to_string();
+                self.file_path = Some(path);
+            }
+            Err(e) => {
+                self.status = format!("保存失败: {}", e);
+            }
         }
     }
 }
@@ -175,8 +325,10 @@ fn main() -> eframe::Result<()> {
     // 初始状态
     let mut initial_state = AppState::default();
 
-    // 如果有命令行参数，尝试作为文件路径加载
-    if args.len() > 1 {
+    // --diff a b 打开两个文件的比较，其他参数尝试作为文件路径加载
+    if args.len() > 3 && args[1] == "--diff" {
+        initial_state.diff_request = Some((PathBuf::from(&args[2]), PathBuf::from(&args[3])));
+    } else if args.len() > 1 {
         let file_path = std::path::PathBuf::from(&args[1]);
         initial_state.load_file(file_path);
     }
@@ -208,4 +360,29 @@ fn main() -> eframe::Result<()> {
             Box::new(initial_state)
         }),
     )
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn append_keeps_characters_split_across_writes() {
+        let dir = std::env::temp_dir().join(format!("code_notebook_follow_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("log.txt");
+        std::fs::write(&path, "a\n").unwrap();
+
+        let mut app_state = AppState { file_path: Some(path.clone()), ..Default::default() };
+        assert!(app_state.reload_from_disk());
+
+        // “中” 的 UTF-8 编码分两次写入
+        let bytes = "中\n".as_bytes();
+        let mut file = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+        std::io::Write::write_all(&mut file, &bytes[..1]).unwrap();
+        app_state.append_from_disk();
+        std::io::Write::write_all(&mut file, &bytes[1..]).unwrap();
+        app_state.append_from_disk();
+        assert_eq!(app_state.code, "a\n中\n");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,365 @@
+// 编码：UTF-8
+// Markdown 解析：CommonMark + GFM 表格、任务列表和删除线，转换为便于预览的块列表
+
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag};
+
+/// 带样式的一段行内文本
+#[derive(Clone, Debug, Default)]
+pub struct Span {
+    pub text: String,
+    pub strong: bool,
+    pub emphasis: bool,
+    pub strikethrough: bool,
+    pub code: bool,
+    pub link: Option<String>, // 链接地址
+}
+
+/// 预览中的一个块
+#[derive(Debug)]
+pub enum Block {
+    Heading { level: u8, spans: Vec<Span> },
+    Paragraph(Vec<Span>),
+    ListItem { marker: String, checked: Option<bool>, spans: Vec<Span> }, // checked 为任务列表的勾选状态
+    Code { language: Option<String>, code: String },
+    Table { header: Vec<Cell>, rows: Vec<Vec<Cell>> },
+    Image { url: String, alt: String },
+    Rule,
+}
+
+/// 块及其在源文件中的位置
+#[derive(Debug)]
+pub struct SourceBlock {
+    pub block: Block,
+    pub line: usize,        // 块开始的行（从 0 开始）
+    pub indent: usize,      // 列表嵌套层数
+    pub quote_depth: usize, // 引用嵌套层数
+}
+
+/// 表格的一个单元格
+pub type Cell = Vec<Span>;
+
+/// 解析过程中的状态
+#[derive(Default)]
+struct BlockBuilder {
+    blocks: Vec<SourceBlock>,
+    spans: Vec<Span>,
+    style: Span,                   // 当前的行内样式（text 字段不使用）
+    line: usize,                   // 当前块开始的行
+    lists: Vec<Option<u64>>,       // 列表栈：有序列表的下一个序号
+    open_item: Option<usize>,      // 还没有内容的列表项
+    quote_depth: usize,
+    code: Option<(Option<String>, String)>, // 代码块的语言和内容
+    image: Option<(String, String)>,        // 图片地址和替代文字
+    table: Option<(Vec<Cell>, Vec<Vec<Cell>>)>, // 表头和各行
+    row: Vec<Cell>,                // 表格当前行
+}
+
+/// 解析 Markdown 文本
+pub fn parse(text: &str) -> Vec<SourceBlock> {
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(text.match_indices('\n').map(|(index, _)| index + 1))
+        .collect();
+    let line_of = |offset: usize| line_starts.partition_point(|&start| start <= offset).saturating_sub(1);
+
+    let options = Options::ENABLE_TABLES | Options::ENABLE_TASKLISTS | Options::ENABLE_STRIKETHROUGH;
+    let mut builder = BlockBuilder::default();
+    for (event, range) in Parser::new_ext(text, options).into_offset_iter() {
+        // 块级元素开始时先结束之前的行内文本，再记录新块的起始行
+        let block_start = matches!(
+            event,
+            Event::Rule
+                | Event::Start(
+                    Tag::Heading(..) | Tag::Paragraph | Tag::CodeBlock(_) | Tag::Table(_)
+                        | Tag::BlockQuote | Tag::List(_) | Tag::Item | Tag::Image(..)
+                )
+        );
+        if block_start && builder.table.is_none() {
+            builder.flush();
+            builder.line = line_of(range.start);
+        }
+        builder.handle(event);
+    }
+    builder.flush();
+    builder.blocks
+}
+
+impl BlockBuilder {
+    fn handle(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) => {
+                if let Some((_, code)) = &mut self.code {
+                    code.push_str(&text);
+                } else if let Some((_, alt)) = &mut self.image {
+                    alt.push_str(&text);
+                } else {
+                    self.push_text(&text, false);
+                }
+            }
+            Event::Code(text) => self.push_text(&text, true),
+            Event::Html(html) => self.push_text(html.trim_end(), true),
+            Event::SoftBreak => self.push_text(" ", false),
+            Event::HardBreak => self.push_text("\n", false),
+            Event::Rule => self.push(Block::Rule),
+            Event::TaskListMarker(checked) => {
+                if let Some(index) = self.open_item {
+                    if let Block::ListItem { checked: item_checked, .. } = &mut self.blocks[index].block {
+                        *item_checked = Some(checked);
+                    }
+                }
+            }
+            Event::FootnoteReference(_) => {}
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Heading(..) | Tag::Paragraph | Tag::CodeBlock(_) | Tag::Table(_) => {
+                self.flush();
+                match tag {
+                    Tag::CodeBlock(kind) => {
+                        let language = match kind {
+                            CodeBlockKind::Fenced(info) => info.split_whitespace().next().map(str::to_lowercase),
+                            CodeBlockKind::Indented => None,
+                        };
+                        self.code = Some((language, String::new()));
+                    }
+                    Tag::Table(_) => self.table = Some((Vec::new(), Vec::new())),
+                    _ => {}
+                }
+            }
+            Tag::BlockQuote => {
+                self.flush();
+                self.quote_depth += 1;
+            }
+            Tag::List(start) => {
+                self.flush();
+                self.lists.push(start);
+            }
+            Tag::Item => {
+                self.flush();
+                let marker = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}.", *number - 1)
+                    }
+                    _ => "•".to_string(),
+                };
+                self.push(Block::ListItem { marker, checked: None, spans: Vec::new() });
+                self.open_item = Some(self.blocks.len() - 1);
+            }
+            Tag::TableHead | Tag::TableRow => self.row.clear(),
+            Tag::TableCell => self.spans.clear(),
+            Tag::Emphasis => self.style.emphasis = true,
+            Tag::Strong => self.style.strong = true,
+            Tag::Strikethrough => self.style.strikethrough = true,
+            Tag::Link(_, url, _) => self.style.link = Some(url.to_string()),
+            Tag::Image(_, url, _) => {
+                // 图片单独成块
+                self.flush();
+                self.image = Some((url.to_string(), String::new()));
+            }
+            Tag::FootnoteDefinition(_) => {}
+        }
+    }
+
+    fn end(&mut self, tag: Tag) {
+        match tag {
+            Tag::Heading(level, ..) => {
+                let spans = std::mem::take(&mut self.spans);
+                self.push(Block::Heading { level: heading_level(level), spans });
+            }
+            Tag::Paragraph | Tag::Item => self.flush(),
+            Tag::CodeBlock(_) => {
+                if let Some((language, mut code)) = self.code.take() {
+                    if code.ends_with('\n') {
+                        code.pop();
+                    }
+                    self.push(Block::Code { language, code });
+                }
+            }
+            Tag::BlockQuote => {
+                self.flush();
+                self.quote_depth = self.quote_depth.saturating_sub(1);
+            }
+            Tag::List(_) => {
+                self.flush();
+                self.lists.pop();
+            }
+            Tag::TableCell => {
+                let cell = std::mem::take(&mut self.spans);
+                self.row.push(cell);
+            }
+            Tag::TableHead => {
+                if let Some((header, _)) = &mut self.table {
+                    *header = std::mem::take(&mut self.row);
+                }
+            }
+            Tag::TableRow => {
+                if let Some((_, rows)) = &mut self.table {
+                    rows.push(std::mem::take(&mut self.row));
+                }
+            }
+            Tag::Table(_) => {
+                if let Some((header, rows)) = self.table.take() {
+                    self.push(Block::Table { header, rows });
+                }
+            }
+            Tag::Emphasis => self.style.emphasis = false,
+            Tag::Strong => self.style.strong = false,
+            Tag::Strikethrough => self.style.strikethrough = false,
+            Tag::Link(..) => self.style.link = None,
+            Tag::Image(..) => {
+                if let Some((url, alt)) = self.image.take() {
+                    self.push(Block::Image { url, alt });
+                }
+            }
+            Tag::FootnoteDefinition(_) => {}
+        }
+    }
+
+    fn push_text(&mut self, text: &str, code: bool) {
+        self.spans.push(Span {
+            text: text.to_string(),
+            code,
+            ..self.style.clone()
+        });
+    }
+
+    fn push(&mut self, block: Block) {
+        self.blocks.push(SourceBlock {
+            block,
+            line: self.line,
+            indent: self.lists.len().saturating_sub(1),
+            quote_depth: self.quote_depth,
+        });
+    }
+
+    /// 收集到的行内文本：填入还没有内容的列表项，否则作为段落
+    fn flush(&mut self) {
+        if self.spans.is_empty() || self.table.is_some() {
+            return;
+        }
+        let spans = std::mem::take(&mut self.spans);
+        if let Some(index) = self.open_item.take() {
+            if let Block::ListItem { spans: item_spans, .. } = &mut self.blocks[index].block {
+                *item_spans = spans;
+                return;
+            }
+        }
+        self.push(Block::Paragraph(spans));
+    }
+}
+
+fn heading_level(level: HeadingLevel) -> u8 {
+    match level {
+        HeadingLevel::H1 => 1,
+        HeadingLevel::H2 => 2,
+        HeadingLevel::H3 => 3,
+        HeadingLevel::H4 => 4,
+        HeadingLevel::H5 => 5,
+        HeadingLevel::H6 => 6,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(spans: &[Span]) -> String {
+        spans.iter().map(|span| span.text.as_str()).collect()
+    }
+
+    #[test]
+    fn headings_paragraphs_and_source_lines() {
+        let blocks = parse("# Title\n\nSome *em* and **strong**\ntext.\n\n---\n");
+        assert_eq!(blocks.len(), 3);
+        match &blocks[0].block {
+            Block::Heading { level, spans } => assert_eq!((*level, text(spans).as_str()), (1, "Title")),
+            other => panic!("{:?}", other),
+        }
+        assert_eq!(blocks[0].line, 0);
+        match &blocks[1].block {
+            Block::Paragraph(spans) => {
+                assert_eq!(text(spans), "Some em and strong text.");
+                assert!(spans.iter().any(|span| span.emphasis && span.text == "em"));
+                assert!(spans.iter().any(|span| span.strong && span.text == "strong"));
+                assert!(spans.iter().filter(|span| span.text == "Some ").all(|span| !span.emphasis && !span.strong));
+            }
+            other => panic!("{:?}", other),
+        }
+        assert_eq!(blocks[1].line, 2);
+        assert!(matches!(blocks[2].block, Block::Rule));
+        assert_eq!(blocks[2].line, 5);
+    }
+
+    #[test]
+    fn inline_code_links_and_strikethrough() {
+        let blocks = parse("Run `cargo` see [docs](https://example.com) ~~old~~");
+        let Block::Paragraph(spans) = &blocks[0].block else {
+            panic!("{:?}", blocks[0].block);
+        };
+        assert!(spans.iter().any(|span| span.code && span.text == "cargo"));
+        assert!(spans.iter().any(|span| span.link.as_deref() == Some("https://example.com") && span.text == "docs"));
+        assert!(spans.iter().any(|span| span.strikethrough && span.text == "old"));
+        assert!(spans.iter().filter(|span| span.text == " see ").all(|span| span.link.is_none()));
+    }
+
+    #[test]
+    fn lists_number_items_and_track_nesting_and_tasks() {
+        let blocks = parse("3. three\n4. four\n   - [x] done\n   - [ ] todo\n");
+        let items: Vec<(String, Option<bool>, String, usize)> = blocks
+            .iter()
+            .map(|block| match &block.block {
+                Block::ListItem { marker, checked, spans } => (marker.clone(), *checked, text(spans), block.indent),
+                other => panic!("{:?}", other),
+            })
+            .collect();
+        assert_eq!(
+            items,
+            [
+                ("3.".to_string(), None, "three".to_string(), 0),
+                ("4.".to_string(), None, "four".to_string(), 0),
+                ("•".to_string(), Some(true), "done".to_string(), 1),
+                ("•".to_string(), Some(false), "todo".to_string(), 1),
+            ]
+        );
+        assert_eq!(blocks.iter().map(|block| block.line).collect::<Vec<_>>(), [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn code_blocks_keep_language_and_content() {
+        let blocks = parse("```Rust ignore\nfn main() {}\n```\n\n    indented\n");
+        match &blocks[0].block {
+            Block::Code { language, code } => assert_eq!((language.as_deref(), code.as_str()), (Some("rust"), "fn main() {}")),
+            other => panic!("{:?}", other),
+        }
+        match &blocks[1].block {
+            Block::Code { language, code } => assert_eq!((language.as_deref(), code.as_str()), (None, "indented")),
+            other => panic!("{:?}", other),
+        }
+        assert_eq!(blocks[1].line, 4);
+    }
+
+    #[test]
+    fn tables_quotes_and_images() {
+        let blocks = parse("| a | b |\n|---|---|\n| 1 | **2** |\n\n> quoted\n\n![alt text](img.png)\n");
+        match &blocks[0].block {
+            Block::Table { header, rows } => {
+                assert_eq!(header.iter().map(|cell| text(cell)).collect::<Vec<_>>(), ["a", "b"]);
+                assert_eq!(rows.len(), 1);
+                assert_eq!(rows[0].iter().map(|cell| text(cell)).collect::<Vec<_>>(), ["1", "2"]);
+                assert!(rows[0][1][0].strong);
+            }
+            other => panic!("{:?}", other),
+        }
+        assert!(matches!(&blocks[1].block, Block::Paragraph(spans) if text(spans) == "quoted"));
+        assert_eq!((blocks[1].quote_depth, blocks[1].line), (1, 4));
+        match &blocks[2].block {
+            Block::Image { url, alt } => assert_eq!((url.as_str(), alt.as_str()), ("img.png", "alt text")),
+            other => panic!("{:?}", other),
+        }
+        assert_eq!(blocks[2].quote_depth, 0);
+    }
+}
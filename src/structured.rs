@@ -0,0 +1,517 @@
+// 编码：UTF-8
+// 结构化文档：JSON、TOML、YAML 解析为统一的树，支持类似 JSONPath 的查询、格式化和压缩
+
+/// 支持的文档格式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl Format {
+    /// 按语法高亮语言识别格式
+    pub fn from_language(language: &str) -> Option<Self> {
+        match language {
+            "json" => Some(Format::Json),
+            "toml" => Some(Format::Toml),
+            "yaml" => Some(Format::Yaml),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Format::Json => "JSON",
+            Format::Toml => "TOML",
+            Format::Yaml => "YAML",
+        }
+    }
+}
+
+/// 解析错误的位置（行列从 0 开始）
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "第 {} 行第 {} 列: {}", self.line + 1, self.column + 1, self.message)
+    }
+}
+
+/// 节点的值类型
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NodeKind {
+    Null,
+    Bool,
+    Number,
+    String,
+    DateTime,
+    Array,
+    Object,
+}
+
+impl NodeKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            NodeKind::Null => "null",
+            NodeKind::Bool => "bool",
+            NodeKind::Number => "number",
+            NodeKind::String => "string",
+            NodeKind::DateTime => "datetime",
+            NodeKind::Array => "array",
+            NodeKind::Object => "object",
+        }
+    }
+
+    pub fn is_container(self) -> bool {
+        matches!(self, NodeKind::Array | NodeKind::Object)
+    }
+}
+
+/// 节点在父节点中的位置
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Key {
+    Root,
+    Name(String), // 对象的键
+    Index(usize), // 数组的下标
+}
+
+/// 树中的一个节点（按先序存放在 Document::nodes 中）
+#[derive(Debug)]
+pub struct Node {
+    pub key: Key,
+    pub kind: NodeKind,
+    pub value: String,       // 标量的显示文本（容器为空）
+    pub depth: usize,
+    pub parent: Option<usize>,
+    pub children: Vec<usize>,
+    pub subtree_end: usize,  // 子树之后第一个节点的下标
+}
+
+/// 解析后的文档树，nodes[0] 为根节点
+#[derive(Debug, Default)]
+pub struct Document {
+    pub nodes: Vec<Node>,
+}
+
+/// 各格式共用的中间值
+enum Value {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    DateTime(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+/// 解析文档
+pub fn parse(format: Format, text: &str) -> Result<Document, ParseError> {
+    let value = parse_value(format, text)?;
+    let mut document = Document::default();
+    document.push(Key::Root, value, 0, None);
+    Ok(document)
+}
+
+/// 格式化：JSON 缩进两格，TOML 和 YAML 按标准格式重新输出（不保留注释）
+pub fn pretty(format: Format, text: &str) -> Result<String, ParseError> {
+    let error = |message: String| ParseError { line: 0, column: 0, message };
+    match format {
+        Format::Json => {
+            let value: serde_json::Value = serde_json::from_str(text).map_err(json_error)?;
+            serde_json::to_string_pretty(&value).map_err(|e| error(e.to_string()))
+        }
+        Format::Toml => {
+            let value: toml::Value = toml::from_str(text).map_err(|e| toml_error(text, e))?;
+            toml::to_string_pretty(&value).map_err(|e| error(e.to_string()))
+        }
+        Format::Yaml => {
+            let value: serde_yaml::Value = serde_yaml::from_str(text).map_err(yaml_error)?;
+            serde_yaml::to_string(&value).map_err(|e| error(e.to_string()))
+        }
+    }
+}
+
+/// 压缩：JSON 去掉全部空白，YAML 输出为单行的流式写法；TOML 没有单行写法，返回 None
+pub fn minify(format: Format, text: &str) -> Option<Result<String, ParseError>> {
+    let error = |e: serde_json::Error| ParseError { line: 0, column: 0, message: e.to_string() };
+    Some(match format {
+        Format::Json => serde_json::from_str::<serde_json::Value>(text)
+            .map_err(json_error)
+            .and_then(|value| serde_json::to_string(&value).map_err(error)),
+        // JSON 是合法的 YAML 流式写法，但只能表示字符串键，也不能带标签
+        Format::Yaml => serde_yaml::from_str::<serde_yaml::Value>(text).map_err(yaml_error).and_then(|value| {
+            if let Some((needle, message)) = unrepresentable(&value) {
+                let (line, column) = locate(text, &needle);
+                return Err(ParseError { line, column, message });
+            }
+            serde_json::to_string(&value).map_err(error)
+        }),
+        Format::Toml => return None,
+    })
+}
+
+/// YAML 中第一处无法压缩为流式写法的内容：返回用于定位的文本和说明
+fn unrepresentable(value: &serde_yaml::Value) -> Option<(String, String)> {
+    match value {
+        serde_yaml::Value::Sequence(items) => items.iter().find_map(unrepresentable),
+        serde_yaml::Value::Mapping(map) => map.iter().find_map(|(key, value)| {
+            if key.is_string() {
+                return unrepresentable(value);
+            }
+            let key = serde_yaml::to_string(key).unwrap_or_default().trim_end().to_string();
+            Some((format!("{}:", key), format!("键 {} 不是字符串，流式写法只支持字符串键", key)))
+        }),
+        serde_yaml::Value::Tagged(tagged) => {
+            let tag = tagged.tag.to_string();
+            Some((tag.clone(), format!("标签 {} 无法用流式写法表示", tag)))
+        }
+        _ => None,
+    }
+}
+
+/// 文本在原文中第一次出现的位置（行、列从 0 开始），找不到时为开头
+fn locate(text: &str, needle: &str) -> (usize, usize) {
+    text.lines()
+        .enumerate()
+        .find_map(|(line, content)| content.find(needle).map(|index| (line, content[..index].chars().count())))
+        .unwrap_or((0, 0))
+}
+
+fn parse_value(format: Format, text: &str) -> Result<Value, ParseError> {
+    match format {
+        Format::Json => serde_json::from_str(text).map(from_json).map_err(json_error),
+        Format::Toml => toml::from_str::<toml::Table>(text)
+            .map(|table| from_toml(toml::Value::Table(table)))
+            .map_err(|e| toml_error(text, e)),
+        Format::Yaml => {
+            // 多文档的 YAML 作为数组显示
+            let mut documents = Vec::new();
+            for document in serde_yaml::Deserializer::from_str(text) {
+                let value = <serde_yaml::Value as serde::Deserialize>::deserialize(document).map_err(yaml_error)?;
+                documents.push(from_yaml(value));
+            }
+            Ok(match documents.len() {
+                0 => Value::Null,
+                1 => documents.remove(0),
+                _ => Value::Array(documents),
+            })
+        }
+    }
+}
+
+fn from_json(value: serde_json::Value) -> Value {
+    match value {
+        serde_json::Value::Null => Value::Null,
+        serde_json::Value::Bool(b) => Value::Bool(b),
+        serde_json::Value::Number(n) => Value::Number(n.to_string()),
+        serde_json::Value::String(s) => Value::String(s),
+        serde_json::Value::Array(items) => Value::Array(items.into_iter().map(from_json).collect()),
+        serde_json::Value::Object(map) => Value::Object(map.into_iter().map(|(k, v)| (k, from_json(v))).collect()),
+    }
+}
+
+fn from_toml(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(s) => Value::String(s),
+        toml::Value::Integer(i) => Value::Number(i.to_string()),
+        toml::Value::Float(f) => Value::Number(f.to_string()),
+        toml::Value::Boolean(b) => Value::Bool(b),
+        toml::Value::Datetime(d) => Value::DateTime(d.to_string()),
+        toml::Value::Array(items) => Value::Array(items.into_iter().map(from_toml).collect()),
+        toml::Value::Table(table) => Value::Object(table.into_iter().map(|(k, v)| (k, from_toml(v))).collect()),
+    }
+}
+
+fn from_yaml(value: serde_yaml::Value) -> Value {
+    match value {
+        serde_yaml::Value::Null => Value::Null,
+        serde_yaml::Value::Bool(b) => Value::Bool(b),
+        serde_yaml::Value::Number(n) => Value::Number(n.to_string()),
+        serde_yaml::Value::String(s) => Value::String(s),
+        serde_yaml::Value::Sequence(items) => Value::Array(items.into_iter().map(from_yaml).collect()),
+        serde_yaml::Value::Mapping(map) => Value::Object(
+            map.into_iter()
+                .map(|(k, v)| {
+                    let key = match k {
+                        serde_yaml::Value::String(s) => s,
+                        other => serde_yaml::to_string(&other).unwrap_or_default().trim_end().to_string(),
+                    };
+                    (key, from_yaml(v))
+                })
+                .collect(),
+        ),
+        serde_yaml::Value::Tagged(tagged) => from_yaml(tagged.value),
+    }
+}
+
+fn json_error(e: serde_json::Error) -> ParseError {
+    ParseError {
+        line: e.line().saturating_sub(1),
+        column: e.column().saturating_sub(1),
+        // 位置已单独记录，去掉消息末尾的 "at line X column Y"
+        message: e.to_string().rsplit_once(" at line ").map_or_else(|| e.to_string(), |(message, _)| message.to_string()),
+    }
+}
+
+fn toml_error(text: &str, e: toml::de::Error) -> ParseError {
+    let offset = e.span().map_or(0, |span| span.start).min(text.len());
+    let before = &text[..offset];
+    let line = before.matches('\n').count();
+    let column = before.rfind('\n').map_or(before.len(), |i| before.len() - i - 1);
+    ParseError { line, column, message: e.message().trim().replace('\n', " ") }
+}
+
+fn yaml_error(e: serde_yaml::Error) -> ParseError {
+    let (line, column) = e.location().map_or((0, 0), |l| (l.line().saturating_sub(1), l.column().saturating_sub(1)));
+    ParseError { line, column, message: e.to_string() }
+}
+
+impl Document {
+    fn push(&mut self, key: Key, value: Value, depth: usize, parent: Option<usize>) -> usize {
+        let index = self.nodes.len();
+        let (kind, text, children) = match value {
+            Value::Null => (NodeKind::Null, "null".to_string(), Vec::new()),
+            Value::Bool(b) => (NodeKind::Bool, b.to_string(), Vec::new()),
+            Value::Number(n) => (NodeKind::Number, n, Vec::new()),
+            Value::String(s) => (NodeKind::String, s, Vec::new()),
+            Value::DateTime(d) => (NodeKind::DateTime, d, Vec::new()),
+            Value::Array(items) => (
+                NodeKind::Array,
+                String::new(),
+                items.into_iter().enumerate().map(|(i, v)| (Key::Index(i), v)).collect(),
+            ),
+            Value::Object(members) => (
+                NodeKind::Object,
+                String::new(),
+                members.into_iter().map(|(k, v)| (Key::Name(k), v)).collect(),
+            ),
+        };
+        self.nodes.push(Node {
+            key,
+            kind,
+            value: text,
+            depth,
+            parent,
+            children: Vec::new(),
+            subtree_end: index + 1,
+        });
+        for (key, value) in children {
+            let child = self.push(key, value, depth + 1, Some(index));
+            self.nodes[index].children.push(child);
+        }
+        self.nodes[index].subtree_end = self.nodes.len();
+        index
+    }
+
+    /// 节点的路径，如 `$.servers[0].name`
+    pub fn path(&self, index: usize) -> String {
+        let mut keys = Vec::new();
+        let mut current = Some(index);
+        while let Some(i) = current {
+            keys.push(&self.nodes[i].key);
+            current = self.nodes[i].parent;
+        }
+        let mut path = String::new();
+        for key in keys.into_iter().rev() {
+            match key {
+                Key::Root => path.push('$'),
+                Key::Index(i) => path.push_str(&format!("[{}]", i)),
+                Key::Name(name) if is_identifier(name) => {
+                    path.push('.');
+                    path.push_str(name);
+                }
+                Key::Name(name) => path.push_str(&format!("['{}']", name.replace('\'', "\\'"))),
+            }
+        }
+        path
+    }
+
+    /// 查询：以 `$` 开头时按路径匹配，否则查找键或值中包含该文本的节点
+    pub fn query(&self, query: &str) -> Result<Vec<usize>, String> {
+        let query = query.trim();
+        if !query.starts_with('$') {
+            let needle = query.to_lowercase();
+            return Ok((0..self.nodes.len())
+                .filter(|&i| {
+                    let node = &self.nodes[i];
+                    let key_matches = matches!(&node.key, Key::Name(name) if name.to_lowercase().contains(&needle));
+                    key_matches || node.value.to_lowercase().contains(&needle)
+                })
+                .collect());
+        }
+
+        let mut current = vec![0];
+        for segment in parse_query(query)? {
+            let mut next = Vec::new();
+            for index in current {
+                match &segment {
+                    Segment::Descendant(name) => {
+                        let node = &self.nodes[index];
+                        next.extend((index + 1..node.subtree_end).filter(|&i| match (name, &self.nodes[i].key) {
+                            (None, _) => true,
+                            (Some(name), Key::Name(key)) => key == name,
+                            _ => false,
+                        }));
+                    }
+                    _ => next.extend(self.nodes[index].children.iter().copied().filter(|&child| match (&segment, &self.nodes[child].key) {
+                        (Segment::Wildcard, _) => true,
+                        (Segment::Child(name), Key::Name(key)) => key == name,
+                        (Segment::Index(i), Key::Index(key)) => key == i,
+                        _ => false,
+                    })),
+                }
+            }
+            next.sort_unstable();
+            next.dedup();
+            current = next;
+        }
+        Ok(current)
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_') && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+}
+
+/// 路径的一段
+#[derive(Debug, PartialEq, Eq)]
+enum Segment {
+    Child(String),              // .name 或 ['name']
+    Index(usize),               // [0]
+    Wildcard,                   // .* 或 [*]
+    Descendant(Option<String>), // ..name 或 ..*
+}
+
+/// 解析查询路径：`$`、`.name`、`['name']`、`[0]`、`*`、`..name`
+fn parse_query(query: &str) -> Result<Vec<Segment>, String> {
+    let mut segments = Vec::new();
+    let mut rest = query.strip_prefix('$').ok_or("路径应以 $ 开头")?;
+    let name_len = |s: &str| s.find(['.', '[']).unwrap_or(s.len());
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix("..") {
+            let len = name_len(after);
+            let name = &after[..len];
+            segments.push(Segment::Descendant((name != "*" && !name.is_empty()).then(|| name.to_string())));
+            rest = &after[len..];
+        } else if let Some(after) = rest.strip_prefix('.') {
+            let len = name_len(after);
+            let name = &after[..len];
+            if name.is_empty() {
+                return Err("“.” 后缺少键名".to_string());
+            }
+            segments.push(if name == "*" { Segment::Wildcard } else { Segment::Child(name.to_string()) });
+            rest = &after[len..];
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let after = after.trim_start();
+            // 带引号的键先找到配对的引号（\ 转义其后的字符），键中可以包含 “]”
+            if let Some(quote) = after.chars().next().filter(|c| matches!(c, '\'' | '"')) {
+                let mut name = String::new();
+                let mut chars = after[1..].char_indices();
+                let close = loop {
+                    match chars.next() {
+                        Some((_, '\\')) => name.extend(chars.next().map(|(_, c)| c)),
+                        Some((index, c)) if c == quote => break index,
+                        Some((_, c)) => name.push(c),
+                        None => return Err(format!("缺少配对的引号 {}", quote)),
+                    }
+                };
+                rest = after[1 + close + 1..].trim_start().strip_prefix(']').ok_or("缺少 “]”")?;
+                segments.push(Segment::Child(name));
+                continue;
+            }
+            let end = after.find(']').ok_or("缺少 “]”")?;
+            let inner = after[..end].trim();
+            segments.push(if inner == "*" {
+                Segment::Wildcard
+            } else {
+                Segment::Index(inner.parse().map_err(|_| format!("无效的下标: {}", inner))?)
+            });
+            rest = &after[end + 1..];
+        } else {
+            return Err(format!("无法解析: {}", rest));
+        }
+    }
+    Ok(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JSON: &str = r#"{"servers": [{"name": "a", "port": 1}, {"name": "b", "port": 2}], "it's": {"a]b": true}}"#;
+
+    #[test]
+    fn parse_query_reads_all_segment_kinds() {
+        use Segment::*;
+        assert_eq!(parse_query("$").unwrap(), []);
+        assert_eq!(
+            parse_query("$.servers[0].name").unwrap(),
+            [Child("servers".to_string()), Index(0), Child("name".to_string())]
+        );
+        assert_eq!(parse_query("$.*[*]").unwrap(), [Wildcard, Wildcard]);
+        assert_eq!(parse_query("$..name").unwrap(), [Descendant(Some("name".to_string()))]);
+        assert_eq!(parse_query("$..*").unwrap(), [Descendant(None)]);
+        assert_eq!(parse_query("$[ 'a]b' ]").unwrap(), [Child("a]b".to_string())]);
+        assert_eq!(parse_query(r#"$["x.y"]"#).unwrap(), [Child("x.y".to_string())]);
+        assert_eq!(parse_query(r"$['it\'s']").unwrap(), [Child("it's".to_string())]);
+    }
+
+    #[test]
+    fn parse_query_reports_malformed_paths() {
+        assert!(parse_query("servers").is_err());
+        assert!(parse_query("$.").is_err());
+        assert!(parse_query("$[0").is_err());
+        assert!(parse_query("$[x]").is_err());
+        assert!(parse_query("$['abc").is_err());
+        assert!(parse_query("$['abc'").is_err());
+        assert!(parse_query("$['a']b']").is_err());
+    }
+
+    #[test]
+    fn query_selects_nodes_and_paths_round_trip() {
+        let document = parse(Format::Json, JSON).unwrap();
+        let paths = |query: &str| -> Vec<String> {
+            document.query(query).unwrap().into_iter().map(|index| document.path(index)).collect()
+        };
+        assert_eq!(paths("$.servers[1].name"), ["$.servers[1].name"]);
+        assert_eq!(paths("$.servers[*].port"), ["$.servers[0].port", "$.servers[1].port"]);
+        assert_eq!(paths("$..name"), ["$.servers[0].name", "$.servers[1].name"]);
+        assert_eq!(paths("$['it\\'s']['a]b']"), [r"$['it\'s']['a]b']"]);
+        assert!(paths("$.missing").is_empty());
+
+        // 路径可以作为查询再次找到同一个节点
+        for index in 0..document.nodes.len() {
+            assert_eq!(document.query(&document.path(index)).unwrap(), [index]);
+        }
+    }
+
+    #[test]
+    fn query_without_dollar_searches_keys_and_values() {
+        let document = parse(Format::Json, JSON).unwrap();
+        let found: Vec<String> = document.query("PORT").unwrap().into_iter().map(|i| document.path(i)).collect();
+        assert_eq!(found, ["$.servers[0].port", "$.servers[1].port"]);
+        assert_eq!(document.query("b").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn minify_reports_yaml_that_json_cannot_represent() {
+        assert_eq!(minify(Format::Json, "{ \"a\" : [1, 2] }").unwrap().unwrap(), r#"{"a":[1,2]}"#);
+        assert_eq!(minify(Format::Yaml, "a:\n  - 1\n  - x\n").unwrap().unwrap(), r#"{"a":[1,"x"]}"#);
+        assert!(minify(Format::Toml, "a = 1").is_none());
+
+        let error = minify(Format::Yaml, "a: 1\n1: b\n").unwrap().unwrap_err();
+        assert_eq!((error.line, error.column), (1, 0));
+        assert!(error.message.contains("不是字符串"));
+
+        let error = minify(Format::Yaml, "a:\n  b: !custom 1\n").unwrap().unwrap_err();
+        assert_eq!((error.line, error.column), (1, 5));
+        assert!(error.message.contains("!custom"));
+    }
+}
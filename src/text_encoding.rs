@@ -0,0 +1,90 @@
+// 编码：UTF-8
+// 文本编码：读取时自动识别、按指定编码重新解码以及保存时编码
+
+use encoding_rs::{Encoding, BIG5, EUC_KR, GB18030, GBK, SHIFT_JIS, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252};
+
+/// 支持手动切换的编码列表
+pub const SUPPORTED_ENCODINGS: &[&Encoding] = &[
+    UTF_8, GBK, GB18030, BIG5, SHIFT_JIS, EUC_KR, UTF_16LE, UTF_16BE, WINDOWS_1252,
+];
+
+/// 判断是否为二进制内容时检查的字节数
+const BINARY_SAMPLE_SIZE: usize = 8192;
+
+/// 自动识别编码：优先BOM，其次合法UTF-8，否则按GBK处理
+pub fn detect_encoding(bytes: &[u8]) -> &'static Encoding {
+    if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+        return encoding;
+    }
+    if std::str::from_utf8(bytes).is_ok() {
+        UTF_8
+    } else {
+        GBK
+    }
+}
+
+/// 判断是否为二进制内容：只检查开头的一部分
+pub fn is_binary(bytes: &[u8]) -> bool {
+    // 有 BOM 的 UTF-16 文本本身就含有 NUL
+    if Encoding::for_bom(bytes).is_some() {
+        return false;
+    }
+    let sample = &bytes[..bytes.len().min(BINARY_SAMPLE_SIZE)];
+    if sample.is_empty() {
+        return false;
+    }
+    if sample.contains(&0) {
+        return true;
+    }
+
+    // 控制字符过多（制表符、换行、换页和转义序列除外）
+    let control = sample
+        .iter()
+        .filter(|&&byte| byte < 0x20 && !matches!(byte, b'\t' | b'\n' | b'\r' | 0x0c | 0x1b))
+        .count();
+    if control * 10 > sample.len() {
+        return true;
+    }
+
+    // 大量字节既不是合法 UTF-8 也不是合法 GBK
+    let mut invalid = 0;
+    let mut rest = sample;
+    while let Err(e) = std::str::from_utf8(rest) {
+        let error_len = e.error_len().unwrap_or(rest.len() - e.valid_up_to());
+        invalid += error_len;
+        rest = &rest[e.valid_up_to() + error_len..];
+    }
+    if invalid * 10 <= sample.len() * 3 {
+        return false;
+    }
+    let (text, _) = GBK.decode_without_bom_handling(sample);
+    text.matches('\u{FFFD}').count() > sample.len() / 100 + 1
+}
+
+/// 按指定编码解码（存在BOM时以BOM为准）
+pub fn decode(bytes: &[u8], encoding: &'static Encoding) -> (String, &'static Encoding) {
+    let (text, actual, _had_errors) = encoding.decode(bytes);
+    (text.into_owned(), actual)
+}
+
+/// 读取文本文件并自动识别编码
+pub fn read_text_file(path: &std::path::Path) -> std::io::Result<String> {
+    let bytes = std::fs::read(path)?;
+    Ok(decode(&bytes, detect_encoding(&bytes)).0)
+}
+
+/// 按指定编码编码文本（encoding_rs不支持输出UTF-16，这里单独处理）
+pub fn encode(text: &str, encoding: &'static Encoding) -> Vec<u8> {
+    if encoding == UTF_16LE {
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+        bytes
+    } else if encoding == UTF_16BE {
+        let mut bytes = vec![0xFE, 0xFF];
+        bytes.extend(text.encode_utf16().flat_map(u16::to_be_bytes));
+        bytes
+    } else {
+        let (bytes, _, _) = encoding.encode(text);
+        bytes.into_owned()
+    }
+}
@@ -0,0 +1,188 @@
+// 编码：UTF-8
+// 自动换行：按列数拆分长行，以及换行后的显示行与可见行（折叠后）的换算
+
+use crate::folding;
+
+/// 换行的最小列数（窗口过窄时不再继续拆分）
+pub const MIN_COLUMNS: usize = 20;
+
+/// 字符占用的列数（中日韩文字和全角字符占两列，制表符见 tab_advance）
+pub fn char_columns(ch: char) -> usize {
+    match ch {
+        '\u{1100}'..='\u{115F}'
+        | '\u{2E80}'..='\u{303E}'
+        | '\u{3041}'..='\u{33FF}'
+        | '\u{3400}'..='\u{4DBF}'
+        | '\u{4E00}'..='\u{9FFF}'
+        | '\u{A000}'..='\u{A4CF}'
+        | '\u{AC00}'..='\u{D7A3}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{FE30}'..='\u{FE4F}'
+        | '\u{FF00}'..='\u{FF60}'
+        | '\u{FFE0}'..='\u{FFE6}'
+        | '\u{1F300}'..='\u{1F64F}'
+        | '\u{1F900}'..='\u{1F9FF}'
+        | '\u{20000}'..='\u{3FFFD}' => 2,
+        _ => 1,
+    }
+}
+
+/// 在第 column 列的制表符占用的列数（对齐到下一个制表位）
+pub fn tab_advance(column: usize, tab_width: usize) -> usize {
+    let tab_width = tab_width.max(1);
+    tab_width - column % tab_width
+}
+
+/// 一段行首文本之后的列号（制表符对齐到制表位，其他字符各占一列）
+pub fn display_column(prefix: &str, tab_width: usize) -> usize {
+    prefix.chars().fold(0, |column, ch| match ch {
+        '\t' => column + tab_advance(column, tab_width),
+        _ => column + 1,
+    })
+}
+
+/// 拆分后每个显示行的起始字节位置（第一项总是 0），优先在空白之后断开
+/// 制表位按整行的列计算，与渲染时一致
+pub fn row_starts(line: &str, columns: usize, tab_width: usize) -> Vec<usize> {
+    let columns = columns.max(1);
+    let mut starts = vec![0];
+    let mut row_start = (0, 0); // 当前显示行的起始（字节位置, 列）
+    let mut last_break = None; // 当前显示行中最后一个空白之后的位置
+    let mut column = 0;
+    for (index, ch) in line.char_indices() {
+        let w = match ch {
+            '\t' => tab_advance(column, tab_width),
+            _ => char_columns(ch),
+        };
+        if column + w - row_start.1 > columns && column > row_start.1 {
+            row_start = last_break.filter(|&(at, _)| at > row_start.0).unwrap_or((index, column));
+            starts.push(row_start.0);
+            last_break = None;
+        }
+        column += w;
+        if ch.is_whitespace() {
+            last_break = Some((index + ch.len_utf8(), column));
+        }
+    }
+    starts
+}
+
+/// 每个可见行（折叠后）的第一个显示行
+#[derive(Default)]
+pub struct WrapIndex {
+    offsets: Vec<usize>, // 最后一项为显示行总数
+}
+
+impl WrapIndex {
+    /// 按每个源文件行拆分出的显示行数建立索引，隐藏的行不计入
+    pub fn build(counts: &[usize], hidden: &[(usize, usize)]) -> Self {
+        let mut offsets = Vec::with_capacity(counts.len() + 1);
+        let mut total = 0;
+        let mut line = 0;
+        while line < counts.len() {
+            offsets.push(total);
+            total += counts[line];
+            line = folding::next_visible_line(line, hidden);
+        }
+        offsets.push(total);
+        Self { offsets }
+    }
+
+    /// 显示行总数
+    pub fn total(&self) -> usize {
+        self.offsets.last().copied().unwrap_or(0)
+    }
+
+    /// 可见行的第一个显示行
+    pub fn row_start(&self, row: usize) -> usize {
+        self.offsets[row.min(self.offsets.len() - 1)]
+    }
+
+    /// 显示行所在的可见行，以及它是该行拆分出的第几个显示行
+    pub fn locate(&self, display_row: usize) -> (usize, usize) {
+        let row = self.offsets.partition_point(|&offset| offset <= display_row).saturating_sub(1);
+        let row = row.min(self.offsets.len().saturating_sub(2));
+        (row, display_row.saturating_sub(self.offsets[row]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tab_stops_and_display_columns() {
+        assert_eq!(tab_advance(0, 4), 4);
+        assert_eq!(tab_advance(3, 4), 1);
+        assert_eq!(tab_advance(4, 4), 4);
+        assert_eq!(tab_advance(5, 0), 1);
+        assert_eq!(display_column("", 4), 0);
+        assert_eq!(display_column("ab\t", 4), 4);
+        assert_eq!(display_column("ab\tc\t", 8), 16);
+        assert_eq!(display_column("\t\t", 2), 4);
+    }
+
+    #[test]
+    fn row_starts_breaks_after_whitespace() {
+        assert_eq!(row_starts("", 5, 4), [0]);
+        assert_eq!(row_starts("short", 5, 4), [0]);
+        assert_eq!(row_starts("aaaa bbbb", 5, 4), [0, 5]);
+        // 没有空白时按列数硬拆分
+        assert_eq!(row_starts("abcdefgh", 3, 4), [0, 3, 6]);
+    }
+
+    #[test]
+    fn row_starts_counts_wide_characters_as_two_columns() {
+        assert_eq!(char_columns('中'), 2);
+        assert_eq!(char_columns('a'), 1);
+        // 每个汉字三个字节、两列
+        assert_eq!(row_starts("中文字符", 5, 4), [0, 6]);
+        assert_eq!(row_starts("ab中", 3, 4), [0, 2]);
+    }
+
+    #[test]
+    fn row_starts_expands_tabs_by_line_column() {
+        // 第二个制表符在第 6 列，对齐到第 8 列，超出 6 列后从第一个制表符之后断开
+        assert_eq!(row_starts("\tab\tcd", 6, 4), [0, 1]);
+        // 制表符宽 2 列时整行 8 列，从第二个制表符之后断开
+        assert_eq!(row_starts("\tab\tcd", 6, 2), [0, 4]);
+        assert_eq!(row_starts("\tab\tcd", 8, 2), [0]);
+        assert_eq!(row_starts("\t\t\t", 4, 4), [0, 1, 2]);
+    }
+
+    #[test]
+    fn wrap_index_without_hidden_lines() {
+        let index = WrapIndex::build(&[2, 1, 3], &[]);
+        assert_eq!(index.total(), 6);
+        assert_eq!(index.row_start(1), 2);
+        assert_eq!(index.row_start(2), 3);
+        assert_eq!(index.locate(0), (0, 0));
+        assert_eq!(index.locate(1), (0, 1));
+        assert_eq!(index.locate(2), (1, 0));
+        assert_eq!(index.locate(5), (2, 2));
+        for row in 0..3 {
+            assert_eq!(index.locate(index.row_start(row)), (row, 0));
+        }
+    }
+
+    #[test]
+    fn wrap_index_skips_hidden_lines() {
+        // 第 1、2 行折叠在第 0 行中，只有第 0、3、4 行可见
+        let index = WrapIndex::build(&[1, 3, 1, 2, 1], &[(1, 2)]);
+        assert_eq!(index.total(), 4);
+        assert_eq!(index.row_start(1), 1);
+        assert_eq!(index.row_start(2), 3);
+        assert_eq!(index.locate(1), (1, 0));
+        assert_eq!(index.locate(2), (1, 1));
+        assert_eq!(index.locate(3), (2, 0));
+        assert_eq!(folding::row_to_line(1, &[(1, 2)]), 3);
+    }
+
+    #[test]
+    fn empty_wrap_index() {
+        let index = WrapIndex::build(&[], &[]);
+        assert_eq!(index.total(), 0);
+        assert_eq!(index.row_start(0), 0);
+        assert_eq!(index.locate(0), (0, 0));
+    }
+}